@@ -1,3 +1,29 @@
+use std::process::Command;
+
 fn main() {
+    // 构建信息供 get_build_info 使用，拿不到 git 信息时回退为 "unknown"
+    let git_hash = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|hash| hash.trim().to_string())
+        .filter(|hash| !hash.is_empty())
+        .unwrap_or_else(|| String::from("unknown"));
+
+    println!("cargo:rustc-env=CODE_CORPSES_GIT_HASH={}", git_hash);
+    println!(
+        "cargo:rustc-env=CODE_CORPSES_TARGET={}",
+        std::env::var("TARGET").unwrap_or_else(|_| String::from("unknown"))
+    );
+    println!(
+        "cargo:rustc-env=CODE_CORPSES_PROFILE={}",
+        std::env::var("PROFILE").unwrap_or_else(|_| String::from("unknown"))
+    );
+    if std::path::Path::new("../../.git/HEAD").exists() {
+        println!("cargo:rerun-if-changed=../../.git/HEAD");
+    }
+
     tauri_build::build()
 }
@@ -1,18 +1,55 @@
 #![allow(unused)]
-use tauri::Manager;
+use tauri::{Emitter, Manager};
 use serde::{Serialize, Deserialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
-use std::path::PathBuf;
-use chrono::{DateTime, Utc, Duration};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc, Duration};
 
 // ========== 数据结构 ==========
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
 pub struct Config {
     pub github_token: Option<String>,
     pub target_org: String,
     pub scan_interval: u64,
     pub auto_start: bool,
+    pub similarity_algo: SimAlgo,
+    pub report_webhook: Option<String>,
+    pub locale: String,
+    pub max_registry_bytes: u64,
+    pub include_private: bool,
+    pub compress_data: bool,
+    pub resurrection_min_count: usize,
+    pub epitaph_templates_path: Option<String>,
+    pub prune_orphan_alerts_on_load: bool,
+    pub target_org_verified: bool,
+    pub notification_cooldown_secs: u64,
+    pub rename_similarity_threshold: f64,
+    pub ignore_globs: Vec<String>,
+    pub external_scanner_cmd: Option<String>,
+    pub scan_concurrency: usize,
+    pub orgs: Vec<OrgConfig>,
+    pub display_timezone: DisplayTz,
+    // 为空时使用 code-corpses/<版本号>；公司代理拦截陌生 UA 时可以改
+    pub user_agent: Option<String>,
+    pub request_timeout_secs: u64,
+    pub retention_days: Option<u32>,
+    pub retention_action: RetentionAction,
+    pub tray_menu_items: Vec<TrayItem>,
+    pub webhook_secret: Option<String>,
+    pub death_rules: Vec<DeathRule>,
+}
+
+// 额外监控的组织；interval_secs 为空时使用全局 scan_interval
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct OrgConfig {
+    pub org: String,
+    #[serde(default)]
+    pub interval_secs: Option<u64>,
 }
 
 impl Default for Config {
@@ -22,6 +59,30 @@ impl Default for Config {
             target_org: "microsoft".to_string(),
             scan_interval: 3600,
             auto_start: false,
+            similarity_algo: SimAlgo::default(),
+            report_webhook: None,
+            locale: default_locale(),
+            max_registry_bytes: DEFAULT_MAX_DATA_BYTES,
+            include_private: true,
+            compress_data: false,
+            resurrection_min_count: 3,
+            epitaph_templates_path: None,
+            prune_orphan_alerts_on_load: false,
+            target_org_verified: false,
+            notification_cooldown_secs: DEFAULT_NOTIFICATION_COOLDOWN_SECS,
+            rename_similarity_threshold: DEFAULT_RENAME_SIMILARITY_THRESHOLD,
+            ignore_globs: vec![],
+            external_scanner_cmd: None,
+            scan_concurrency: DEFAULT_SCAN_CONCURRENCY,
+            orgs: vec![],
+            display_timezone: DisplayTz::default(),
+            user_agent: None,
+            request_timeout_secs: DEFAULT_REQUEST_TIMEOUT_SECS,
+            retention_days: None,
+            retention_action: RetentionAction::default(),
+            tray_menu_items: default_tray_menu_items(),
+            webhook_secret: None,
+            death_rules: vec![],
         }
     }
 }
@@ -40,6 +101,26 @@ pub struct Tombstone {
     pub died_at: String,
     pub resurrected_at: Option<String>,
     pub resurrected_to: Option<String>,
+    #[serde(default)]
+    pub repo: Option<String>,
+    #[serde(default)]
+    pub pinned: bool,
+    #[serde(default)]
+    pub content_hash: Option<String>,
+    #[serde(default)]
+    pub attachments: Vec<Attachment>,
+    // 最后改动（通常就是删除）该文件的提交作者；只有本地 git 仓库的扫描能拿到
+    #[serde(default)]
+    pub author: Option<String>,
+}
+
+// 墓碑附件（报错截图、事后复盘笔记等）；path 相对于墓地根目录
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Attachment {
+    pub kind: String,
+    pub path: String,
+    #[serde(default)]
+    pub caption: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -52,6 +133,26 @@ pub struct Asset {
     pub tags: Vec<String>,
     pub alive: bool,
     pub line_count: usize,
+    // 测试、fixture、生成代码等预期会反复出现/消失的文件，删除后不立碑
+    #[serde(default)]
+    pub exempt: bool,
+    #[serde(default)]
+    pub content_hash: Option<String>,
+    // 旧索引没有这个字段，读出来为 None，不做回填；之后的扫描按仓库列表补上
+    #[serde(default)]
+    pub repo: Option<String>,
+    #[serde(default)]
+    pub author: Option<String>,
+    // TypeScript 索引器写入的最后修改时间
+    #[serde(default, alias = "updatedAt")]
+    pub updated_at: Option<String>,
+    // 远程扫描时 GitHub 文件树给出的 git blob sha，用来识别移动过的文件
+    #[serde(default)]
+    pub blob_sha: Option<String>,
+    // 已经立过碑的资产（TypeScript 端同样写 tombstoneId）。墓碑之后被归档、删除，
+    // 也不会因为资产仍是 alive = false 而在下次扫描时重新立碑
+    #[serde(default, alias = "tombstoneId")]
+    pub tombstone_id: Option<String>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -69,19 +170,67 @@ pub struct ScanResult {
     pub success: bool,
     pub scanned: usize,
     pub zombies: usize,
+    pub repos: usize,
     pub message: String,
 }
 
 // ========== 路径工具 ==========
 
+// 应用目录的来源：环境变量 → 系统目录 → $HOME/.code-corpses → 当前目录下的 ./.code-corpses（会打印警告）
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DirSource {
+    Env,
+    Os,
+    Home,
+    Cwd,
+}
+
+// 非空的目录类环境变量；用于把应用指向独立的目录（演示、复现问题、测试）
+fn env_dir(var: &str) -> Option<PathBuf> {
+    std::env::var_os(var).filter(|v| !v.is_empty()).map(PathBuf::from)
+}
+
+fn resolve_app_dir(kind: &str, env_var: &str, os_dir: Option<PathBuf>) -> (PathBuf, DirSource) {
+    if let Some(dir) = env_dir(env_var) {
+        return (dir, DirSource::Env);
+    }
+    if let Some(dir) = os_dir {
+        return (dir.join("code-corpses"), DirSource::Os);
+    }
+    if let Some(home) = dirs::home_dir() {
+        return (home.join(".code-corpses"), DirSource::Home);
+    }
+    let fallback = std::env::current_dir()
+        .unwrap_or_else(|_| PathBuf::from("."))
+        .join(".code-corpses");
+    eprintln!(
+        "⚠️ 找不到系统{}目录和 HOME，改用 {}",
+        kind,
+        fallback.display()
+    );
+    (fallback, DirSource::Cwd)
+}
+
+fn app_config_dir() -> &'static (PathBuf, DirSource) {
+    static DIR: OnceLock<(PathBuf, DirSource)> = OnceLock::new();
+    DIR.get_or_init(|| resolve_app_dir("配置", "CODE_CORPSES_CONFIG_DIR", dirs::config_dir()))
+}
+
+fn app_data_dir() -> &'static (PathBuf, DirSource) {
+    static DIR: OnceLock<(PathBuf, DirSource)> = OnceLock::new();
+    DIR.get_or_init(|| resolve_app_dir("数据", "CODE_CORPSES_DATA_DIR", dirs::data_dir()))
+}
+
 fn get_config_path() -> PathBuf {
-    let mut path = dirs::config_dir().unwrap_or_else(|| PathBuf::from("."));
-    path.push("code-corpses");
-    path.push("cemetery.config.json");
-    path
+    app_config_dir().0.join("cemetery.config.json")
 }
 
 fn get_base_path() -> PathBuf {
+    // CODE_CORPSES_BASE_DIR 直接指定墓地根目录（.cemetery 的上一级）
+    if let Some(base) = env_dir("CODE_CORPSES_BASE_DIR") {
+        return base;
+    }
     // 尝试从当前工作目录查找 .cemetery 目录
     let mut base = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
     if !base.join(".cemetery").exists() {
@@ -98,367 +247,645 @@ fn get_asset_index_path() -> PathBuf {
     get_base_path().join(".cemetery/asset-index.json")
 }
 
+fn get_cemetery_ignore_path() -> PathBuf {
+    get_base_path().join(".cemetery/.cemeteryignore")
+}
+
 fn get_tombstone_registry_path() -> PathBuf {
     get_base_path().join(".cemetery/tombstone-registry.json")
 }
 
-// ========== 配置命令 ==========
+fn get_archive_path() -> PathBuf {
+    get_base_path().join(".cemetery/archive.json")
+}
 
-#[tauri::command]
-pub fn load_config() -> Result<Config, String> {
-    let path = get_config_path();
-    
-    if path.exists() {
-        let content = fs::read_to_string(&path)
-            .map_err(|e| format!("读取配置失败: {}", e))?;
-        serde_json::from_str(&content)
-            .map_err(|e| format!("解析配置失败: {}", e))
-    } else {
-        let default_config = Config::default();
-        save_config(&default_config)?;
-        Ok(default_config)
-    }
+fn get_backup_dir() -> PathBuf {
+    get_base_path().join(".cemetery/backups")
 }
 
-#[tauri::command]
-pub fn save_config(config: &Config) -> Result<(), String> {
-    let path = get_config_path();
-    
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent)
-            .map_err(|e| format!("创建配置目录失败: {}", e))?;
-    }
-    
-    let content = serde_json::to_string_pretty(config)
-        .map_err(|e| format!("序列化配置失败: {}", e))?;
-    
-    fs::write(&path, content)
-        .map_err(|e| format!("写入配置失败: {}", e))?;
-    
-    Ok(())
+fn get_undo_stack_path() -> PathBuf {
+    get_base_path().join(".cemetery/undo-stack.json")
 }
 
-#[tauri::command]
-pub fn update_github_token(token: String) -> Result<(), String> {
-    let mut config = load_config()?;
-    config.github_token = Some(token);
-    save_config(&config)
+fn get_stats_history_path() -> PathBuf {
+    get_base_path().join(".cemetery/stats-history.json")
 }
 
-// ========== 墓地数据命令 ==========
+#[derive(Serialize, Deserialize)]
+pub struct AppPaths {
+    pub config_dir: PathBuf,
+    pub config_dir_source: DirSource,
+    pub data_dir: PathBuf,
+    pub data_dir_source: DirSource,
+    pub base_path: PathBuf,
+    pub config_file: PathBuf,
+    pub tombstone_registry: PathBuf,
+    pub asset_index: PathBuf,
+    pub zombie_alerts: PathBuf,
+}
 
+// 告诉用户数据实际放在哪里，以及目录是从哪一级回退得到的
 #[tauri::command]
-pub fn get_stats() -> Stats {
-    let asset_path = get_asset_index_path();
-    let tombstone_path = get_tombstone_registry_path();
-    
-    let mut total_assets = 0;
-    let mut alive_assets = 0;
-    let mut total_tombstones = 0;
-    let mut resurrected = 0;
-    let mut last_scan = String::from("未知");
-
-    // 读取资产
-    if asset_path.exists() {
-        if let Ok(content) = fs::read_to_string(&asset_path) {
-            if let Ok(assets) = serde_json::from_str::<Vec<Asset>>(&content) {
-                total_assets = assets.len();
-                alive_assets = assets.iter().filter(|a| a.alive).count();
-                
-                // 获取最后更新时间
-                if let Ok(metadata) = fs::metadata(&asset_path) {
-                    if let Ok(modified) = metadata.modified() {
-                        if let Ok(dt) = DateTime::<Utc>::from_system_time(modified) {
-                            last_scan = dt.format("%Y-%m-%d %H:%M:%S").to_string();
-                        }
-                    }
-                }
-            }
-        }
-    }
-
-    // 读取墓碑
-    if tombstone_path.exists() {
-        if let Ok(content) = fs::read_to_string(&tombstone_path) {
-            if let Ok(tombstones) = serde_json::from_str::<Vec<Tombstone>>(&content) {
-                total_tombstones = tombstones.len();
-                resurrected = tombstones.iter().filter(|t| t.resurrected_at.is_some()).count();
-            }
-        }
+pub fn get_paths() -> AppPaths {
+    let (config_dir, config_dir_source) = app_config_dir().clone();
+    let (data_dir, data_dir_source) = app_data_dir().clone();
+    AppPaths {
+        config_dir,
+        config_dir_source,
+        data_dir,
+        data_dir_source,
+        base_path: get_base_path(),
+        config_file: get_config_path(),
+        tombstone_registry: get_tombstone_registry_path(),
+        asset_index: get_asset_index_path(),
+        zombie_alerts: get_zombie_alerts_path(),
     }
+}
 
-    Stats {
-        total_assets,
-        alive_assets,
-        dead_assets: total_assets - alive_assets,
-        total_tombstones,
-        resurrected,
-        last_scan,
-    }
+// 受 compress_data 管理的数据文件（配置文件本身始终是明文）
+fn data_file_paths() -> Vec<PathBuf> {
+    vec![
+        get_tombstone_registry_path(),
+        get_asset_index_path(),
+        get_zombie_alerts_path(),
+        get_archive_path(),
+        get_undo_stack_path(),
+        get_stats_history_path(),
+    ]
 }
 
-#[tauri::command]
-pub fn get_recent_corpses(limit: i32) -> Vec<Tombstone> {
-    let tombstone_path = get_tombstone_registry_path();
-    
-    if !tombstone_path.exists() {
-        return get_mock_corpses();
-    }
-    
-    if let Ok(content) = fs::read_to_string(&tombstone_path) {
-        if let Ok(mut tombstones) = serde_json::from_str::<Vec<Tombstone>>(&content) {
-            // 按死亡日期排序
-            tombstones.sort_by(|a, b| b.died_at.cmp(&a.died_at));
-            return tombstones.into_iter().take(limit as usize).collect();
-        }
-    }
-    
-    get_mock_corpses()
+// ========== 存储工具 ==========
+
+const DEFAULT_MAX_DATA_BYTES: u64 = 100 * 1024 * 1024;
+
+// 读写数据文件用到的两项配置。每次读写开头取一次，再传给下面的工具函数，
+// 避免一次操作里反复读取、解析配置文件
+#[derive(Clone, Copy)]
+struct DataSettings {
+    max_bytes: u64,
+    compress: bool,
 }
 
-// 后备模拟数据
-fn get_mock_corpses() -> Vec<Tombstone> {
-    vec![
-        Tombstone {
-            id: String::from("regex-validator"),
-            name: String::from("RegEx 验证码解析器"),
-            cause_of_death: String::from("被滑块验证干掉了"),
-            epitaph: String::from("它曾经能识别99%的验证码，直到验证码学会了自我进化"),
-            tags: vec![String::from("rust"), String::from("validator")],
-            original_path: String::from("src/utils/regex-validator.ts"),
-            language: Some(String::from("Rust")),
-            line_count: 256,
-            died_at: String::from("2024-03-15T00:00:00Z"),
-            resurrected_at: None,
-            resurrected_to: None,
-        },
-        Tombstone {
-            id: String::from("vue2-admin"),
-            name: String::from("Vue 2.0 管理系统"),
-            cause_of_death: String::from("Vue 3发布了"),
-            epitaph: String::from("Composition API 永不为奴！"),
-            tags: vec![String::from("vue"), String::from("admin")],
-            original_path: String::from("packages/admin/src/main.ts"),
-            language: Some(String::from("Vue")),
-            line_count: 1542,
-            died_at: String::from("2023-01-07T00:00:00Z"),
-            resurrected_at: None,
-            resurrected_to: None,
+fn data_settings() -> DataSettings {
+    match resolve_config() {
+        Ok(config) => DataSettings {
+            max_bytes: config.max_registry_bytes,
+            compress: config.compress_data,
         },
-        Tombstone {
-            id: String::from("jquery-branch"),
-            name: String::from("JQuery 分支"),
-            cause_of_death: String::from("IE11终于死了"),
-            epitaph: String::from("RIP IE, 你终于走了"),
-            tags: vec![String::from("javascript"), String::from("jquery")],
-            original_path: String::from("src/legacy/jquery-bridge.js"),
-            language: Some(String::from("JavaScript")),
-            line_count: 892,
-            died_at: String::from("2022-06-15T00:00:00Z"),
-            resurrected_at: None,
-            resurrected_to: None,
+        Err(_) => DataSettings {
+            max_bytes: DEFAULT_MAX_DATA_BYTES,
+            compress: false,
         },
-    ]
+    }
 }
 
-// ========== 扫描命令 ==========
+fn gz_path(path: &Path) -> PathBuf {
+    let mut gz = path.as_os_str().to_owned();
+    gz.push(".gz");
+    PathBuf::from(gz)
+}
 
-#[tauri::command]
-pub async fn trigger_scan() -> Result<ScanResult, String> {
-    println!("🔄 开始扫描本地墓地...");
-    
-    // 重新读取数据
-    let stats = get_stats();
-    
-    let zombies = stats.total_tombstones;
-    let scanned = stats.total_assets;
-    
-    println!("✅ 扫描完成！发现 {} 个墓碑", zombies);
-    
-    Ok(ScanResult {
-        success: true,
-        scanned,
-        zombies,
-        message: format!("扫描完成！发现 {} 个墓碑", zombies),
-    })
+fn is_gz(path: &Path) -> bool {
+    path.extension().map_or(false, |ext| ext == "gz")
 }
 
-// ========== 诈尸提醒功能 ==========
+// 数据文件可能以明文或 .gz 形式存在：两者都在时（切换压缩选项的中途）优先当前配置对应的那个
+fn resolve_data_path(path: &Path, compress: bool) -> Option<PathBuf> {
+    let gz = gz_path(path);
+    let (preferred, fallback) = if compress {
+        (gz, path.to_path_buf())
+    } else {
+        (path.to_path_buf(), gz)
+    };
+    [preferred, fallback].into_iter().find(|p| p.exists())
+}
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
-pub struct ZombieAlert {
-    pub id: String,
-    pub corpse_repo: String,
-    pub corpse_path: String,
-    pub zombie_repo: String,
-    pub zombie_path: String,
-    pub similarity: f64,
-    pub resurrection_type: String,
-    pub confidence: f64,
-    pub detected_at: String,
-    pub notified: bool,
+// 存在与否和优先哪种形式无关，不需要读配置
+fn data_file_exists(path: &Path) -> bool {
+    path.exists() || gz_path(path).exists()
 }
 
-#[derive(Serialize, Deserialize)]
-pub struct ZombieAlerts {
-    pub alerts: Vec<ZombieAlert>,
-    pub last_check: String,
-    pub total_alerts: usize,
-    pub unread_count: usize,
+// 所有数据文件（注册表、资产索引、诈尸提醒）都经这里读取：
+// 先用 metadata 检查大小，超过 max_registry_bytes 直接拒绝，避免把超大文件整个读进内存；
+// .gz 文件透明解压，解压后的内容同样受上限约束
+fn read_data_file(path: &Path) -> Result<String, String> {
+    let settings = data_settings();
+    let source = resolve_data_path(path, settings.compress)
+        .ok_or_else(|| format!("文件不存在: {}", path.display()))?;
+    let limit = settings.max_bytes;
+    let too_large = |size: u64| {
+        format!(
+            "{} 大小 {} MB 超过上限 {} MB，已拒绝加载。请先归档旧墓碑，或调大 max_registry_bytes",
+            source.display(),
+            size / 1024 / 1024,
+            limit / 1024 / 1024
+        )
+    };
+
+    let size = fs::metadata(&source)
+        .map_err(|e| format!("读取 {} 信息失败: {}", source.display(), e))?
+        .len();
+    if size > limit {
+        return Err(too_large(size));
+    }
+
+    if !is_gz(&source) {
+        return fs::read_to_string(&source)
+            .map_err(|e| format!("读取 {} 失败: {}", source.display(), e));
+    }
+
+    let file = fs::File::open(&source)
+        .map_err(|e| format!("读取 {} 失败: {}", source.display(), e))?;
+    let mut content = String::new();
+    flate2::read::GzDecoder::new(file)
+        .take(limit + 1)
+        .read_to_string(&mut content)
+        .map_err(|e| format!("解压 {} 失败: {}", source.display(), e))?;
+    if content.len() as u64 > limit {
+        return Err(too_large(content.len() as u64));
+    }
+    Ok(content)
 }
 
-fn get_zombie_alerts_path() -> PathBuf {
-    let mut path = dirs::data_dir().unwrap_or_else(|| PathBuf::from("."));
-    path.push("code-corpses");
-    path.push("zombie-alerts.json");
-    path
+fn read_tombstones_from(path: &Path) -> Result<Vec<Tombstone>, String> {
+    if !data_file_exists(path) {
+        return Ok(vec![]);
+    }
+    let content = read_data_file(path)?;
+    serde_json::from_str(&content)
+        .map_err(|e| format!("解析墓碑失败: {}", e))
 }
 
-#[tauri::command]
-pub fn get_zombie_alerts() -> ZombieAlerts {
-    let path = get_zombie_alerts_path();
+fn read_tombstones() -> Result<Vec<Tombstone>, String> {
+    read_tombstones_from(&get_tombstone_registry_path())
+}
 
-    if let Ok(content) = fs::read_to_string(&path) {
-        if let Ok(data) = serde_json::from_str::<serde_json::Value>(&content) {
-            let alerts: Vec<ZombieAlert> = data["alerts"]
-                .as_array()
-                .map(|arr| {
-                    arr.iter()
-                        .filter_map(|v| serde_json::from_value(v.clone()).ok())
-                        .collect()
-                })
-                .unwrap_or_default();
+fn read_assets() -> Result<Vec<Asset>, String> {
+    let path = get_asset_index_path();
+    if !data_file_exists(&path) {
+        return Ok(vec![]);
+    }
+    let content = read_data_file(&path)?;
+    serde_json::from_str(&content)
+        .map_err(|e| format!("解析资产索引失败: {}", e))
+}
 
-            let unread_count = alerts.iter().filter(|a| !a.notified).count();
+// 先写 *.tmp 再 rename，进程中途崩溃也不会截断原文件
+fn write_bytes_atomic(path: &Path, bytes: &[u8]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("创建目录失败: {}", e))?;
+    }
 
-            return ZombieAlerts {
-                alerts,
-                last_check: data["last_check"].as_str().unwrap_or("从未检查").to_string(),
-                total_alerts: alerts.len(),
-                unread_count,
-            };
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+
+    fs::write(&tmp, bytes)
+        .map_err(|e| format!("写入临时文件失败: {}", e))?;
+    fs::rename(&tmp, path)
+        .map_err(|e| format!("替换文件失败: {}", e))
+}
+
+fn gzip_bytes(bytes: &[u8]) -> Result<Vec<u8>, String> {
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder
+        .write_all(bytes)
+        .and_then(|_| encoder.finish())
+        .map_err(|e| format!("压缩失败: {}", e))
+}
+
+// 按目标形式写入数据文件，并删除另一种形式，保证磁盘上只有一份
+fn write_data_bytes(path: &Path, bytes: &[u8], compress: bool) -> Result<(), String> {
+    let gz = gz_path(path);
+    if compress {
+        write_bytes_atomic(&gz, &gzip_bytes(bytes)?)?;
+        if path.exists() {
+            fs::remove_file(path).map_err(|e| format!("删除旧文件失败: {}", e))?;
+        }
+    } else {
+        write_bytes_atomic(path, bytes)?;
+        if gz.exists() {
+            fs::remove_file(&gz).map_err(|e| format!("删除旧文件失败: {}", e))?;
         }
     }
+    Ok(())
+}
 
-    ZombieAlerts {
-        alerts: vec![],
-        last_check: String::from("从未检查"),
-        total_alerts: 0,
-        unread_count: 0,
+fn write_json_atomic<T: Serialize + ?Sized>(path: &Path, value: &T) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(value)
+        .map_err(|e| format!("序列化失败: {}", e))?;
+    if path == get_tombstone_registry_path() {
+        mark_own_registry_write();
     }
-}
+    write_data_bytes(path, content.as_bytes(), data_settings().compress)?;
 
-#[tauri::command]
-pub fn mark_alert_read(alert_id: String) -> Result<(), String> {
-    let path = get_zombie_alerts_path();
-    
-    if !path.exists() {
-        return Ok(());
+    if affects_stats(path) {
+        emit_stats_updated();
     }
-    
-    let content = fs::read_to_string(&path)
-        .map_err(|e| e.to_string())?;
-    
-    let mut data: serde_json::Value = serde_json::from_str(&content)
-        .map_err(|e| e.to_string())?;
-    
-    if let Some(alerts) = data["alerts"].as_array_mut() {
-        for alert in alerts {
-            if alert["id"] == alert_id {
-                alert["notified"] = serde_json::json!(true);
-                break;
+    if path == get_zombie_alerts_path() {
+        if let Some(app) = APP_HANDLE.get() {
+            if let Err(e) = refresh_tray_badge(app.clone()) {
+                eprintln!("⚠️ 更新托盘角标失败: {}", e);
             }
         }
     }
-    
-    fs::write(&path, serde_json::to_string_pretty(&data).unwrap())
-        .map_err(|e| e.to_string())?;
-    
     Ok(())
 }
 
-#[tauri::command]
-pub fn clear_all_alerts() -> Result<(), String> {
-    let alerts_data = ZombieAlerts {
-        alerts: vec![],
-        last_check: Utc::now().to_rfc3339(),
-        total_alerts: 0,
-        unread_count: 0,
+// 启动时记录，供存储层在数据变化后推送事件
+static APP_HANDLE: OnceLock<tauri::AppHandle> = OnceLock::new();
+
+fn affects_stats(path: &Path) -> bool {
+    [
+        get_tombstone_registry_path(),
+        get_asset_index_path(),
+        get_zombie_alerts_path(),
+    ]
+    .iter()
+    .any(|p| p == path)
+}
+
+// 注册表、资产索引、提醒的每次写入都推送最新统计，前端据此更新而不必轮询 get_stats
+fn emit_stats_updated() {
+    if let Some(app) = APP_HANDLE.get() {
+        if let Err(e) = app.emit("stats-updated", get_stats()) {
+            eprintln!("⚠️ 推送 stats-updated 失败: {}", e);
+        }
+    }
+}
+
+// 扫描中每立一块新墓碑推送一次 tombstone-created，前端可以直接插到列表顶部。
+// 调用时注册表已经写入，推送出去的墓碑不会因为扫描后续步骤崩溃而丢失
+fn emit_tombstones_created(created: &[Tombstone]) {
+    let Some(app) = APP_HANDLE.get() else {
+        return;
     };
-    
-    let path = get_zombie_alerts_path();
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent).ok();
+    for t in created {
+        if let Err(e) = app.emit("tombstone-created", t) {
+            eprintln!("⚠️ 推送 tombstone-created 失败: {}", e);
+        }
     }
-    
-    let content = serde_json::to_string_pretty(&alerts_data)
-        .map_err(|e| e.to_string())?;
-    
-    fs::write(&path, content)
-        .map_err(|e| e.to_string())?;
-    
-    Ok(())
 }
 
-// ========== 实用命令 ==========
+// compress_data 切换后把现有数据文件转换成对应形式
+fn migrate_data_compression(compress: bool) -> Result<usize, String> {
+    let mut migrated = 0;
+    for path in data_file_paths() {
+        let _lock = lock_data_file(&path)?;
+        let from = if compress { path.clone() } else { gz_path(&path) };
+        if !from.exists() {
+            continue;
+        }
+        let mut content = vec![];
+        let file = fs::File::open(&from)
+            .map_err(|e| format!("读取 {} 失败: {}", from.display(), e))?;
+        let read = if is_gz(&from) {
+            flate2::read::GzDecoder::new(file).read_to_end(&mut content)
+        } else {
+            std::io::BufReader::new(file).read_to_end(&mut content)
+        };
+        read.map_err(|e| format!("读取 {} 失败: {}", from.display(), e))?;
 
-#[tauri::command]
-pub fn log_message(message: String) {
-    println!("{}", message);
+        write_data_bytes(&path, &content, compress)?;
+        migrated += 1;
+    }
+    Ok(migrated)
 }
 
-#[tauri::command]
-pub fn get_version() -> String {
-    env!("CARGO_PKG_VERSION").to_string()
+fn write_tombstones(tombstones: &[Tombstone]) -> Result<(), String> {
+    write_json_atomic(&get_tombstone_registry_path(), tombstones)
 }
 
-#[tauri::command]
-pub fn set_autostart(enabled: bool) -> Result<(), String> {
-    #[cfg(target_os = "macos")]
-    {
-        use std::process::Command;
-        let app_path = std::env::current_executable()
-            .map_err(|e| e.to_string())?
-            .to_string_lossy()
-            .into_owned();
-        
-        if enabled {
-            let script = format!(
-                r#"tell application "System Events" to make login item at end with properties {{path: "{}", name: "Code Corpses", hidden: false}}"#,
-                app_path
-            );
-            Command::new("osascript")
-                .args(&["-e", &script])
-                .output()
-                .map_err(|e| e.to_string())?;
-        } else {
-            let script = r#"tell application "System Events" to delete login item "Code Corpses""#;
-            Command::new("osascript")
-                .args(&["-e", script])
-                .output()
-                .ok();
-        }
+// 数据文件的建议锁：<文件>.lock 上的 fs2 独占锁，对线程和其他进程都生效。
+// 所有 读-改-写 序列开头持有它，guard 离开作用域自动解锁。
+// 同一文件不可嵌套加锁；涉及多个文件时按 注册表 → 资产索引 / 归档 → 撤销栈 / 提醒 的顺序加锁
+pub struct DataFileLock {
+    file: fs::File,
+}
+
+impl Drop for DataFileLock {
+    fn drop(&mut self) {
+        fs2::FileExt::unlock(&self.file).ok();
+    }
+}
+
+fn lock_data_file(path: &Path) -> Result<DataFileLock, String> {
+    let mut lock_path = path.as_os_str().to_owned();
+    lock_path.push(".lock");
+    let lock_path = PathBuf::from(lock_path);
+
+    if let Some(parent) = lock_path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("创建目录失败: {}", e))?;
+    }
+    let file = fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .open(&lock_path)
+        .map_err(|e| format!("打开锁文件失败: {}", e))?;
+    fs2::FileExt::lock_exclusive(&file)
+        .map_err(|e| format!("锁定 {} 失败: {}", path.display(), e))?;
+
+    Ok(DataFileLock { file })
+}
+
+// 修改数据文件前备份到 .cemetery/backups/<文件名>.<时间戳>.bak
+fn backup_file(path: &Path) -> Result<Option<PathBuf>, String> {
+    let Some(path) = resolve_data_path(path, data_settings().compress) else {
+        return Ok(None);
+    };
+
+    let dir = get_backup_dir();
+    fs::create_dir_all(&dir)
+        .map_err(|e| format!("创建备份目录失败: {}", e))?;
+
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| String::from("data"));
+    let backup = dir.join(format!("{}.{}.bak", name, Utc::now().format("%Y%m%dT%H%M%S%.3f")));
+
+    fs::copy(&path, &backup)
+        .map_err(|e| format!("备份失败: {}", e))?;
+    Ok(Some(backup))
+}
+
+// ========== 撤销栈 ==========
+
+const UNDO_STACK_LIMIT: usize = 20;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct UndoSnapshot {
+    pub label: String,
+    pub created_at: String,
+    pub tombstones: Vec<Tombstone>,
+}
+
+fn read_undo_stack() -> Result<Vec<UndoSnapshot>, String> {
+    let path = get_undo_stack_path();
+    if !data_file_exists(&path) {
+        return Ok(vec![]);
+    }
+    let content = read_data_file(&path)?;
+    serde_json::from_str(&content)
+        .map_err(|e| format!("解析撤销栈失败: {}", e))
+}
+
+// 在批量修改注册表之前记录修改前的完整快照，只保留最近 UNDO_STACK_LIMIT 个
+fn push_undo_snapshot(label: &str, tombstones: &[Tombstone]) -> Result<(), String> {
+    let _lock = lock_data_file(&get_undo_stack_path())?;
+    let mut stack = read_undo_stack()?;
+    stack.push(UndoSnapshot {
+        label: label.to_string(),
+        created_at: Utc::now().to_rfc3339(),
+        tombstones: tombstones.to_vec(),
+    });
+    if stack.len() > UNDO_STACK_LIMIT {
+        let overflow = stack.len() - UNDO_STACK_LIMIT;
+        stack.drain(..overflow);
+    }
+    write_json_atomic(&get_undo_stack_path(), &stack)
+}
+
+// 恢复最近一次快照，返回被撤销操作的说明
+#[tauri::command]
+pub fn undo_last_change() -> Result<String, String> {
+    let _registry_lock = lock_data_file(&get_tombstone_registry_path())?;
+    let _undo_lock = lock_data_file(&get_undo_stack_path())?;
+    let mut stack = read_undo_stack()?;
+    let snapshot = stack.pop().ok_or_else(|| String::from("没有可撤销的操作"))?;
+
+    backup_file(&get_tombstone_registry_path())?;
+    write_tombstones(&snapshot.tombstones)?;
+    write_json_atomic(&get_undo_stack_path(), &stack)?;
+
+    Ok(snapshot.label)
+}
+
+// FNV-1a，跨平台、跨版本稳定，用于需要确定性的选择和 id
+fn stable_hash(input: &str) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in input.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+// 宽松解析时间：RFC3339 优先，其次是不带时区的日期时间 / 纯日期（按 UTC 处理）
+fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+        return Some(dt.with_timezone(&Utc));
+    }
+    if let Ok(naive) = NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S") {
+        return Some(naive.and_utc());
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .map(|naive| naive.and_utc())
+}
+
+// ========== 国际化 ==========
+
+// 后端生成的文案（扫描结果、统计兜底值、报告、通知）按 Config.locale 查表
+const LOCALE_TABLES: &[(&str, &str)] = &[
+    ("en", include_str!("../locales/en.json")),
+    ("zh-CN", include_str!("../locales/zh-CN.json")),
+];
+
+fn locale_tables() -> &'static HashMap<String, HashMap<String, String>> {
+    static TABLES: OnceLock<HashMap<String, HashMap<String, String>>> = OnceLock::new();
+    TABLES.get_or_init(|| {
+        LOCALE_TABLES
+            .iter()
+            .map(|(locale, raw)| (locale.to_string(), serde_json::from_str(raw).unwrap_or_default()))
+            .collect()
+    })
+}
+
+// 把 "zh_CN.UTF-8" / "zh-Hans-CN" / "en-US" 之类归一到已有的翻译表，找不到时用 en
+fn normalize_locale(raw: &str) -> String {
+    let tag = raw.split('.').next().unwrap_or("").replace('_', "-");
+    let tables = locale_tables();
+
+    if let Some(key) = tables.keys().find(|k| k.eq_ignore_ascii_case(&tag)) {
+        return key.clone();
+    }
+
+    let lang = tag.split('-').next().unwrap_or("");
+    tables
+        .keys()
+        .find(|k| k.split('-').next().map_or(false, |l| l.eq_ignore_ascii_case(lang)))
+        .cloned()
+        .unwrap_or_else(|| String::from("en"))
+}
+
+fn default_locale() -> String {
+    sys_locale::get_locale()
+        .map(|l| normalize_locale(&l))
+        .unwrap_or_else(|| String::from("en"))
+}
+
+fn current_locale() -> String {
+    resolve_config()
+        .map(|c| c.locale)
+        .unwrap_or_else(|_| default_locale())
+}
+
+// 缺少翻译时直接返回 key 本身
+fn tr(key: &str) -> String {
+    tr_args(key, &[])
+}
+
+fn tr_args(key: &str, args: &[(&str, String)]) -> String {
+    let locale = normalize_locale(&current_locale());
+    let mut text = locale_tables()
+        .get(&locale)
+        .and_then(|table| table.get(key))
+        .cloned()
+        .unwrap_or_else(|| key.to_string());
+
+    for (name, value) in args {
+        text = text.replace(&format!("{{{}}}", name), value);
+    }
+    text
+}
+
+// ========== 时间显示 ==========
+
+// 面向用户的时间按哪个时区显示；Named 为 IANA 时区名，如 "Asia/Shanghai"
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Default)]
+pub enum DisplayTz {
+    #[default]
+    Utc,
+    Local,
+    Named(String),
+}
+
+const DISPLAY_TS_FORMAT: &str = "%Y-%m-%d %H:%M:%S %:z";
+
+fn parse_named_tz(name: &str) -> Result<chrono_tz::Tz, String> {
+    name.parse::<chrono_tz::Tz>()
+        .map_err(|_| format!("未知的时区: {}", name))
+}
+
+fn display_tz() -> DisplayTz {
+    resolve_config().map(|c| c.display_timezone).unwrap_or_default()
+}
+
+// 所有按时区换算的出口：格式化和按日期分桶都经这里，时区无效时退回 UTC
+fn to_display_tz(tz: &DisplayTz, dt: DateTime<Utc>) -> DateTime<chrono::FixedOffset> {
+    match tz {
+        DisplayTz::Utc => dt.fixed_offset(),
+        DisplayTz::Local => dt.with_timezone(&chrono::Local).fixed_offset(),
+        DisplayTz::Named(name) => match parse_named_tz(name) {
+            Ok(zone) => dt.with_timezone(&zone).fixed_offset(),
+            Err(e) => {
+                eprintln!("⚠️ {}，按 UTC 显示", e);
+                dt.fixed_offset()
+            }
+        },
+    }
+}
+
+fn format_ts_in(tz: &DisplayTz, dt: DateTime<Utc>) -> String {
+    to_display_tz(tz, dt).format(DISPLAY_TS_FORMAT).to_string()
+}
+
+// 显示时区下的日期，用于按天/周分桶
+fn date_in(tz: &DisplayTz, dt: DateTime<Utc>) -> NaiveDate {
+    to_display_tz(tz, dt).date_naive()
+}
+
+pub fn format_ts(dt: DateTime<Utc>) -> String {
+    format_ts_in(&display_tz(), dt)
+}
+
+#[tauri::command]
+pub fn set_display_timezone(timezone: DisplayTz) -> Result<(), String> {
+    if let DisplayTz::Named(name) = &timezone {
+        parse_named_tz(name)?;
+    }
+    let mut config = load_config()?;
+    config.display_timezone = timezone;
+    save_config(&config)
+}
+
+// ========== 配置命令 ==========
+
+// 安全模式：配置或数据损坏导致启动崩溃时的逃生口。
+// 不读配置文件（直接用默认配置）、不启动后台任务，让用户能进入设置页修复
+fn safe_mode_enabled() -> bool {
+    static SAFE_MODE: OnceLock<bool> = OnceLock::new();
+    *SAFE_MODE.get_or_init(|| {
+        let from_env = std::env::var("CODE_CORPSES_SAFE_MODE")
+            .map(|v| matches!(v.trim(), "1" | "true" | "yes"))
+            .unwrap_or(false);
+        from_env || std::env::args().any(|arg| arg == "--safe-mode")
+    })
+}
+
+#[tauri::command]
+pub fn is_safe_mode() -> bool {
+    safe_mode_enabled()
+}
+
+#[tauri::command]
+pub fn load_config() -> Result<Config, String> {
+    if safe_mode_enabled() {
+        return Ok(Config::default());
     }
+
+    let path = get_config_path();
     
-    #[cfg(target_os = "windows")]
-    {
-        use winreg::RegKey;
-        use winreg::enums::*;
-        
-        let key = RegKey::predef(HKEY_CURRENT_USER)
-            .open_subkey("Software\\Microsoft\\Windows\\CurrentVersion\\Run")
-            .map_err(|e| e.to_string())?;
-        
-        let exe_path = std::env::current_executable()
-            .map_err(|e| e.to_string())?
-            .to_string_lossy()
-            .into_owned();
-        
-        if enabled {
-            key.set_value("CodeCorpses", &exe_path)
-                .map_err(|e| e.to_string())?;
-        } else {
-            key.delete_value("CodeCorpses").ok();
+    if path.exists() {
+        let content = fs::read_to_string(&path)
+            .map_err(|e| format!("读取配置失败: {}", e))?;
+        serde_json::from_str(&content)
+            .map_err(|e| format!("解析配置失败: {}", e))
+    } else {
+        let default_config = Config::default();
+        save_config(&default_config)?;
+        Ok(default_config)
+    }
+}
+
+#[tauri::command]
+pub fn save_config(config: &Config) -> Result<(), String> {
+    let path = get_config_path();
+    let previous = fs::read_to_string(&path)
+        .ok()
+        .and_then(|content| serde_json::from_str::<Config>(&content).ok());
+    let was_compressed = previous.as_ref().map_or(false, |p| p.compress_data);
+    
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("创建配置目录失败: {}", e))?;
+    }
+    
+    let content = serde_json::to_string_pretty(config)
+        .map_err(|e| format!("序列化配置失败: {}", e))?;
+    
+    write_bytes_atomic(&path, content.as_bytes())
+        .map_err(|e| format!("写入配置失败: {}", e))?;
+
+    if let Err(e) = record_config_change(previous.as_ref(), config) {
+        eprintln!("⚠️ 记录配置变更失败: {}", e);
+    }
+
+    if was_compressed != config.compress_data {
+        let migrated = migrate_data_compression(config.compress_data)?;
+        println!("🗜️ 已转换 {} 个数据文件", migrated);
+    }
+
+    if previous.as_ref().map_or(true, |p| p.tray_menu_items != config.tray_menu_items) {
+        if let Some(app) = APP_HANDLE.get() {
+            if let Err(e) = refresh_tray_menu(app.clone()) {
+                eprintln!("⚠️ 重建托盘菜单失败: {}", e);
+            }
         }
     }
     
@@ -466,45 +893,9379 @@ pub fn set_autostart(enabled: bool) -> Result<(), String> {
 }
 
 #[tauri::command]
-pub async fn send_report() -> Result<String, String> {
-    let stats = get_stats();
-    let corpses = get_recent_corpses(10);
-    
-    let message = format!(
-        "📊 代码墓地报告\n\n资产: {} (存活: {}, 死亡: {})\n墓碑: {} (复活: {})",
-        stats.total_assets,
-        stats.alive_assets,
-        stats.dead_assets,
-        stats.total_tombstones,
-        stats.resurrected
-    );
-    
-    Ok(message)
+pub fn update_github_token(token: String) -> Result<(), String> {
+    let mut config = load_config()?;
+    config.github_token = Some(token);
+    save_config(&config)
 }
 
-// ========== 主入口 ==========
+// ========== 配置变更历史 ==========
 
-#[cfg_attr(mobile, tauri::mobile_entry_point)]
-pub fn run() {
-    tauri::Builder::default()
-        .plugin(tauri_plugin_shell::init())
-        .plugin(tauri_plugin_dialog::init())
-        .plugin(tauri_plugin_notification::init())
-        .invoke_handler(tauri::generate_handler![
-            get_stats,
-            get_recent_corpses,
-            trigger_scan,
-            send_report,
-            load_config,
-            save_config,
-            update_github_token,
-            set_autostart,
-            log_message,
-            get_version,
-            get_zombie_alerts,
-            mark_alert_read,
-            clear_all_alerts
-        ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+const CONFIG_HISTORY_LIMIT: usize = 200;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ConfigFieldChange {
+    pub field: String,
+    pub before: serde_json::Value,
+    pub after: serde_json::Value,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ConfigChange {
+    pub changed_at: String,
+    pub changes: Vec<ConfigFieldChange>,
+}
+
+fn get_config_history_path() -> PathBuf {
+    app_config_dir().0.join("config-history.json")
+}
+
+fn read_config_history() -> Result<Vec<ConfigChange>, String> {
+    let path = get_config_history_path();
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("读取配置历史失败: {}", e))?;
+    serde_json::from_str(&content)
+        .map_err(|e| format!("解析配置历史失败: {}", e))
+}
+
+// 令牌只记录打码后的形式
+fn redact_config_value(field: &str, value: serde_json::Value) -> serde_json::Value {
+    match (field, &value) {
+        ("github_token" | "webhook_secret", serde_json::Value::String(secret)) => {
+            serde_json::json!(mask_secret(secret))
+        }
+        _ => value,
+    }
+}
+
+// 逐字段比较前后配置，只记录有变化的字段；previous 为 None（首次写入）时 before 均为 null
+fn diff_configs(previous: Option<&Config>, next: &Config) -> Vec<ConfigFieldChange> {
+    let to_map = |config: &Config| match serde_json::to_value(config) {
+        Ok(serde_json::Value::Object(map)) => map,
+        _ => serde_json::Map::new(),
+    };
+    let before = previous.map(to_map).unwrap_or_default();
+    let after = to_map(next);
+
+    after
+        .into_iter()
+        .filter_map(|(field, new_value)| {
+            let old_value = before.get(&field).cloned().unwrap_or(serde_json::Value::Null);
+            if old_value == new_value {
+                return None;
+            }
+            Some(ConfigFieldChange {
+                before: redact_config_value(&field, old_value),
+                after: redact_config_value(&field, new_value),
+                field,
+            })
+        })
+        .collect()
+}
+
+fn record_config_change(previous: Option<&Config>, next: &Config) -> Result<(), String> {
+    let changes = diff_configs(previous, next);
+    if changes.is_empty() {
+        return Ok(());
+    }
+
+    let path = get_config_history_path();
+    let _lock = lock_data_file(&path)?;
+    let mut history = read_config_history()?;
+    history.push(ConfigChange {
+        changed_at: Utc::now().to_rfc3339(),
+        changes,
+    });
+    if history.len() > CONFIG_HISTORY_LIMIT {
+        let overflow = history.len() - CONFIG_HISTORY_LIMIT;
+        history.drain(..overflow);
+    }
+
+    let content = serde_json::to_string_pretty(&history)
+        .map_err(|e| format!("序列化配置历史失败: {}", e))?;
+    write_bytes_atomic(&path, content.as_bytes())
+}
+
+// 最近 limit 次配置变更，最新的在前
+#[tauri::command]
+pub fn get_config_history(limit: usize) -> Result<Vec<ConfigChange>, String> {
+    let mut history = read_config_history()?;
+    history.reverse();
+    history.truncate(limit);
+    Ok(history)
+}
+
+// ========== 生效配置 ==========
+
+// 环境变量覆盖配置文件中的同名字段（变量名与 .env.example 一致）
+const ENV_OVERRIDES: &[(&str, &str)] = &[
+    ("github_token", "GITHUB_TOKEN"),
+    ("target_org", "TARGET_ORG"),
+    ("scan_interval", "SCAN_INTERVAL"),
+    ("auto_start", "AUTO_START"),
+];
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConfigSource {
+    File,
+    Env,
+    Default,
+}
+
+#[derive(Serialize)]
+pub struct EffectiveConfig {
+    pub config: Config,
+    pub sources: BTreeMap<String, ConfigSource>,
+}
+
+// 按字段原本的 JSON 类型解析环境变量，返回被覆盖的字段名
+fn apply_env_overrides(config: &mut Config) -> Result<Vec<&'static str>, String> {
+    let mut value = serde_json::to_value(&*config)
+        .map_err(|e| format!("序列化配置失败: {}", e))?;
+    let mut overridden = vec![];
+
+    for (field, var) in ENV_OVERRIDES {
+        let Ok(raw) = std::env::var(var) else {
+            continue;
+        };
+        let parsed = match &value[*field] {
+            serde_json::Value::Number(_) => raw
+                .trim()
+                .parse::<u64>()
+                .map(serde_json::Value::from)
+                .map_err(|_| format!("环境变量 {} 不是有效数字: {}", var, raw))?,
+            serde_json::Value::Bool(_) => {
+                serde_json::Value::Bool(matches!(raw.trim().to_lowercase().as_str(), "1" | "true" | "yes" | "on"))
+            }
+            _ => serde_json::Value::String(raw),
+        };
+        value[*field] = parsed;
+        overridden.push(*field);
+    }
+
+    *config = serde_json::from_value(value)
+        .map_err(|e| format!("应用环境变量失败: {}", e))?;
+    Ok(overridden)
+}
+
+// 实际运行时使用的配置：配置文件 + 环境变量覆盖
+pub fn resolve_config() -> Result<Config, String> {
+    let mut config = load_config()?;
+    apply_env_overrides(&mut config)?;
+    Ok(config)
+}
+
+fn mask_secret(secret: &str) -> String {
+    let chars: Vec<char> = secret.chars().collect();
+    if chars.len() <= 8 {
+        return String::from("****");
+    }
+    let head: String = chars[..4].iter().collect();
+    let tail: String = chars[chars.len() - 4..].iter().collect();
+    format!("{}****{}", head, tail)
+}
+
+#[tauri::command]
+pub fn get_effective_config() -> Result<EffectiveConfig, String> {
+    let mut config = load_config()?;
+    let overridden = apply_env_overrides(&mut config)?;
+
+    // 配置文件里写了的字段记为 File，其余是 serde 填充的默认值
+    let file_keys: HashSet<String> = fs::read_to_string(get_config_path())
+        .ok()
+        .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+        .and_then(|v| v.as_object().map(|o| o.keys().cloned().collect()))
+        .unwrap_or_default();
+
+    let fields = serde_json::to_value(&config)
+        .map_err(|e| format!("序列化配置失败: {}", e))?;
+    let sources = fields
+        .as_object()
+        .map(|o| {
+            o.keys()
+                .map(|key| {
+                    let source = if overridden.contains(&key.as_str()) {
+                        ConfigSource::Env
+                    } else if file_keys.contains(key) {
+                        ConfigSource::File
+                    } else {
+                        ConfigSource::Default
+                    };
+                    (key.clone(), source)
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    config.github_token = config.github_token.as_deref().map(mask_secret);
+    config.webhook_secret = config.webhook_secret.as_deref().map(mask_secret);
+
+    Ok(EffectiveConfig { config, sources })
+}
+
+// ========== 墓地数据命令 ==========
+
+#[tauri::command]
+pub fn get_stats() -> Stats {
+    compute_stats(|_| true, |_| true)
+}
+
+fn has_tag(tags: &[String], tag: &str) -> bool {
+    tags.iter().any(|t| t.trim().eq_ignore_ascii_case(tag))
+}
+
+// 只统计带某个标签的资产和墓碑（不区分大小写），作为该标签的独立仪表盘；没有匹配时各项为 0
+#[tauri::command]
+pub fn get_stats_for_tag(tag: String) -> Stats {
+    let tag = tag.trim().to_string();
+    compute_stats(|a| has_tag(&a.tags, &tag), |t| has_tag(&t.tags, &tag))
+}
+
+// 统计计算本身，get_stats 与按条件过滤的统计共用；last_scan 始终取资产索引的修改时间
+fn compute_stats(include_asset: impl Fn(&Asset) -> bool, include_tombstone: impl Fn(&Tombstone) -> bool) -> Stats {
+    let asset_path = get_asset_index_path();
+    let tombstone_path = get_tombstone_registry_path();
+    
+    let mut total_assets = 0;
+    let mut alive_assets = 0;
+    let mut total_tombstones = 0;
+    let mut resurrected = 0;
+    let mut last_scan = tr("stats.unknown");
+
+    // 读取资产
+    if data_file_exists(&asset_path) {
+        match read_data_file(&asset_path) {
+            Ok(content) => {
+                if let Ok(assets) = serde_json::from_str::<Vec<Asset>>(&content) {
+                    let assets: Vec<Asset> = assets.into_iter().filter(|a| include_asset(a)).collect();
+                    total_assets = assets.len();
+                    alive_assets = assets.iter().filter(|a| a.alive).count();
+
+                    // 获取最后更新时间
+                    if let Some(Ok(metadata)) = resolve_data_path(&asset_path, data_settings().compress).map(fs::metadata) {
+                        if let Ok(modified) = metadata.modified() {
+                            last_scan = format_ts(DateTime::<Utc>::from(modified));
+                        }
+                    }
+                }
+            }
+            Err(e) => eprintln!("⚠️ {}", e),
+        }
+    }
+
+    // 读取墓碑
+    if data_file_exists(&tombstone_path) {
+        match read_data_file(&tombstone_path) {
+            Ok(content) => {
+                if let Ok(tombstones) = serde_json::from_str::<Vec<Tombstone>>(&content) {
+                    let tombstones: Vec<Tombstone> = tombstones.into_iter().filter(|t| include_tombstone(t)).collect();
+                    total_tombstones = tombstones.len();
+                    resurrected = tombstones.iter().filter(|t| t.resurrected_at.is_some()).count();
+                }
+            }
+            Err(e) => eprintln!("⚠️ {}", e),
+        }
+    }
+
+    Stats {
+        total_assets,
+        alive_assets,
+        dead_assets: total_assets - alive_assets,
+        total_tombstones,
+        resurrected,
+        last_scan,
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CorpsesPage {
+    pub items: Vec<Tombstone>,
+    pub total: usize,
+    pub has_more: bool,
+}
+
+// 分页读取墓碑，供无限滚动列表使用。按死亡日期倒序，pinned_first 时置顶的墓碑无视日期排在最前。
+// 注册表不存在时返回空页，不再用模拟数据掩盖“还没有数据”的状态
+#[tauri::command]
+pub fn get_corpses_page(offset: usize, limit: usize, pinned_first: Option<bool>) -> Result<CorpsesPage, String> {
+    let mut tombstones = read_tombstones()?;
+    let by_date = |a: &Tombstone, b: &Tombstone| parse_timestamp(&b.died_at).cmp(&parse_timestamp(&a.died_at));
+    if pinned_first.unwrap_or(false) {
+        tombstones.sort_by(|a, b| b.pinned.cmp(&a.pinned).then_with(|| by_date(a, b)));
+    } else {
+        tombstones.sort_by(by_date);
+    }
+
+    let total = tombstones.len();
+    let items: Vec<Tombstone> = tombstones.into_iter().skip(offset).take(limit).collect();
+    Ok(CorpsesPage {
+        has_more: offset.saturating_add(items.len()) < total,
+        items,
+        total,
+    })
+}
+
+// 旧接口，保留给现有调用方：取第一页
+#[tauri::command]
+pub fn get_recent_corpses(limit: i32, pinned_first: Option<bool>) -> Vec<Tombstone> {
+    match get_corpses_page(0, limit.max(0) as usize, pinned_first) {
+        Ok(page) => page.items,
+        Err(e) => {
+            eprintln!("⚠️ {}", e);
+            vec![]
+        }
+    }
+}
+
+// ========== 归档命令 ==========
+
+#[derive(Serialize, Deserialize)]
+pub struct ArchiveSummary {
+    pub archived: usize,
+    pub remaining: usize,
+    pub backup_path: Option<String>,
+}
+
+// 把复活超过 N 天的墓碑移入 archive.json，让主视图只保留真正的死代码
+#[tauri::command]
+pub fn archive_resurrected(older_than_days: u32) -> Result<ArchiveSummary, String> {
+    let registry_path = get_tombstone_registry_path();
+    let archive_path = get_archive_path();
+    let _registry_lock = lock_data_file(&registry_path)?;
+    let _archive_lock = lock_data_file(&archive_path)?;
+    let cutoff = Utc::now() - Duration::days(older_than_days as i64);
+
+    let (to_archive, remaining): (Vec<Tombstone>, Vec<Tombstone>) = read_tombstones()?
+        .into_iter()
+        .partition(|t| {
+            t.resurrected_at
+                .as_deref()
+                .and_then(parse_timestamp)
+                .map(|at| at < cutoff)
+                .unwrap_or(false)
+        });
+
+    if to_archive.is_empty() {
+        return Ok(ArchiveSummary {
+            archived: 0,
+            remaining: remaining.len(),
+            backup_path: None,
+        });
+    }
+
+    let backup = backup_file(&registry_path)?;
+    backup_file(&archive_path)?;
+
+    // 先写归档再写注册表，中途失败最多产生重复，不会丢数据
+    let archived = to_archive.len();
+    let mut archive = read_tombstones_from(&archive_path)?;
+    archive.extend(to_archive);
+    write_json_atomic(&archive_path, &archive)?;
+    write_tombstones(&remaining)?;
+
+    println!("📦 已归档 {} 个复活墓碑", archived);
+
+    Ok(ArchiveSummary {
+        archived,
+        remaining: remaining.len(),
+        backup_path: backup.map(|p| p.to_string_lossy().into_owned()),
+    })
+}
+
+// ========== 保留策略 ==========
+
+// 超过 retention_days 的未复活墓碑如何处理；None 表示不自动处理
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum RetentionAction {
+    Archive,
+    Delete,
+    #[default]
+    None,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RetentionSummary {
+    pub action: RetentionAction,
+    pub archived: usize,
+    pub deleted: usize,
+    // 已过期但因置顶而保留的墓碑数
+    pub kept_pinned: usize,
+    pub backup_path: Option<String>,
+}
+
+// 按配置的保留策略归档或删除死亡超过 retention_days 天的墓碑。
+// 已复活和置顶的墓碑永远不动；死亡时间无法解析的也不动。
+// 动手前备份注册表并压入撤销快照（撤销只恢复注册表，归档里的副本可用 unarchive 去重恢复）
+#[tauri::command]
+pub fn apply_retention() -> Result<RetentionSummary, String> {
+    let config = resolve_config()?;
+    let mut summary = RetentionSummary {
+        action: config.retention_action,
+        archived: 0,
+        deleted: 0,
+        kept_pinned: 0,
+        backup_path: None,
+    };
+    let Some(days) = config.retention_days.filter(|_| config.retention_action != RetentionAction::None) else {
+        return Ok(summary);
+    };
+
+    let registry_path = get_tombstone_registry_path();
+    let archive_path = get_archive_path();
+    let _registry_lock = lock_data_file(&registry_path)?;
+    let _archive_lock = lock_data_file(&archive_path)?;
+    let cutoff = Utc::now() - Duration::days(days as i64);
+
+    let original = read_tombstones()?;
+    let expired = |t: &Tombstone| {
+        t.resurrected_at.is_none() && parse_timestamp(&t.died_at).is_some_and(|at| at < cutoff)
+    };
+    summary.kept_pinned = original.iter().filter(|t| t.pinned && expired(t)).count();
+    let (expiring, remaining): (Vec<Tombstone>, Vec<Tombstone>) = original
+        .iter()
+        .cloned()
+        .partition(|t| !t.pinned && expired(t));
+    if expiring.is_empty() {
+        return Ok(summary);
+    }
+
+    let backup = backup_file(&registry_path)?;
+    summary.backup_path = backup.map(|p| p.to_string_lossy().into_owned());
+
+    let label = match config.retention_action {
+        RetentionAction::Archive => {
+            backup_file(&archive_path)?;
+            summary.archived = expiring.len();
+            format!("保留策略归档 {} 个墓碑", expiring.len())
+        }
+        RetentionAction::Delete => {
+            summary.deleted = expiring.len();
+            format!("保留策略删除 {} 个墓碑", expiring.len())
+        }
+        RetentionAction::None => return Ok(summary),
+    };
+    push_undo_snapshot(&label, &original)?;
+
+    // 与 archive_resurrected 相同：先写归档再写注册表
+    if config.retention_action == RetentionAction::Archive {
+        let mut archive = read_tombstones_from(&archive_path)?;
+        archive.extend(expiring);
+        write_json_atomic(&archive_path, &archive)?;
+    }
+    write_tombstones(&remaining)?;
+
+    println!("🧹 {}", label);
+    Ok(summary)
+}
+
+// ids 为空时恢复全部归档；注册表中已存在的 id 不会重复恢复
+#[tauri::command]
+pub fn unarchive(ids: Vec<String>) -> Result<usize, String> {
+    let registry_path = get_tombstone_registry_path();
+    let archive_path = get_archive_path();
+    let _registry_lock = lock_data_file(&registry_path)?;
+    let _archive_lock = lock_data_file(&archive_path)?;
+
+    let mut tombstones = read_tombstones()?;
+    let existing: HashSet<String> = tombstones.iter().map(|t| t.id.clone()).collect();
+
+    let (restore, keep): (Vec<Tombstone>, Vec<Tombstone>) = read_tombstones_from(&archive_path)?
+        .into_iter()
+        .partition(|t| {
+            (ids.is_empty() || ids.contains(&t.id)) && !existing.contains(&t.id)
+        });
+
+    if restore.is_empty() {
+        return Ok(0);
+    }
+
+    backup_file(&registry_path)?;
+    backup_file(&archive_path)?;
+
+    // 先写回注册表再缩减归档，与归档顺序相反
+    let restored = restore.len();
+    tombstones.extend(restore);
+    write_tombstones(&tombstones)?;
+    write_json_atomic(&archive_path, &keep)?;
+
+    Ok(restored)
+}
+
+// ========== 批量删除 ==========
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct TombstoneFilter {
+    pub repo: Option<String>,
+    pub language: Option<String>,
+    pub tag: Option<String>,
+    pub died_before: Option<String>,
+    pub died_after: Option<String>,
+}
+
+impl TombstoneFilter {
+    fn is_empty(&self) -> bool {
+        self.repo.is_none()
+            && self.language.is_none()
+            && self.tag.is_none()
+            && self.died_before.is_none()
+            && self.died_after.is_none()
+    }
+
+    // 所有已设置的条件都满足才算匹配；died_at 无法解析的墓碑不参与日期条件
+    fn matches(&self, t: &Tombstone, before: Option<DateTime<Utc>>, after: Option<DateTime<Utc>>) -> bool {
+        if let Some(repo) = &self.repo {
+            if t.repo.as_deref() != Some(repo.as_str()) {
+                return false;
+            }
+        }
+        if let Some(language) = &self.language {
+            if !t.language.as_deref().map_or(false, |l| l.eq_ignore_ascii_case(language)) {
+                return false;
+            }
+        }
+        if let Some(tag) = &self.tag {
+            if !t.tags.iter().any(|x| x.eq_ignore_ascii_case(tag)) {
+                return false;
+            }
+        }
+        if before.is_some() || after.is_some() {
+            let Some(died) = parse_timestamp(&t.died_at) else {
+                return false;
+            };
+            if before.map_or(false, |b| died >= b) || after.map_or(false, |a| died <= a) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+fn parse_filter_date(value: &Option<String>, field: &str) -> Result<Option<DateTime<Utc>>, String> {
+    match value {
+        Some(raw) => parse_timestamp(raw)
+            .map(Some)
+            .ok_or_else(|| format!("{} 不是有效日期: {}", field, raw)),
+        None => Ok(None),
+    }
+}
+
+// 手动立碑。died_at 为空时取当前时间，其余字段按导入时的规则校验和规范化；
+// id 已存在时拒绝。注册表和 .cemetery 目录不存在时自动创建
+#[tauri::command]
+pub fn create_tombstone(tombstone: Tombstone) -> Result<(), String> {
+    let mut tombstone = tombstone;
+    if tombstone.died_at.trim().is_empty() {
+        tombstone.died_at = Utc::now().to_rfc3339();
+    }
+    let tombstone = normalize_tombstone(tombstone).map_err(|errors| {
+        errors
+            .into_iter()
+            .map(|e| format!("{}: {}", e.field, e.message))
+            .collect::<Vec<_>>()
+            .join("\n")
+    })?;
+
+    let _lock = lock_data_file(&get_tombstone_registry_path())?;
+    let original = read_tombstones()?;
+    if original.iter().any(|t| t.id == tombstone.id) {
+        return Err(format!("墓碑 id 已存在: {}", tombstone.id));
+    }
+
+    let mut tombstones = original.clone();
+    tombstones.push(tombstone.clone());
+    push_undo_snapshot(&format!("立碑 {}", tombstone.name), &original)?;
+    write_tombstones(&tombstones)?;
+    emit_tombstones_created(std::slice::from_ref(&tombstone));
+    Ok(())
+}
+
+// 删除单个墓碑（例如误立的碑），可通过撤销恢复。
+// 对应资产上的 tombstoneId 保留，之后的扫描不会为它重新立碑
+#[tauri::command]
+pub fn delete_tombstone(id: String) -> Result<(), String> {
+    let _lock = lock_data_file(&get_tombstone_registry_path())?;
+    let original = read_tombstones()?;
+    let Some(index) = original.iter().position(|t| t.id == id) else {
+        return Err(format!("找不到墓碑: {}", id));
+    };
+
+    let mut tombstones = original.clone();
+    let removed = tombstones.remove(index);
+    backup_file(&get_tombstone_registry_path())?;
+    push_undo_snapshot(&format!("删除墓碑 {}", removed.name), &original)?;
+    write_tombstones(&tombstones)
+}
+
+#[tauri::command]
+pub fn delete_tombstones_where(filter: TombstoneFilter) -> Result<usize, String> {
+    // 空过滤条件会匹配全部墓碑，直接拒绝以防误删
+    if filter.is_empty() {
+        return Err(String::from("至少需要设置一个过滤条件"));
+    }
+    let before = parse_filter_date(&filter.died_before, "died_before")?;
+    let after = parse_filter_date(&filter.died_after, "died_after")?;
+
+    let _lock = lock_data_file(&get_tombstone_registry_path())?;
+    let tombstones = read_tombstones()?;
+    let remaining: Vec<Tombstone> = tombstones
+        .iter()
+        .filter(|t| !filter.matches(t, before, after))
+        .cloned()
+        .collect();
+    let deleted = tombstones.len() - remaining.len();
+    if deleted == 0 {
+        return Ok(0);
+    }
+
+    backup_file(&get_tombstone_registry_path())?;
+    push_undo_snapshot(&format!("批量删除 {} 个墓碑", deleted), &tombstones)?;
+    write_tombstones(&remaining)?;
+
+    Ok(deleted)
+}
+
+// mapping 为 (墓碑 id, 复活后的路径)。不存在或已复活的 id 跳过，返回实际复活的数量
+#[tauri::command]
+pub fn bulk_resurrect(mapping: Vec<(String, String)>) -> Result<usize, String> {
+    let _lock = lock_data_file(&get_tombstone_registry_path())?;
+    let original = read_tombstones()?;
+    let mut tombstones = original.clone();
+
+    let now = Utc::now().to_rfc3339();
+    let mut applied = 0;
+    for (id, resurrected_to) in mapping {
+        let Some(tombstone) = tombstones
+            .iter_mut()
+            .find(|t| t.id == id && t.resurrected_at.is_none())
+        else {
+            continue;
+        };
+        tombstone.resurrected_at = Some(now.clone());
+        tombstone.resurrected_to = Some(resurrected_to);
+        applied += 1;
+    }
+    if applied == 0 {
+        return Ok(0);
+    }
+
+    backup_file(&get_tombstone_registry_path())?;
+    push_undo_snapshot(&format!("批量复活 {} 个墓碑", applied), &original)?;
+    write_tombstones(&tombstones)?;
+
+    Ok(applied)
+}
+
+// 复活单个墓碑：resurrected_to 为复活后的路径或仓库。已复活过的返回错误并注明复活时间
+#[tauri::command]
+pub fn resurrect_tombstone(id: String, resurrected_to: String) -> Result<Tombstone, String> {
+    let resurrected_to = resurrected_to.trim().to_string();
+    if resurrected_to.is_empty() {
+        return Err(String::from("复活去向不能为空"));
+    }
+
+    let _lock = lock_data_file(&get_tombstone_registry_path())?;
+    let original = read_tombstones()?;
+    let mut tombstones = original.clone();
+    let Some(tombstone) = tombstones.iter_mut().find(|t| t.id == id) else {
+        return Err(format!("找不到墓碑: {}", id));
+    };
+    if let Some(at) = &tombstone.resurrected_at {
+        return Err(format!("墓碑 {} 已在 {} 复活过", tombstone.name, at));
+    }
+    tombstone.resurrected_at = Some(Utc::now().to_rfc3339());
+    tombstone.resurrected_to = Some(resurrected_to);
+    let updated = tombstone.clone();
+
+    push_undo_snapshot(&format!("复活 {}", updated.name), &original)?;
+    write_tombstones(&tombstones)?;
+    Ok(updated)
+}
+
+// ========== 自动标签 ==========
+
+// 路径中出现这些目录名时打对应标签（按路径段精确匹配，不区分大小写）
+const DIR_TAG_RULES: &[(&[&str], &str)] = &[
+    (&["test", "tests", "__tests__", "spec", "specs", "e2e"], "test"),
+    (&["fixtures", "fixture", "mocks", "__mocks__"], "fixture"),
+    (&["docs", "doc"], "docs"),
+    (&["scripts", "bin", "tools"], "script"),
+    (&["vendor", "third_party", "node_modules"], "vendor"),
+    (&["migrations", "migration"], "migration"),
+    (&["generated", "gen", "dist", "build"], "generated"),
+];
+
+// 路径中出现这些框架名时打同名标签。路径按非字母数字字符切词后逐词比较，
+// vue-admin、react_utils 能命中，expression 不会误判为 express
+const FRAMEWORK_TAGS: &[&str] = &[
+    "react", "vue", "angular", "svelte", "next", "nuxt", "django", "flask", "rails", "spring", "express",
+    "tauri", "jquery",
+];
+
+// 从原路径和语言推断标签。规则依次为：
+// 1. DIR_TAG_RULES：目录名（test/、docs/ 等）
+// 2. 文件名：*.test.* / *.spec.* / *_test.* → test；.config、*.config.*、*rc、常见配置扩展名 → config
+// 3. FRAMEWORK_TAGS：路径中作为独立单词出现的框架名
+// 4. 语言名本身（小写）
+// 新增规则只需扩充上面的表或在这里追加分支；结果已去重并按推断顺序排列
+fn inferred_tags(original_path: &str, language: Option<&str>) -> Vec<String> {
+    let mut tags: Vec<String> = vec![];
+    let mut add = |tag: &str| {
+        if !tags.iter().any(|t| t == tag) {
+            tags.push(tag.to_string());
+        }
+    };
+
+    let lower = original_path.replace('\\', "/").to_lowercase();
+    let segments: Vec<&str> = lower.split('/').filter(|s| !s.is_empty()).collect();
+    let (file_name, dirs) = segments.split_last().map_or(("", &[][..]), |(f, d)| (*f, d));
+
+    for (names, tag) in DIR_TAG_RULES {
+        if dirs.iter().any(|d| names.contains(d)) {
+            add(tag);
+        }
+    }
+
+    if [".test.", ".spec.", "_test.", "_spec."].iter().any(|m| file_name.contains(m)) {
+        add("test");
+    }
+    let is_config = file_name == ".config"
+        || file_name.contains(".config.")
+        || (file_name.starts_with('.') && file_name.ends_with("rc"))
+        || [".toml", ".yaml", ".yml", ".ini", ".env"].iter().any(|ext| file_name.ends_with(ext))
+        || dirs.contains(&".config");
+    if is_config {
+        add("config");
+    }
+
+    let words: HashSet<&str> = lower.split(|c: char| !c.is_ascii_alphanumeric()).collect();
+    for framework in FRAMEWORK_TAGS {
+        if words.contains(framework) {
+            add(framework);
+        }
+    }
+
+    if let Some(language) = language.map(str::trim).filter(|l| !l.is_empty()) {
+        add(&language.to_lowercase());
+    }
+    tags
+}
+
+// 按启发式规则为墓碑补充标签：只添加，不删除已有标签，已有的（不区分大小写）不重复添加。
+// 返回获得了新标签的墓碑数
+#[tauri::command]
+pub fn auto_tag() -> Result<usize, String> {
+    let _lock = lock_data_file(&get_tombstone_registry_path())?;
+    let original = read_tombstones()?;
+    let mut tombstones = original.clone();
+
+    let mut tagged = 0;
+    for t in tombstones.iter_mut() {
+        let mut gained = false;
+        for tag in inferred_tags(&t.original_path, t.language.as_deref()) {
+            if !t.tags.iter().any(|existing| existing.eq_ignore_ascii_case(&tag)) {
+                t.tags.push(tag);
+                gained = true;
+            }
+        }
+        if gained {
+            tagged += 1;
+        }
+    }
+    if tagged == 0 {
+        return Ok(0);
+    }
+
+    backup_file(&get_tombstone_registry_path())?;
+    push_undo_snapshot(&format!("自动标签 {} 个墓碑", tagged), &original)?;
+    write_tombstones(&tombstones)?;
+
+    Ok(tagged)
+}
+
+// 按 location / name 做不区分大小写的子串匹配；空查询返回空结果而不是整个索引。
+// 传了 repo 时只在该仓库（不区分大小写）的资产中查找
+#[tauri::command]
+pub fn search_assets(query: String, limit: i32, repo: Option<String>) -> Vec<Asset> {
+    let query = query.trim().to_lowercase();
+    if query.is_empty() || limit <= 0 {
+        return vec![];
+    }
+    let repo = trim_optional(repo);
+
+    let assets = read_assets().unwrap_or_else(|e| {
+        eprintln!("⚠️ {}", e);
+        vec![]
+    });
+    assets
+        .into_iter()
+        .filter(|a| {
+            repo.as_deref().map_or(true, |repo| {
+                a.repo.as_deref().is_some_and(|r| r.eq_ignore_ascii_case(repo))
+            })
+        })
+        .filter(|a| a.location.to_lowercase().contains(&query) || a.name.to_lowercase().contains(&query))
+        .take(limit as usize)
+        .collect()
+}
+
+#[tauri::command]
+pub fn set_pinned(id: String, pinned: bool) -> Result<(), String> {
+    let _lock = lock_data_file(&get_tombstone_registry_path())?;
+    let mut tombstones = read_tombstones()?;
+    let tombstone = tombstones
+        .iter_mut()
+        .find(|t| t.id == id)
+        .ok_or_else(|| format!("墓碑不存在: {}", id))?;
+
+    if tombstone.pinned == pinned {
+        return Ok(());
+    }
+    tombstone.pinned = pinned;
+    write_tombstones(&tombstones)
+}
+
+// ========== 墓碑附件 ==========
+
+// 单个墓碑所有附件的总大小上限
+const MAX_ATTACHMENT_BYTES_PER_TOMBSTONE: u64 = 20 * 1024 * 1024;
+
+// 附件目录 .cemetery/attachments/<id>/；id 含路径分隔符等字符时改用其哈希作目录名
+fn attachment_dir_name(id: &str) -> String {
+    if !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        id.to_string()
+    } else {
+        format!("{:016x}", stable_hash(id))
+    }
+}
+
+fn attachments_size(attachments: &[Attachment]) -> u64 {
+    let base = get_base_path();
+    attachments
+        .iter()
+        .filter_map(|a| fs::metadata(base.join(&a.path)).ok())
+        .map(|m| m.len())
+        .sum()
+}
+
+// 把 source_path 复制到墓碑的附件目录并记录；同名文件自动加序号，不覆盖已有附件
+#[tauri::command]
+pub fn add_attachment(
+    id: String,
+    kind: String,
+    source_path: String,
+    caption: Option<String>,
+) -> Result<Attachment, String> {
+    let kind = kind.trim().to_lowercase();
+    if kind.is_empty() {
+        return Err(String::from("附件类型不能为空"));
+    }
+    let source = PathBuf::from(source_path.trim());
+    let metadata = fs::metadata(&source)
+        .map_err(|e| format!("附件文件不存在: {} ({})", source.display(), e))?;
+    if !metadata.is_file() {
+        return Err(format!("附件必须是文件: {}", source.display()));
+    }
+    let file_name = source
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .ok_or_else(|| format!("无法识别文件名: {}", source.display()))?;
+
+    let _lock = lock_data_file(&get_tombstone_registry_path())?;
+    let mut tombstones = read_tombstones()?;
+    let tombstone = tombstones
+        .iter_mut()
+        .find(|t| t.id == id)
+        .ok_or_else(|| format!("墓碑不存在: {}", id))?;
+
+    let used = attachments_size(&tombstone.attachments);
+    if used + metadata.len() > MAX_ATTACHMENT_BYTES_PER_TOMBSTONE {
+        return Err(format!(
+            "附件总大小超过上限 {} MB（已用 {:.1} MB）",
+            MAX_ATTACHMENT_BYTES_PER_TOMBSTONE / 1024 / 1024,
+            used as f64 / 1024.0 / 1024.0
+        ));
+    }
+
+    let relative_dir = PathBuf::from(".cemetery/attachments").join(attachment_dir_name(&id));
+    let dir = get_base_path().join(&relative_dir);
+    fs::create_dir_all(&dir).map_err(|e| format!("创建附件目录失败: {}", e))?;
+
+    let (stem, ext) = match file_name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => (stem.to_string(), format!(".{}", ext)),
+        _ => (file_name.clone(), String::new()),
+    };
+    let mut target_name = file_name.clone();
+    let mut n = 1;
+    while dir.join(&target_name).exists() {
+        n += 1;
+        target_name = format!("{}-{}{}", stem, n, ext);
+    }
+    fs::copy(&source, dir.join(&target_name)).map_err(|e| format!("复制附件失败: {}", e))?;
+
+    let attachment = Attachment {
+        kind,
+        path: relative_dir.join(&target_name).to_string_lossy().replace('\\', "/"),
+        caption: trim_optional(caption),
+    };
+    tombstone.attachments.push(attachment.clone());
+    if let Err(e) = write_tombstones(&tombstones) {
+        fs::remove_file(dir.join(&target_name)).ok();
+        return Err(e);
+    }
+    Ok(attachment)
+}
+
+// 删除附件记录和对应文件；文件已经不在时只删记录
+#[tauri::command]
+pub fn remove_attachment(id: String, path: String) -> Result<(), String> {
+    let _lock = lock_data_file(&get_tombstone_registry_path())?;
+    let mut tombstones = read_tombstones()?;
+    let tombstone = tombstones
+        .iter_mut()
+        .find(|t| t.id == id)
+        .ok_or_else(|| format!("墓碑不存在: {}", id))?;
+
+    let index = tombstone
+        .attachments
+        .iter()
+        .position(|a| a.path == path)
+        .ok_or_else(|| format!("墓碑 {} 没有附件 {}", id, path))?;
+    let removed = tombstone.attachments.remove(index);
+    write_tombstones(&tombstones)?;
+
+    let file = get_base_path().join(&removed.path);
+    if file.exists() {
+        fs::remove_file(&file).map_err(|e| format!("删除附件文件失败: {}", e))?;
+    }
+    Ok(())
+}
+
+// 直接修改索引中的 JSON 对象，保留 TypeScript 端写入的其他字段
+#[tauri::command]
+pub fn set_asset_exempt(id: String, exempt: bool) -> Result<(), String> {
+    let path = get_asset_index_path();
+    let _lock = lock_data_file(&path)?;
+    if !data_file_exists(&path) {
+        return Err(format!("资产不存在: {}", id));
+    }
+
+    let mut assets: serde_json::Value = serde_json::from_str(&read_data_file(&path)?)
+        .map_err(|e| format!("解析资产索引失败: {}", e))?;
+    let asset = assets
+        .as_array_mut()
+        .and_then(|list| list.iter_mut().find(|a| a["id"] == id.as_str()))
+        .ok_or_else(|| format!("资产不存在: {}", id))?;
+
+    if asset["exempt"].as_bool().unwrap_or(false) == exempt {
+        return Ok(());
+    }
+    asset["exempt"] = serde_json::json!(exempt);
+    write_json_atomic(&path, &assets)
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DuplicateGroup {
+    pub location: String,
+    pub count: usize,
+    pub ids: Vec<String>,
+}
+
+fn read_asset_values() -> Result<Vec<serde_json::Value>, String> {
+    let path = get_asset_index_path();
+    if !data_file_exists(&path) {
+        return Ok(vec![]);
+    }
+    serde_json::from_str(&read_data_file(&path)?)
+        .map_err(|e| format!("解析资产索引失败: {}", e))
+}
+
+// 按规范化后的 location 分组，返回每组在索引中的下标（保持原顺序）
+fn group_assets_by_location(assets: &[serde_json::Value]) -> BTreeMap<String, Vec<usize>> {
+    let mut groups: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+    for (i, asset) in assets.iter().enumerate() {
+        if let Some(location) = asset["location"].as_str() {
+            groups.entry(normalize_repo_path(location)).or_default().push(i);
+        }
+    }
+    groups
+}
+
+// TypeScript 端写 updatedAt / indexedAt，也兼容蛇形命名
+fn asset_updated_at(asset: &serde_json::Value) -> Option<DateTime<Utc>> {
+    ["updatedAt", "updated_at", "indexedAt", "indexed_at"]
+        .iter()
+        .filter_map(|key| asset[*key].as_str())
+        .filter_map(parse_timestamp)
+        .max()
+}
+
+#[tauri::command]
+pub fn find_duplicate_assets() -> Result<Vec<DuplicateGroup>, String> {
+    let assets = read_asset_values()?;
+    Ok(group_assets_by_location(&assets)
+        .into_iter()
+        .filter(|(_, indices)| indices.len() > 1)
+        .map(|(location, indices)| DuplicateGroup {
+            location,
+            count: indices.len(),
+            ids: indices
+                .iter()
+                .map(|&i| assets[i]["id"].as_str().unwrap_or_default().to_string())
+                .collect(),
+        })
+        .collect())
+}
+
+// 每个 location 只保留更新时间最新的一条（时间相同或缺失时保留靠后的），先备份再重写索引。
+// 返回删除的条目数
+#[tauri::command]
+pub fn dedupe_assets() -> Result<usize, String> {
+    let path = get_asset_index_path();
+    let _lock = lock_data_file(&path)?;
+    let assets = read_asset_values()?;
+
+    let mut keep: HashSet<usize> = HashSet::new();
+    for indices in group_assets_by_location(&assets).into_values() {
+        let newest = indices
+            .iter()
+            .copied()
+            .max_by_key(|&i| (asset_updated_at(&assets[i]), i))
+            .expect("分组至少有一个元素");
+        keep.insert(newest);
+    }
+
+    // 没有 location 的条目不参与去重，原样保留
+    let deduped: Vec<serde_json::Value> = assets
+        .iter()
+        .enumerate()
+        .filter(|(i, asset)| keep.contains(i) || asset["location"].as_str().is_none())
+        .map(|(_, asset)| asset.clone())
+        .collect();
+    let removed = assets.len() - deduped.len();
+    if removed == 0 {
+        return Ok(0);
+    }
+
+    backup_file(&path)?;
+    write_json_atomic(&path, &deduped)?;
+    Ok(removed)
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct AssetMergeSummary {
+    pub added: usize,
+    pub updated: usize,
+    pub marked_dead: usize,
+}
+
+// 把一次新扫描的结果合并进现有索引（按规范化 location 匹配）：
+// - 匹配到的只更新扫描得到的字段 alive / line_count / language / blob_sha，exempt、content_hash、标签等保留；
+//   blob_sha 变了的远程文件清掉 content_hash；
+//   原本已死的文件重新出现时清掉 tombstoneId，之后再次删除会重新立碑
+// - 新出现的追加到末尾
+// - 在 in_scope 范围内、这次没扫到的标记 alive = false 而不是删除，之后照常立碑
+// TypeScript 端写的 lineCount 同步更新，避免两种写法不一致
+fn merge_asset_values(
+    existing: &mut Vec<serde_json::Value>,
+    fresh: Vec<Asset>,
+    in_scope: impl Fn(&str) -> bool,
+) -> Result<AssetMergeSummary, String> {
+    let mut summary = AssetMergeSummary::default();
+    let mut fresh_by_location: HashMap<String, Asset> = fresh
+        .into_iter()
+        .map(|a| (normalize_repo_path(&a.location), a))
+        .collect();
+
+    for asset in existing.iter_mut() {
+        let Some(location) = asset["location"].as_str().map(normalize_repo_path) else {
+            continue;
+        };
+        match fresh_by_location.remove(&location) {
+            Some(found) => {
+                if !asset["alive"].as_bool().unwrap_or(false) {
+                    if let Some(fields) = asset.as_object_mut() {
+                        fields.remove("tombstoneId");
+                        fields.remove("tombstone_id");
+                    }
+                }
+                asset["alive"] = serde_json::json!(true);
+                asset["line_count"] = serde_json::json!(found.line_count);
+                if asset.get("lineCount").is_some() {
+                    asset["lineCount"] = serde_json::json!(found.line_count);
+                }
+                asset["language"] = serde_json::json!(found.language);
+                if found.blob_sha.is_some() {
+                    // 内容变了，旧哈希作废，等 hash_remote_blobs 重算
+                    if asset["blob_sha"].as_str() != found.blob_sha.as_deref() {
+                        if let Some(fields) = asset.as_object_mut() {
+                            fields.remove("content_hash");
+                        }
+                    }
+                    asset["blob_sha"] = serde_json::json!(found.blob_sha);
+                }
+                summary.updated += 1;
+            }
+            None if asset["alive"].as_bool().unwrap_or(false) && in_scope(&location) => {
+                asset["alive"] = serde_json::json!(false);
+                summary.marked_dead += 1;
+            }
+            None => {}
+        }
+    }
+
+    let mut added: Vec<Asset> = fresh_by_location.into_values().collect();
+    added.sort_by(|a, b| a.location.cmp(&b.location));
+    summary.added = added.len();
+    for asset in added {
+        existing.push(serde_json::to_value(&asset).map_err(|e| format!("序列化资产失败: {}", e))?);
+    }
+    Ok(summary)
+}
+
+// scope 为这次扫描覆盖的路径前缀（例如某个仓库目录），只有其中没扫到的资产会被标记死亡；
+// 为空时视为扫描了整个索引
+#[tauri::command]
+pub fn merge_asset_index(assets: Vec<Asset>, scope: Option<String>) -> Result<AssetMergeSummary, String> {
+    let scope = trim_optional(scope).map(|s| normalize_repo_path(&s));
+    let path = get_asset_index_path();
+    let _lock = lock_data_file(&path)?;
+    let mut existing = read_asset_values()?;
+
+    let summary = merge_asset_values(&mut existing, assets, |location| {
+        scope.as_deref().map_or(true, |prefix| location.starts_with(prefix))
+    })?;
+    if summary.added + summary.updated + summary.marked_dead == 0 {
+        return Ok(summary);
+    }
+
+    backup_file(&path)?;
+    write_json_atomic(&path, &existing)?;
+    Ok(summary)
+}
+
+// ========== 墓地分析 ==========
+
+// 只读分析命令：读取失败时记录日志并按空墓地处理
+fn load_tombstones_or_log() -> Vec<Tombstone> {
+    read_tombstones().unwrap_or_else(|e| {
+        eprintln!("⚠️ {}", e);
+        vec![]
+    })
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CauseImpact {
+    pub cause: String,
+    pub tombstones: usize,
+    pub total_lines: usize,
+    pub pct_of_all_lines: f64,
+}
+
+// 按死因统计死代码行数占比（只算未复活的墓碑），按行数降序
+#[tauri::command]
+pub fn get_cause_impact() -> Vec<CauseImpact> {
+    let mut by_cause: HashMap<String, (usize, usize)> = HashMap::new();
+    let mut all_lines = 0;
+
+    for t in load_tombstones_or_log().iter().filter(|t| t.resurrected_at.is_none()) {
+        let cause = match t.cause_of_death.trim() {
+            "" => tr("stats.unknown"),
+            cause => cause.to_string(),
+        };
+        let entry = by_cause.entry(cause).or_insert((0, 0));
+        entry.0 += 1;
+        entry.1 += t.line_count;
+        all_lines += t.line_count;
+    }
+
+    let mut impacts: Vec<CauseImpact> = by_cause
+        .into_iter()
+        .map(|(cause, (tombstones, total_lines))| CauseImpact {
+            cause,
+            tombstones,
+            total_lines,
+            pct_of_all_lines: if all_lines == 0 {
+                0.0
+            } else {
+                total_lines as f64 * 100.0 / all_lines as f64
+            },
+        })
+        .collect();
+
+    impacts.sort_by(|a, b| {
+        b.total_lines
+            .cmp(&a.total_lines)
+            .then(b.tombstones.cmp(&a.tombstones))
+            .then(a.cause.cmp(&b.cause))
+    });
+    impacts
+}
+
+fn language_key(language: &Option<String>) -> String {
+    language
+        .as_deref()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .unwrap_or("Unknown")
+        .to_string()
+}
+
+// 各语言的复活率（复活墓碑数 / 该语言墓碑总数），样本少于 resurrection_min_count 的语言不计入
+#[tauri::command]
+pub fn get_resurrection_by_language() -> Vec<(String, f64)> {
+    let min_count = resolve_config()
+        .map(|c| c.resurrection_min_count)
+        .unwrap_or(3)
+        .max(1);
+
+    let mut by_language: HashMap<String, (usize, usize)> = HashMap::new();
+    for t in load_tombstones_or_log() {
+        let entry = by_language.entry(language_key(&t.language)).or_insert((0, 0));
+        entry.0 += 1;
+        if t.resurrected_at.is_some() {
+            entry.1 += 1;
+        }
+    }
+
+    let mut rates: Vec<(String, f64)> = by_language
+        .into_iter()
+        .filter(|(_, (total, _))| *total >= min_count)
+        .map(|(language, (total, resurrected))| (language, resurrected as f64 / total as f64))
+        .collect();
+
+    rates.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+    rates
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct LangRatio {
+    pub language: String,
+    pub alive: usize,
+    pub dead: usize,
+    pub ratio: f64,
+}
+
+// 各语言死/活比：活的来自资产索引，死的是未复活的墓碑。
+// 某语言没有活文件时按 alive = 1 计算，避免除零，同时仍排在前面
+#[tauri::command]
+pub fn get_alive_dead_ratio() -> Result<Vec<LangRatio>, String> {
+    let mut counts: HashMap<String, (usize, usize)> = HashMap::new();
+    for asset in read_assets()?.into_iter().filter(|a| a.alive) {
+        counts.entry(language_key(&asset.language)).or_insert((0, 0)).0 += 1;
+    }
+    for t in read_tombstones()? {
+        if t.resurrected_at.is_none() {
+            counts.entry(language_key(&t.language)).or_insert((0, 0)).1 += 1;
+        }
+    }
+
+    let mut ratios: Vec<LangRatio> = counts
+        .into_iter()
+        .map(|(language, (alive, dead))| LangRatio {
+            language,
+            alive,
+            dead,
+            ratio: dead as f64 / alive.max(1) as f64,
+        })
+        .collect();
+
+    ratios.sort_by(|a, b| b.ratio.total_cmp(&a.ratio).then(a.language.cmp(&b.language)));
+    Ok(ratios)
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct LangShare {
+    pub language: String,
+    pub count: usize,
+    pub probability: f64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct LanguageEntropy {
+    pub entropy: f64,
+    pub shares: Vec<LangShare>,
+}
+
+// 香农熵 H = -Σ p·log2(p)，单位 bit：全是一种语言时为 0，n 种语言均匀分布时为 log2(n)
+fn shannon_entropy(counts: &[usize]) -> f64 {
+    let total: usize = counts.iter().sum();
+    if total == 0 {
+        return 0.0;
+    }
+    counts
+        .iter()
+        .filter(|&&c| c > 0)
+        .map(|&c| {
+            let p = c as f64 / total as f64;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+// 墓地语言的多样性：对所有墓碑按语言分布求香农熵，同时返回各语言占比便于核对。
+// 没有语言信息的归入 Unknown 单独计一类
+#[tauri::command]
+pub fn get_language_entropy() -> Result<LanguageEntropy, String> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for t in read_tombstones()? {
+        *counts.entry(language_key(&t.language)).or_insert(0) += 1;
+    }
+
+    let total: usize = counts.values().sum();
+    let mut shares: Vec<LangShare> = counts
+        .into_iter()
+        .map(|(language, count)| LangShare {
+            language,
+            count,
+            probability: count as f64 / total as f64,
+        })
+        .collect();
+    shares.sort_by(|a, b| b.count.cmp(&a.count).then(a.language.cmp(&b.language)));
+
+    let counts: Vec<usize> = shares.iter().map(|s| s.count).collect();
+    Ok(LanguageEntropy {
+        entropy: shannon_entropy(&counts),
+        shares,
+    })
+}
+
+// 一条诈尸提醒的风险相当于多少个存活文件：提醒说明仓库正在接收复活的死代码，比单纯体量大更值得关注
+const AT_RISK_ALERT_WEIGHT: f64 = 10.0;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AtRiskRepo {
+    pub repo: String,
+    pub alive_assets: usize,
+    pub zombie_alerts: usize,
+    pub score: f64,
+}
+
+// 各仓库的风险排名：score = 存活资产数 + AT_RISK_ALERT_WEIGHT × 该仓库作为 zombie_repo 的提醒数。
+// 仓库名不区分大小写合并；没有 repo 字段的资产不计入
+#[tauri::command]
+pub fn get_at_risk_repos() -> Result<Vec<AtRiskRepo>, String> {
+    fn entry<'a>(repos: &'a mut HashMap<String, AtRiskRepo>, repo: &str) -> &'a mut AtRiskRepo {
+        repos.entry(repo.to_lowercase()).or_insert_with(|| AtRiskRepo {
+            repo: repo.to_string(),
+            alive_assets: 0,
+            zombie_alerts: 0,
+            score: 0.0,
+        })
+    }
+
+    let mut repos: HashMap<String, AtRiskRepo> = HashMap::new();
+    for asset in read_assets()?.into_iter().filter(|a| a.alive) {
+        if let Some(repo) = asset.repo.as_deref().map(str::trim).filter(|r| !r.is_empty()) {
+            entry(&mut repos, repo).alive_assets += 1;
+        }
+    }
+    for alert in get_zombie_alerts().alerts {
+        let repo = alert.zombie_repo.trim();
+        if !repo.is_empty() {
+            entry(&mut repos, repo).zombie_alerts += 1;
+        }
+    }
+
+    let mut ranked: Vec<AtRiskRepo> = repos
+        .into_values()
+        .map(|mut r| {
+            r.score = r.alive_assets as f64 + AT_RISK_ALERT_WEIGHT * r.zombie_alerts as f64;
+            r
+        })
+        .collect();
+    ranked.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.repo.cmp(&b.repo)));
+    Ok(ranked)
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TagPair {
+    pub a: String,
+    pub b: String,
+    pub count: usize,
+}
+
+// 同一墓碑上同时出现的标签对（不区分大小写、无序，a < b），按次数降序。
+// 每个墓碑的标签先去重，同一对在一个墓碑上只算一次；少于两个标签的墓碑不计入
+#[tauri::command]
+pub fn get_tag_cooccurrence() -> Vec<TagPair> {
+    let mut counts: HashMap<(String, String), usize> = HashMap::new();
+    for t in load_tombstones_or_log() {
+        let tags: Vec<String> = t
+            .tags
+            .iter()
+            .map(|tag| tag.trim().to_lowercase())
+            .filter(|tag| !tag.is_empty())
+            .collect::<std::collections::BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        for (i, a) in tags.iter().enumerate() {
+            for b in &tags[i + 1..] {
+                *counts.entry((a.clone(), b.clone())).or_insert(0) += 1;
+            }
+        }
+    }
+
+    let mut pairs: Vec<TagPair> = counts
+        .into_iter()
+        .map(|((a, b), count)| TagPair { a, b, count })
+        .collect();
+    pairs.sort_by(|x, y| {
+        y.count
+            .cmp(&x.count)
+            .then_with(|| x.a.cmp(&y.a))
+            .then_with(|| x.b.cmp(&y.b))
+    });
+    pairs
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ResurrectionAge {
+    pub tombstone_id: String,
+    pub name: String,
+    pub died_at: String,
+    pub resurrected_at: String,
+    pub resurrected_to: Option<String>,
+    pub days_dead: i64,
+}
+
+// 死得最久才复活的代码：按 died_at 到 resurrected_at 的整天数降序取前 limit 个。
+// 任一时间无法解析、或复活早于死亡（数据有误）的墓碑跳过
+#[tauri::command]
+pub fn get_longest_resurrections(limit: i32) -> Vec<ResurrectionAge> {
+    let mut ages: Vec<ResurrectionAge> = load_tombstones_or_log()
+        .into_iter()
+        .filter_map(|t| {
+            let resurrected_at = t.resurrected_at.clone()?;
+            let gap = parse_timestamp(&resurrected_at)? - parse_timestamp(&t.died_at)?;
+            if gap < Duration::zero() {
+                return None;
+            }
+            Some(ResurrectionAge {
+                tombstone_id: t.id,
+                name: t.name,
+                died_at: t.died_at,
+                resurrected_at,
+                resurrected_to: t.resurrected_to,
+                days_dead: gap.num_days(),
+            })
+        })
+        .collect();
+
+    ages.sort_by(|a, b| {
+        b.days_dead
+            .cmp(&a.days_dead)
+            .then_with(|| a.tombstone_id.cmp(&b.tombstone_id))
+    });
+    ages.truncate(limit.max(0) as usize);
+    ages
+}
+
+// 少于这么多个可用样本时不给出半衰期
+const HALFLIFE_MIN_SAMPLES: usize = 5;
+
+// 复活"半衰期"：在最终复活了的代码里，一半在死后多少天内回来。
+// 只看已复活的墓碑（以"终将复活"为条件，不需要处理删失），取死亡到复活间隔的经验中位数，
+// 样本数为偶数时取中间两个的平均；间隔按小时精度折算成天。
+// 时间无法解析或复活早于死亡的墓碑跳过，剩余样本不足 HALFLIFE_MIN_SAMPLES 时返回 None
+fn resurrection_halflife(tombstones: &[Tombstone]) -> Option<f64> {
+    let mut gaps: Vec<f64> = tombstones
+        .iter()
+        .filter_map(|t| {
+            let gap = parse_timestamp(t.resurrected_at.as_deref()?)? - parse_timestamp(&t.died_at)?;
+            (gap >= Duration::zero()).then(|| gap.num_hours() as f64 / 24.0)
+        })
+        .collect();
+    if gaps.len() < HALFLIFE_MIN_SAMPLES {
+        return None;
+    }
+
+    gaps.sort_by(f64::total_cmp);
+    let mid = gaps.len() / 2;
+    Some(if gaps.len() % 2 == 0 {
+        (gaps[mid - 1] + gaps[mid]) / 2.0
+    } else {
+        gaps[mid]
+    })
+}
+
+#[tauri::command]
+pub fn get_resurrection_halflife() -> Option<f64> {
+    resurrection_halflife(&load_tombstones_or_log())
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GraveyardScore {
+    pub total: u64,
+    pub rank: String,
+    pub corpse_points: u64,
+    pub line_points: u64,
+    pub diversity_points: u64,
+    pub resurrection_points: u64,
+    pub corpses: usize,
+    pub dead_lines: usize,
+    pub languages: usize,
+    pub resurrection_rate: f64,
+}
+
+// 墓地积分公式，调整时只改这里：
+// - 每个未复活墓碑 10 分
+// - 死代码行数取 sqrt 后 ×2，避免一个巨型文件刷分
+// - 每种语言（含 Unknown）25 分
+// - 复活率 × 200，复活也算成就
+// 段位：<100 Gravedigger，<500 Gravekeeper，<2000 Undertaker，<5000 Necromancer，其余 Lich King
+fn graveyard_rank(total: u64) -> &'static str {
+    match total {
+        0..=99 => "Gravedigger",
+        100..=499 => "Gravekeeper",
+        500..=1999 => "Undertaker",
+        2000..=4999 => "Necromancer",
+        _ => "Lich King",
+    }
+}
+
+fn compute_graveyard_score(tombstones: &[Tombstone]) -> GraveyardScore {
+    let corpses: Vec<&Tombstone> = tombstones.iter().filter(|t| t.resurrected_at.is_none()).collect();
+    let dead_lines: usize = corpses.iter().map(|t| t.line_count).sum();
+    let languages: HashSet<String> = tombstones.iter().map(|t| language_key(&t.language)).collect();
+    let resurrection_rate = if tombstones.is_empty() {
+        0.0
+    } else {
+        (tombstones.len() - corpses.len()) as f64 / tombstones.len() as f64
+    };
+
+    let corpse_points = corpses.len() as u64 * 10;
+    let line_points = ((dead_lines as f64).sqrt() * 2.0).round() as u64;
+    let diversity_points = languages.len() as u64 * 25;
+    let resurrection_points = (resurrection_rate * 200.0).round() as u64;
+    let total = corpse_points + line_points + diversity_points + resurrection_points;
+
+    GraveyardScore {
+        total,
+        rank: graveyard_rank(total).to_string(),
+        corpse_points,
+        line_points,
+        diversity_points,
+        resurrection_points,
+        corpses: corpses.len(),
+        dead_lines,
+        languages: languages.len(),
+        resurrection_rate,
+    }
+}
+
+#[tauri::command]
+pub fn get_graveyard_score() -> GraveyardScore {
+    compute_graveyard_score(&load_tombstones_or_log())
+}
+
+// 默认产出：每人每天 50 行有效代码；每月按 21 个工作日计
+const DEFAULT_LINES_PER_DAY: f64 = 50.0;
+const WORKING_DAYS_PER_MONTH: f64 = 21.0;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct EffortEstimate {
+    pub dead_lines: usize,
+    pub lines_per_day: f64,
+    pub person_days: f64,
+    pub person_months: f64,
+    pub cost: Option<f64>,
+}
+
+// 人天 = 未复活墓碑的总行数 / 每天行数；lines_per_day 非正数或非有限值时用默认值，
+// 给了 cost_per_day 才计算金额
+fn compute_effort_estimate(dead_lines: usize, lines_per_day: f64, cost_per_day: Option<f64>) -> EffortEstimate {
+    let lines_per_day = if lines_per_day.is_finite() && lines_per_day > 0.0 {
+        lines_per_day
+    } else {
+        DEFAULT_LINES_PER_DAY
+    };
+    let person_days = dead_lines as f64 / lines_per_day;
+    EffortEstimate {
+        dead_lines,
+        lines_per_day,
+        person_days,
+        person_months: person_days / WORKING_DAYS_PER_MONTH,
+        cost: cost_per_day
+            .filter(|cost| cost.is_finite() && *cost >= 0.0)
+            .map(|cost| person_days * cost),
+    }
+}
+
+#[tauri::command]
+pub fn get_effort_estimate(lines_per_day: f64, cost_per_day: Option<f64>) -> EffortEstimate {
+    let dead_lines = load_tombstones_or_log()
+        .iter()
+        .filter(|t| t.resurrected_at.is_none())
+        .map(|t| t.line_count)
+        .sum();
+    compute_effort_estimate(dead_lines, lines_per_day, cost_per_day)
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Flow {
+    pub from_repo: String,
+    pub to_repo: String,
+    pub count: usize,
+}
+
+// 从 resurrected_to 中解析目标仓库，支持：
+// - https://github.com/owner/repo/...（以及不带协议的 github.com/owner/repo）
+// - owner/repo:path 或 repo:path
+// - 恰好两段的 owner/repo
+// 其他形式（例如单纯的文件路径）无法确定仓库，返回 None
+fn parse_destination_repo(resurrected_to: &str) -> Option<String> {
+    let target = resurrected_to.trim();
+    if let Some(index) = target.find("github.com/") {
+        let mut segments = target[index + "github.com/".len()..].split('/').filter(|s| !s.is_empty());
+        let owner = segments.next()?;
+        let repo = segments.next()?.trim_end_matches(".git");
+        return Some(format!("{}/{}", owner, repo));
+    }
+    if let Some((repo, _)) = target.split_once(':') {
+        let repo = repo.trim();
+        return Some(repo.to_string()).filter(|r| !r.is_empty() && !r.contains(char::is_whitespace));
+    }
+
+    let segments: Vec<&str> = target.split('/').collect();
+    match segments.as_slice() {
+        [owner, repo] if !owner.is_empty() && !repo.is_empty() && !repo.contains('.') => {
+            Some(target.to_string())
+        }
+        _ => None,
+    }
+}
+
+// 复活路径的仓库流向（桑基图数据），目标仓库无法解析的记为 unknown
+#[tauri::command]
+pub fn get_resurrection_flows() -> Vec<Flow> {
+    let mut flows: HashMap<(String, String), usize> = HashMap::new();
+    for t in load_tombstones_or_log() {
+        let (Some(from_repo), Some(resurrected_to)) = (t.repo, t.resurrected_to) else {
+            continue;
+        };
+        let to_repo = parse_destination_repo(&resurrected_to).unwrap_or_else(|| String::from("unknown"));
+        *flows.entry((from_repo, to_repo)).or_insert(0) += 1;
+    }
+
+    let mut result: Vec<Flow> = flows
+        .into_iter()
+        .map(|((from_repo, to_repo), count)| Flow {
+            from_repo,
+            to_repo,
+            count,
+        })
+        .collect();
+    result.sort_by(|a, b| {
+        b.count
+            .cmp(&a.count)
+            .then_with(|| a.from_repo.cmp(&b.from_repo))
+            .then_with(|| a.to_repo.cmp(&b.to_repo))
+    });
+    result
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ChurnPoint {
+    pub week_start: String,
+    pub deaths: usize,
+    pub resurrections: usize,
+    pub net: i64,
+}
+
+fn week_start_of(date: NaiveDate) -> NaiveDate {
+    use chrono::Datelike;
+    date - Duration::days(date.weekday().num_days_from_monday() as i64)
+}
+
+// 最近 weeks 周（周一为起点，含本周）每周死亡与复活数，没有事件的周也输出 0 点
+#[tauri::command]
+pub fn get_churn_rate(weeks: u32) -> Vec<ChurnPoint> {
+    if weeks == 0 {
+        return vec![];
+    }
+
+    let tz = display_tz();
+    let current_week = week_start_of(date_in(&tz, Utc::now()));
+    let first_week = current_week - Duration::weeks(weeks as i64 - 1);
+    let mut buckets: BTreeMap<NaiveDate, (usize, usize)> = (0..weeks as i64)
+        .map(|i| (first_week + Duration::weeks(i), (0, 0)))
+        .collect();
+
+    let mut unparseable = 0;
+    let mut count_event = |raw: &str, is_death: bool| {
+        let Some(at) = parse_timestamp(raw) else {
+            unparseable += 1;
+            return;
+        };
+        if let Some(bucket) = buckets.get_mut(&week_start_of(date_in(&tz, at))) {
+            if is_death {
+                bucket.0 += 1;
+            } else {
+                bucket.1 += 1;
+            }
+        }
+    };
+
+    for t in load_tombstones_or_log() {
+        count_event(&t.died_at, true);
+        if let Some(resurrected_at) = &t.resurrected_at {
+            count_event(resurrected_at, false);
+        }
+    }
+
+    if unparseable > 0 {
+        eprintln!("⚠️ 流失率统计跳过 {} 个无法解析的日期", unparseable);
+    }
+
+    buckets
+        .into_iter()
+        .map(|(week, (deaths, resurrections))| ChurnPoint {
+            week_start: week.format("%Y-%m-%d").to_string(),
+            deaths,
+            resurrections,
+            net: resurrections as i64 - deaths as i64,
+        })
+        .collect()
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TimelinePoint {
+    pub period: String,
+    pub deaths: usize,
+}
+
+// 时间线最多输出的点数，避免按天统计多年数据时一次返回上万个点
+const MAX_TIMELINE_POINTS: usize = 1000;
+
+const TIMELINE_BUCKETS: &[&str] = &["day", "week", "month"];
+
+fn timeline_bucket_start(bucket: &str, date: NaiveDate) -> NaiveDate {
+    use chrono::Datelike;
+    match bucket {
+        "week" => week_start_of(date),
+        "month" => date.with_day(1).unwrap_or(date),
+        _ => date,
+    }
+}
+
+fn next_timeline_bucket(bucket: &str, start: NaiveDate) -> NaiveDate {
+    match bucket {
+        "week" => start + Duration::weeks(1),
+        "month" => start
+            .checked_add_months(chrono::Months::new(1))
+            .unwrap_or(start + Duration::days(31)),
+        _ => start + Duration::days(1),
+    }
+}
+
+// 按 bucket（day / week / month，显示时区）把墓碑按死亡时间分组：从最早一次死亡到最近一次
+// 的每个区间都输出（没有死亡的区间为空列表），超过 MAX_TIMELINE_POINTS 时只保留最近的部分。
+// 死亡时间无法解析的墓碑不计入
+fn bucket_tombstones_by_death(bucket: &str, tombstones: Vec<Tombstone>) -> Result<Vec<(String, Vec<Tombstone>)>, String> {
+    let bucket = bucket.trim().to_lowercase();
+    if !TIMELINE_BUCKETS.contains(&bucket.as_str()) {
+        return Err(format!("不支持的时间粒度: {}（可选 {}）", bucket, TIMELINE_BUCKETS.join(" / ")));
+    }
+
+    let tz = display_tz();
+    let mut groups: BTreeMap<NaiveDate, Vec<Tombstone>> = BTreeMap::new();
+    for t in tombstones {
+        if let Some(at) = parse_timestamp(&t.died_at) {
+            groups.entry(timeline_bucket_start(&bucket, date_in(&tz, at))).or_default().push(t);
+        }
+    }
+
+    let (Some(&first), Some(&last)) = (groups.keys().next(), groups.keys().next_back()) else {
+        return Ok(vec![]);
+    };
+
+    let format = if bucket == "month" { "%Y-%m" } else { "%Y-%m-%d" };
+    let mut periods = vec![];
+    let mut current = first;
+    while current <= last {
+        periods.push((current.format(format).to_string(), groups.remove(&current).unwrap_or_default()));
+        current = next_timeline_bucket(&bucket, current);
+    }
+    if periods.len() > MAX_TIMELINE_POINTS {
+        periods.drain(..periods.len() - MAX_TIMELINE_POINTS);
+    }
+    Ok(periods)
+}
+
+// 死亡时间线：每个区间的死亡数，中间没有死亡的区间也输出 0 点
+#[tauri::command]
+pub fn get_death_timeline(bucket: String) -> Result<Vec<TimelinePoint>, String> {
+    Ok(bucket_tombstones_by_death(&bucket, load_tombstones_or_log())?
+        .into_iter()
+        .map(|(period, tombstones)| TimelinePoint {
+            period,
+            deaths: tombstones.len(),
+        })
+        .collect())
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SizeTrendPoint {
+    pub period: String,
+    // 没有死亡的区间为 null 而不是 0：0 会被画成"死掉的都是空文件"，拉低趋势线
+    pub avg_line_count: Option<f64>,
+    pub death_count: usize,
+}
+
+// 每个区间死亡墓碑的平均行数，看清理的是越来越大的模块还是零碎小文件
+#[tauri::command]
+pub fn get_avg_size_trend(bucket: String) -> Result<Vec<SizeTrendPoint>, String> {
+    Ok(bucket_tombstones_by_death(&bucket, load_tombstones_or_log())?
+        .into_iter()
+        .map(|(period, tombstones)| {
+            let total: usize = tombstones.iter().map(|t| t.line_count).sum();
+            SizeTrendPoint {
+                period,
+                avg_line_count: (!tombstones.is_empty()).then(|| total as f64 / tombstones.len() as f64),
+                death_count: tombstones.len(),
+            }
+        })
+        .collect())
+}
+
+// 按显示时区的星期几统计死亡数，下标 0 = 周一 … 6 = 周日
+#[tauri::command]
+pub fn get_deaths_by_weekday() -> [usize; 7] {
+    use chrono::Datelike;
+    let tz = display_tz();
+    let mut counts = [0; 7];
+    for t in load_tombstones_or_log() {
+        if let Some(at) = parse_timestamp(&t.died_at) {
+            let weekday = date_in(&tz, at).weekday();
+            counts[weekday.num_days_from_monday() as usize] += 1;
+        }
+    }
+    counts
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct WorstDay {
+    pub date: String,
+    pub count: usize,
+    pub tombstone_ids: Vec<String>,
+}
+
+// 死亡最多的一天（按显示时区划分日期），并列时取最近的一天
+#[tauri::command]
+pub fn get_worst_day() -> Option<WorstDay> {
+    let tz = display_tz();
+    let mut by_day: BTreeMap<NaiveDate, Vec<String>> = BTreeMap::new();
+    for t in load_tombstones_or_log() {
+        if let Some(at) = parse_timestamp(&t.died_at) {
+            let date = date_in(&tz, at);
+            by_day.entry(date).or_default().push(t.id);
+        }
+    }
+
+    // BTreeMap 按日期升序，max_by_key 在并列时返回最后一个，即最近的一天
+    by_day
+        .into_iter()
+        .max_by_key(|(_, ids)| ids.len())
+        .map(|(date, tombstone_ids)| WorstDay {
+            date: date.format("%Y-%m-%d").to_string(),
+            count: tombstone_ids.len(),
+            tombstone_ids,
+        })
+}
+
+// 日历热力图：year 年的每一天（闰年 366 天）与当天死亡数
+#[tauri::command]
+pub fn get_death_heatmap(year: i32) -> Result<Vec<(String, usize)>, String> {
+    let first = NaiveDate::from_ymd_opt(year, 1, 1).ok_or_else(|| format!("无效的年份: {}", year))?;
+    let next = NaiveDate::from_ymd_opt(year + 1, 1, 1).ok_or_else(|| format!("无效的年份: {}", year))?;
+
+    let tz = display_tz();
+    let mut counts: HashMap<NaiveDate, usize> = HashMap::new();
+    for t in load_tombstones_or_log() {
+        if let Some(at) = parse_timestamp(&t.died_at) {
+            let date = date_in(&tz, at);
+            if date >= first && date < next {
+                *counts.entry(date).or_insert(0) += 1;
+            }
+        }
+    }
+
+    Ok(first
+        .iter_days()
+        .take_while(|date| *date < next)
+        .map(|date| (date.format("%Y-%m-%d").to_string(), counts.get(&date).copied().unwrap_or(0)))
+        .collect())
+}
+
+// ========== 墓志铭 ==========
+
+// 模板文件格式（epitaph_templates_path 指向的 JSON）：
+// { "by_cause": { "<死因关键字>": ["..."] }, "by_language": { "<语言>": ["..."] }, "default": ["..."] }
+// 模板里可用 {name} {cause} {language} {lines} 占位符。
+// 匹配顺序：死因包含关键字（不区分大小写）→ 语言相同 → default；缺省的 default 使用内置模板
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[serde(default)]
+pub struct EpitaphBank {
+    pub by_cause: BTreeMap<String, Vec<String>>,
+    pub by_language: BTreeMap<String, Vec<String>>,
+    pub default: Vec<String>,
+}
+
+impl EpitaphBank {
+    fn template_count(&self) -> usize {
+        self.by_cause.values().chain(self.by_language.values()).map(Vec::len).sum::<usize>()
+            + self.default.len()
+    }
+}
+
+fn template_map(entries: &[(&str, &[&str])]) -> BTreeMap<String, Vec<String>> {
+    entries
+        .iter()
+        .map(|(key, templates)| (key.to_string(), templates.iter().map(|t| t.to_string()).collect()))
+        .collect()
+}
+
+fn builtin_epitaphs() -> EpitaphBank {
+    EpitaphBank {
+        by_cause: template_map(&[
+            ("deprecated", &["曾经辉煌一时，如今只剩 @deprecated 标记"]),
+            ("refactor", &["{name} 没有死，它只是被重构成了更好的样子"]),
+            ("unused", &["{lines} 行代码，零个调用者"]),
+        ]),
+        by_language: template_map(&[
+            ("JavaScript", &["undefined is not a function，{name} 也不再是了"]),
+            ("Rust", &["借用检查器终于放过了 {name}"]),
+            ("Python", &["缩进对齐，安息吧 {name}"]),
+        ]),
+        default: vec![
+            String::from("{name} 死于 {cause}，享年 {lines} 行"),
+            String::from("这里长眠着 {name}，它曾经能跑"),
+            String::from("RIP {name}，git log 会记住你"),
+        ],
+    }
+}
+
+fn validate_epitaph_bank(bank: &EpitaphBank) -> Result<(), String> {
+    let all = bank
+        .by_cause
+        .values()
+        .chain(bank.by_language.values())
+        .chain(std::iter::once(&bank.default));
+    for templates in all {
+        if templates.iter().any(|t| t.trim().is_empty()) {
+            return Err(String::from("模板不能为空字符串"));
+        }
+    }
+    if bank.template_count() == 0 {
+        return Err(String::from("模板文件中没有任何模板"));
+    }
+    Ok(())
+}
+
+fn load_epitaph_bank() -> Result<EpitaphBank, String> {
+    let Some(path) = resolve_config()?.epitaph_templates_path else {
+        return Ok(builtin_epitaphs());
+    };
+
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("读取墓志铭模板失败: {}", e))?;
+    let mut bank: EpitaphBank = serde_json::from_str(&content)
+        .map_err(|e| format!("解析墓志铭模板失败: {}", e))?;
+    validate_epitaph_bank(&bank)?;
+
+    if bank.default.is_empty() {
+        bank.default = builtin_epitaphs().default;
+    }
+    Ok(bank)
+}
+
+fn epitaph_cache() -> &'static Mutex<Option<EpitaphBank>> {
+    static CACHE: OnceLock<Mutex<Option<EpitaphBank>>> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(None))
+}
+
+// 加载失败时打印原因并回退到内置模板
+fn current_epitaph_bank() -> EpitaphBank {
+    let mut cache = epitaph_cache().lock().unwrap_or_else(|e| e.into_inner());
+    cache
+        .get_or_insert_with(|| {
+            load_epitaph_bank().unwrap_or_else(|e| {
+                eprintln!("⚠️ {}，使用内置墓志铭模板", e);
+                builtin_epitaphs()
+            })
+        })
+        .clone()
+}
+
+// 修改模板文件后无需重启，返回加载的模板数量
+#[tauri::command]
+pub fn reload_epitaph_templates() -> Result<usize, String> {
+    let loaded = load_epitaph_bank();
+    let bank = loaded.clone().unwrap_or_else(|_| builtin_epitaphs());
+    let count = bank.template_count();
+    *epitaph_cache().lock().unwrap_or_else(|e| e.into_inner()) = Some(bank);
+    loaded.map(|_| count)
+}
+
+// 同样的输入总是得到同样的墓志铭
+#[tauri::command]
+pub fn suggest_epitaph(name: String, cause: String, language: Option<String>, line_count: Option<usize>) -> String {
+    let bank = current_epitaph_bank();
+    let cause_lower = cause.to_lowercase();
+
+    let templates = bank
+        .by_cause
+        .iter()
+        .find(|(key, templates)| !templates.is_empty() && cause_lower.contains(&key.to_lowercase()))
+        .map(|(_, templates)| templates)
+        .or_else(|| {
+            language.as_deref().and_then(|language| {
+                bank.by_language
+                    .iter()
+                    .find(|(key, templates)| !templates.is_empty() && key.eq_ignore_ascii_case(language))
+                    .map(|(_, templates)| templates)
+            })
+        })
+        .unwrap_or(&bank.default);
+
+    if templates.is_empty() {
+        return format!("RIP {}", name);
+    }
+
+    let template = &templates[(stable_hash(&name) % templates.len() as u64) as usize];
+    template
+        .replace("{name}", &name)
+        .replace("{cause}", &cause)
+        .replace("{language}", language.as_deref().unwrap_or("Unknown"))
+        .replace("{lines}", &line_count.unwrap_or(0).to_string())
+}
+
+// ========== 墓碑校验 ==========
+
+const MAX_PLAUSIBLE_LINE_COUNT: usize = 1_000_000;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ValidationError {
+    pub field: String,
+    pub message: String,
+}
+
+fn validation_error(field: &str, message: impl Into<String>) -> ValidationError {
+    ValidationError {
+        field: field.to_string(),
+        message: message.into(),
+    }
+}
+
+fn trim_optional(value: Option<String>) -> Option<String> {
+    value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty())
+}
+
+// 新增/导入墓碑共用的校验：修剪字符串、标签小写去重、时间统一为 UTC RFC3339。
+// 一次返回全部错误，而不是遇到第一个就失败
+#[tauri::command]
+pub fn normalize_tombstone(t: Tombstone) -> Result<Tombstone, Vec<ValidationError>> {
+    let mut errors = vec![];
+
+    let id = t.id.trim().to_string();
+    if id.is_empty() {
+        errors.push(validation_error("id", "不能为空"));
+    }
+    let name = t.name.trim().to_string();
+    if name.is_empty() {
+        errors.push(validation_error("name", "不能为空"));
+    }
+    let original_path = t.original_path.trim().to_string();
+    if original_path.is_empty() {
+        errors.push(validation_error("original_path", "不能为空"));
+    }
+    if t.line_count > MAX_PLAUSIBLE_LINE_COUNT {
+        errors.push(validation_error(
+            "line_count",
+            format!("{} 行不合理（上限 {}）", t.line_count, MAX_PLAUSIBLE_LINE_COUNT),
+        ));
+    }
+
+    let died_at = match parse_timestamp(&t.died_at) {
+        Some(at) => at.to_rfc3339(),
+        None => {
+            errors.push(validation_error("died_at", format!("无法解析的时间: {}", t.died_at)));
+            String::new()
+        }
+    };
+    let resurrected_at = match trim_optional(t.resurrected_at) {
+        Some(raw) => match parse_timestamp(&raw) {
+            Some(at) => Some(at.to_rfc3339()),
+            None => {
+                errors.push(validation_error("resurrected_at", format!("无法解析的时间: {}", raw)));
+                None
+            }
+        },
+        None => None,
+    };
+    // 没有复活时间时复活去向没有意义
+    let resurrected_to = trim_optional(t.resurrected_to).filter(|_| resurrected_at.is_some());
+
+    let mut seen = HashSet::new();
+    let tags: Vec<String> = t
+        .tags
+        .iter()
+        .map(|tag| tag.trim().to_lowercase())
+        .filter(|tag| !tag.is_empty() && seen.insert(tag.clone()))
+        .collect();
+
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    Ok(Tombstone {
+        id,
+        name,
+        cause_of_death: t.cause_of_death.trim().to_string(),
+        epitaph: t.epitaph.trim().to_string(),
+        tags,
+        original_path,
+        language: trim_optional(t.language),
+        line_count: t.line_count,
+        died_at,
+        resurrected_at,
+        resurrected_to,
+        repo: trim_optional(t.repo),
+        pinned: t.pinned,
+        content_hash: trim_optional(t.content_hash),
+        attachments: t.attachments,
+        author: trim_optional(t.author),
+    })
+}
+
+// ========== 数据包导入导出 ==========
+
+const BUNDLE_SCHEMA_VERSION: u32 = 1;
+
+// 数据文件原样保存为 JSON（保留 TypeScript 端写入的额外字段），不存在的文件为 null
+#[derive(Serialize, Deserialize)]
+pub struct DataBundle {
+    pub schema_version: u32,
+    pub exported_at: String,
+    pub app_version: String,
+    pub config: Config,
+    pub assets: serde_json::Value,
+    pub tombstones: serde_json::Value,
+    pub alerts: serde_json::Value,
+}
+
+fn read_json_value(path: &Path) -> Result<serde_json::Value, String> {
+    if !data_file_exists(path) {
+        return Ok(serde_json::Value::Null);
+    }
+    let content = read_data_file(path)?;
+    serde_json::from_str(&content)
+        .map_err(|e| format!("解析 {} 失败: {}", path.display(), e))
+}
+
+// 导出配置（令牌和 webhook 密钥已移除）+ 资产索引 + 墓碑注册表 + 诈尸提醒，用于跨设备迁移
+#[tauri::command]
+pub fn export_bundle() -> Result<String, String> {
+    let mut config = load_config()?;
+    config.github_token = None;
+    config.webhook_secret = None;
+
+    let bundle = DataBundle {
+        schema_version: BUNDLE_SCHEMA_VERSION,
+        exported_at: Utc::now().to_rfc3339(),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        config,
+        assets: read_json_value(&get_asset_index_path())?,
+        tombstones: read_json_value(&get_tombstone_registry_path())?,
+        alerts: read_json_value(&get_zombie_alerts_path())?,
+    };
+
+    serde_json::to_string_pretty(&bundle)
+        .map_err(|e| format!("序列化数据包失败: {}", e))
+}
+
+// 导入前逐条校验墓碑，错误信息带上来源（数据包 / 状态快照）
+fn validate_tombstones_value(value: &serde_json::Value, source: &str) -> Result<(), String> {
+    let tombstones = serde_json::from_value::<Vec<Tombstone>>(value.clone())
+        .map_err(|e| format!("{}中的墓碑格式无效: {}", source, e))?;
+    let problems: Vec<String> = tombstones
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, t)| normalize_tombstone(t).err().map(|errors| (i, errors)))
+        .flat_map(|(i, errors)| {
+            errors
+                .into_iter()
+                .map(move |e| format!("#{} {}: {}", i, e.field, e.message))
+        })
+        .collect();
+    if !problems.is_empty() {
+        return Err(format!("{}中的墓碑校验失败:\n{}", source, problems.join("\n")));
+    }
+    Ok(())
+}
+
+// 恢复数据包：先整体校验再备份写入；数据包中为 null 的部分保持本地不变
+#[tauri::command]
+pub fn import_bundle(json: String, include_config: bool) -> Result<(), String> {
+    let bundle: DataBundle = serde_json::from_str(&json)
+        .map_err(|e| format!("解析数据包失败: {}", e))?;
+
+    if bundle.schema_version != BUNDLE_SCHEMA_VERSION {
+        return Err(format!(
+            "不支持的数据包版本: {}（当前支持 {}）",
+            bundle.schema_version, BUNDLE_SCHEMA_VERSION
+        ));
+    }
+    if !bundle.tombstones.is_null() {
+        validate_tombstones_value(&bundle.tombstones, "数据包")?;
+    }
+    if !(bundle.assets.is_null() || bundle.assets.is_array()) {
+        return Err(String::from("数据包中的资产索引必须是数组"));
+    }
+    if !(bundle.alerts.is_null() || bundle.alerts.is_object()) {
+        return Err(String::from("数据包中的诈尸提醒必须是对象"));
+    }
+
+    let targets = [
+        (get_tombstone_registry_path(), &bundle.tombstones),
+        (get_asset_index_path(), &bundle.assets),
+        (get_zombie_alerts_path(), &bundle.alerts),
+    ];
+    for (path, value) in targets.iter() {
+        if value.is_null() {
+            continue;
+        }
+        let _lock = lock_data_file(path)?;
+        backup_file(path)?;
+        write_json_atomic(path, value)?;
+    }
+
+    if include_config {
+        // 数据包里的令牌和密钥已被移除，保留本机的
+        let mut config = bundle.config;
+        let local = load_config()?;
+        config.github_token = local.github_token;
+        config.webhook_secret = local.webhook_secret;
+        save_config(&config)?;
+    }
+
+    Ok(())
+}
+
+// ========== 状态快照 ==========
+
+const SNAPSHOT_SCHEMA_VERSION: u32 = 1;
+
+// 与数据包不同，快照覆盖全部数据文件和调度状态，是可以直接贴进 issue 的自描述文本，
+// 用于复现问题和演示。文件按名称存放，不存在的为 null
+#[derive(Serialize, Deserialize)]
+pub struct StateSnapshot {
+    pub schema_version: u32,
+    pub exported_at: String,
+    pub app_version: String,
+    pub config: Config,
+    pub files: BTreeMap<String, serde_json::Value>,
+    pub ignore_rules: Option<String>,
+    pub scheduler: SchedulerSnapshot,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct SchedulerSnapshot {
+    pub paused: bool,
+    pub one_shot_at: Option<String>,
+}
+
+// 快照里的文件名 → 实际路径；路径都经过 get_*_path，跟随当前数据目录
+fn snapshot_files() -> [(&'static str, PathBuf); 6] {
+    [
+        ("asset-index", get_asset_index_path()),
+        ("tombstone-registry", get_tombstone_registry_path()),
+        ("archive", get_archive_path()),
+        ("zombie-alerts", get_zombie_alerts_path()),
+        ("undo-stack", get_undo_stack_path()),
+        ("stats-history", get_stats_history_path()),
+    ]
+}
+
+// 导出完整状态；配置中的令牌和 webhook 密钥已移除
+#[tauri::command]
+pub fn export_state_snapshot(state: tauri::State<'_, AppState>) -> Result<String, String> {
+    let snapshot = build_state_snapshot(state.scheduler_paused.load(std::sync::atomic::Ordering::Relaxed))?;
+    serde_json::to_string_pretty(&snapshot)
+        .map_err(|e| format!("序列化状态快照失败: {}", e))
+}
+
+fn build_state_snapshot(scheduler_paused: bool) -> Result<StateSnapshot, String> {
+    let mut config = load_config()?;
+    config.github_token = None;
+    config.webhook_secret = None;
+
+    let mut files = BTreeMap::new();
+    for (name, path) in snapshot_files() {
+        files.insert(name.to_string(), read_json_value(&path)?);
+    }
+
+    let ignore_path = get_cemetery_ignore_path();
+    let ignore_rules = if ignore_path.exists() {
+        Some(fs::read_to_string(&ignore_path).map_err(|e| format!("读取 {} 失败: {}", ignore_path.display(), e))?)
+    } else {
+        None
+    };
+
+    Ok(StateSnapshot {
+        schema_version: SNAPSHOT_SCHEMA_VERSION,
+        exported_at: Utc::now().to_rfc3339(),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        config,
+        files,
+        ignore_rules,
+        scheduler: SchedulerSnapshot {
+            paused: scheduler_paused,
+            one_shot_at: read_one_shot()?.map(|at| at.to_rfc3339()),
+        },
+    })
+}
+
+// 载入快照：先整体校验，再逐个备份并覆盖本地文件；快照中为 null 的文件保持本地不变，
+// 本机的令牌和 webhook 密钥保留。restart_scheduler 时按快照恢复暂停状态和单次扫描计划
+#[tauri::command]
+pub fn import_state_snapshot(app: tauri::AppHandle, json: String, restart_scheduler: bool) -> Result<(), String> {
+    let snapshot: StateSnapshot = serde_json::from_str(&json)
+        .map_err(|e| format!("解析状态快照失败: {}", e))?;
+    let (paused, one_shot_at) = restore_state_snapshot(snapshot)?;
+
+    if restart_scheduler {
+        app.state::<AppState>()
+            .scheduler_paused
+            .store(paused, std::sync::atomic::Ordering::Relaxed);
+        match one_shot_at {
+            Some(at) => {
+                let content = serde_json::to_string_pretty(&OneShotScan { at: at.to_rfc3339() })
+                    .map_err(|e| format!("序列化单次扫描计划失败: {}", e))?;
+                write_bytes_atomic(&get_one_shot_path(), content.as_bytes())?;
+                rearm_one_shot(&app);
+            }
+            None => {
+                cancel_one_shot(app.state::<AppState>());
+            }
+        }
+        if let Err(e) = refresh_tray_menu(app.clone()) {
+            eprintln!("⚠️ 重建托盘菜单失败: {}", e);
+        }
+    }
+
+    Ok(())
+}
+
+// 校验并写入快照中的文件和配置，返回快照里的调度状态（是否暂停、单次扫描时间）
+fn restore_state_snapshot(snapshot: StateSnapshot) -> Result<(bool, Option<DateTime<Utc>>), String> {
+    if snapshot.schema_version != SNAPSHOT_SCHEMA_VERSION {
+        return Err(format!(
+            "不支持的快照版本: {}（当前支持 {}）",
+            snapshot.schema_version, SNAPSHOT_SCHEMA_VERSION
+        ));
+    }
+    let known = snapshot_files();
+    if let Some(unknown) = snapshot.files.keys().find(|name| !known.iter().any(|(k, _)| k == name)) {
+        return Err(format!("快照中有未知的数据文件: {}", unknown));
+    }
+    if let Some(tombstones) = snapshot.files.get("tombstone-registry").filter(|v| !v.is_null()) {
+        validate_tombstones_value(tombstones, "快照")?;
+    }
+    let one_shot_at = snapshot
+        .scheduler
+        .one_shot_at
+        .as_deref()
+        .map(|at| {
+            DateTime::parse_from_rfc3339(at)
+                .map(|at| at.with_timezone(&Utc))
+                .map_err(|e| format!("快照中的单次扫描时间无效: {}", e))
+        })
+        .transpose()?;
+
+    // 与其他写入保持同样的加锁顺序：注册表 → 归档 → 其余
+    for (name, path) in known.iter() {
+        let Some(value) = snapshot.files.get(*name).filter(|v| !v.is_null()) else {
+            continue;
+        };
+        let _lock = lock_data_file(path)?;
+        backup_file(path)?;
+        write_json_atomic(path, value)?;
+    }
+
+    if let Some(rules) = &snapshot.ignore_rules {
+        let path = get_cemetery_ignore_path();
+        backup_file(&path)?;
+        write_bytes_atomic(&path, rules.as_bytes())?;
+    }
+
+    let mut config = snapshot.config;
+    if let Ok(local) = load_config() {
+        config.github_token = local.github_token;
+        config.webhook_secret = local.webhook_secret;
+    }
+    save_config(&config)?;
+
+    Ok((snapshot.scheduler.paused, one_shot_at))
+}
+
+// ========== 流式导出 ==========
+
+const EXPORT_FLUSH_EVERY: usize = 500;
+
+const TOMBSTONE_COLUMNS: &[&str] = &[
+    "id",
+    "name",
+    "cause_of_death",
+    "epitaph",
+    "tags",
+    "original_path",
+    "language",
+    "line_count",
+    "died_at",
+    "resurrected_at",
+    "resurrected_to",
+    "repo",
+    "pinned",
+    "author",
+];
+
+const ALERT_COLUMNS: &[&str] = &[
+    "id",
+    "corpse_repo",
+    "corpse_path",
+    "zombie_repo",
+    "zombie_path",
+    "similarity",
+    "resurrection_type",
+    "confidence",
+    "detected_at",
+    "notified",
+];
+
+fn tombstone_row(t: &Tombstone) -> Vec<String> {
+    vec![
+        t.id.clone(),
+        t.name.clone(),
+        t.cause_of_death.clone(),
+        t.epitaph.clone(),
+        t.tags.join(";"),
+        t.original_path.clone(),
+        t.language.clone().unwrap_or_default(),
+        t.line_count.to_string(),
+        t.died_at.clone(),
+        t.resurrected_at.clone().unwrap_or_default(),
+        t.resurrected_to.clone().unwrap_or_default(),
+        t.repo.clone().unwrap_or_default(),
+        t.pinned.to_string(),
+        t.author.clone().unwrap_or_default(),
+    ]
+}
+
+fn alert_row(a: &ZombieAlert) -> Vec<String> {
+    vec![
+        a.id.clone(),
+        a.corpse_repo.clone(),
+        a.corpse_path.clone(),
+        a.zombie_repo.clone(),
+        a.zombie_path.clone(),
+        format!("{:.4}", a.similarity),
+        a.resurrection_type.clone(),
+        format!("{:.4}", a.confidence),
+        a.detected_at.clone(),
+        a.notified.to_string(),
+    ]
+}
+
+// RFC 4180：含逗号、引号或换行的字段用双引号包裹，内部引号加倍
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn markdown_cell(value: &str) -> String {
+    value.replace('|', "\\|").replace("\r\n", "<br>").replace('\n', "<br>")
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum ExportFormat {
+    Markdown,
+    Csv,
+    Json,
+}
+
+impl ExportFormat {
+    fn parse(format: &str) -> Result<Self, String> {
+        match format {
+            "markdown" | "md" => Ok(ExportFormat::Markdown),
+            "csv" => Ok(ExportFormat::Csv),
+            "json" => Ok(ExportFormat::Json),
+            other => Err(format!("不支持的导出格式: {}（可选 markdown / csv / json）", other)),
+        }
+    }
+}
+
+// 逐条写入并定期 flush，输出端内存占用与记录数无关
+struct RecordWriter<'a, W: Write> {
+    writer: &'a mut W,
+    format: ExportFormat,
+    written: usize,
+}
+
+impl<'a, W: Write> RecordWriter<'a, W> {
+    fn begin(writer: &'a mut W, format: ExportFormat, columns: &[&str]) -> std::io::Result<Self> {
+        match format {
+            ExportFormat::Markdown => {
+                writeln!(writer, "| {} |", columns.join(" | "))?;
+                writeln!(writer, "|{}", " --- |".repeat(columns.len()))?;
+            }
+            ExportFormat::Csv => writeln!(writer, "{}", columns.join(","))?,
+            ExportFormat::Json => writeln!(writer, "[")?,
+        }
+        Ok(RecordWriter { writer, format, written: 0 })
+    }
+
+    fn write<T: Serialize>(&mut self, record: &T, row: &[String]) -> std::io::Result<()> {
+        match self.format {
+            ExportFormat::Markdown => {
+                let cells: Vec<String> = row.iter().map(|c| markdown_cell(c)).collect();
+                writeln!(self.writer, "| {} |", cells.join(" | "))?;
+            }
+            ExportFormat::Csv => {
+                let fields: Vec<String> = row.iter().map(|f| csv_field(f)).collect();
+                writeln!(self.writer, "{}", fields.join(","))?;
+            }
+            ExportFormat::Json => {
+                if self.written > 0 {
+                    writeln!(self.writer, ",")?;
+                }
+                serde_json::to_writer(&mut *self.writer, record)?;
+            }
+        }
+        self.written += 1;
+        if self.written % EXPORT_FLUSH_EVERY == 0 {
+            self.writer.flush()?;
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> std::io::Result<usize> {
+        if self.format == ExportFormat::Json {
+            writeln!(self.writer, "\n]")?;
+        }
+        self.writer.flush()?;
+        Ok(self.written)
+    }
+}
+
+// 逐个反序列化数组元素并交给 f，不构造 Vec
+struct SeqVisitor<T, F> {
+    f: F,
+    marker: std::marker::PhantomData<T>,
+}
+
+impl<'de, T, F> serde::de::Visitor<'de> for SeqVisitor<T, F>
+where
+    T: Deserialize<'de>,
+    F: FnMut(T) -> std::io::Result<()>,
+{
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("JSON 数组")
+    }
+
+    fn visit_seq<A: serde::de::SeqAccess<'de>>(mut self, mut seq: A) -> Result<(), A::Error> {
+        while let Some(item) = seq.next_element::<T>()? {
+            (self.f)(item).map_err(serde::de::Error::custom)?;
+        }
+        Ok(())
+    }
+}
+
+// 从注册表 / 归档文件流式读取墓碑，同一时刻内存里只有一个；.gz 透明解压，文件不存在时什么也不做。
+// 不经过 read_data_file，所以不受 max_registry_bytes 限制——超大注册表正是需要导出的时候
+fn for_each_tombstone_in(path: &Path, f: impl FnMut(Tombstone) -> std::io::Result<()>) -> Result<(), String> {
+    let Some(source) = resolve_data_path(path, data_settings().compress) else {
+        return Ok(());
+    };
+    let file = fs::File::open(&source)
+        .map_err(|e| format!("读取 {} 失败: {}", source.display(), e))?;
+    let reader: Box<dyn Read> = if is_gz(&source) {
+        Box::new(flate2::read::GzDecoder::new(file))
+    } else {
+        Box::new(file)
+    };
+
+    let mut de = serde_json::Deserializer::from_reader(std::io::BufReader::new(reader));
+    serde::Deserializer::deserialize_seq(&mut de, SeqVisitor::<Tombstone, _> { f, marker: std::marker::PhantomData })
+        .and_then(|_| de.end())
+        .map_err(|e| format!("读取 {} 失败: {}", source.display(), e))
+}
+
+fn stream_tombstones<W: Write>(writer: &mut W, format: ExportFormat, path: &Path) -> Result<usize, String> {
+    let mut out = RecordWriter::begin(writer, format, TOMBSTONE_COLUMNS)
+        .map_err(|e| format!("写入导出文件失败: {}", e))?;
+    for_each_tombstone_in(path, |t| out.write(&t, &tombstone_row(&t)))?;
+    out.finish().map_err(|e| format!("写入导出文件失败: {}", e))
+}
+
+fn stream_alerts<W: Write>(writer: &mut W, format: ExportFormat, alerts: &[ZombieAlert]) -> std::io::Result<usize> {
+    let mut out = RecordWriter::begin(writer, format, ALERT_COLUMNS)?;
+    for alert in alerts {
+        out.write(alert, &alert_row(alert))?;
+    }
+    out.finish()
+}
+
+// kind: tombstones / archive / alerts；先写 <path>.tmp 再 rename，返回写入的记录数
+#[tauri::command]
+pub fn export_to_file(kind: String, format: String, path: String) -> Result<usize, String> {
+    let format = ExportFormat::parse(&format)?;
+    let target = PathBuf::from(&path);
+    let mut tmp = target.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+
+    let file = fs::File::create(&tmp)
+        .map_err(|e| format!("创建导出文件失败: {}", e))?;
+    let mut writer = std::io::BufWriter::new(file);
+
+    let written = match kind.as_str() {
+        "tombstones" => stream_tombstones(&mut writer, format, &get_tombstone_registry_path()),
+        "archive" => stream_tombstones(&mut writer, format, &get_archive_path()),
+        "alerts" => stream_alerts(&mut writer, format, &get_zombie_alerts().alerts)
+            .map_err(|e| format!("写入导出文件失败: {}", e)),
+        other => Err(format!("不支持的导出类型: {}（可选 tombstones / archive / alerts）", other)),
+    };
+
+    drop(writer);
+    let written = match written {
+        Ok(written) => written,
+        Err(e) => {
+            fs::remove_file(&tmp).ok();
+            return Err(e);
+        }
+    };
+    fs::rename(&tmp, &target)
+        .map_err(|e| format!("保存导出文件失败: {}", e))?;
+
+    Ok(written)
+}
+
+// ========== CSV 导入 ==========
+
+// RFC 4180 解析，与 csv_field 的写法对应：引号内可以有逗号、换行和加倍的引号
+fn parse_csv(content: &str) -> Vec<Vec<String>> {
+    let mut records = vec![];
+    let mut record = vec![];
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = content.trim_start_matches('\u{feff}').chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (c, in_quotes) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            ('"', true) => in_quotes = false,
+            ('"', false) if field.is_empty() => in_quotes = true,
+            (',', false) => record.push(std::mem::take(&mut field)),
+            ('\r', false) if chars.peek() == Some(&'\n') => {}
+            ('\n', false) | ('\r', false) => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            (c, _) => field.push(c),
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+
+    // 跳过空行
+    records.retain(|r| !(r.len() == 1 && r[0].trim().is_empty()));
+    records
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RowError {
+    pub row: usize,
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ImportSummary {
+    pub added: usize,
+    pub skipped: usize,
+    pub errored: usize,
+    pub errors: Vec<RowError>,
+}
+
+// 列名与 TOMBSTONE_COLUMNS 一致（顺序任意，缺少的可选列取默认值）；id 为空时按 repo + 路径生成
+fn tombstone_from_csv_row(header: &[String], row: &[String]) -> Result<Tombstone, String> {
+    let get = |column: &str| -> String {
+        header
+            .iter()
+            .position(|h| h.trim() == column)
+            .and_then(|i| row.get(i))
+            .map(|v| v.trim().to_string())
+            .unwrap_or_default()
+    };
+    let optional = |column: &str| Some(get(column)).filter(|v| !v.is_empty());
+
+    let line_count = match get("line_count").as_str() {
+        "" => 0,
+        raw => raw.parse().map_err(|_| format!("line_count 不是数字: {}", raw))?,
+    };
+    let pinned = match get("pinned").to_lowercase().as_str() {
+        "" | "false" | "0" | "no" => false,
+        "true" | "1" | "yes" => true,
+        raw => return Err(format!("pinned 不是布尔值: {}", raw)),
+    };
+
+    let repo = optional("repo");
+    let original_path = get("original_path");
+    let id = optional("id").unwrap_or_else(|| tombstone_id_for(repo.as_deref(), &original_path));
+
+    let tombstone = Tombstone {
+        id,
+        name: get("name"),
+        cause_of_death: get("cause_of_death"),
+        epitaph: get("epitaph"),
+        tags: get("tags").split(';').map(String::from).collect(),
+        original_path,
+        language: optional("language"),
+        line_count,
+        died_at: get("died_at"),
+        resurrected_at: optional("resurrected_at"),
+        resurrected_to: optional("resurrected_to"),
+        repo,
+        pinned,
+        content_hash: optional("content_hash"),
+        attachments: vec![],
+        author: optional("author"),
+    };
+
+    normalize_tombstone(tombstone).map_err(|errors| {
+        errors
+            .iter()
+            .map(|e| format!("{}: {}", e.field, e.message))
+            .collect::<Vec<_>>()
+            .join("; ")
+    })
+}
+
+// 逐行校验，坏行记入 errors 不影响其他行；id 或 repo+路径 已存在的行跳过
+#[tauri::command]
+pub fn import_csv(path: String) -> Result<ImportSummary, String> {
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("读取 CSV 失败: {}", e))?;
+    let mut records = parse_csv(&content).into_iter();
+    let header = records
+        .next()
+        .ok_or_else(|| String::from("CSV 为空"))?;
+    if !header.iter().any(|h| h.trim() == "name") {
+        return Err(String::from("CSV 缺少表头或 name 列"));
+    }
+
+    let mut summary = ImportSummary::default();
+    let mut candidates = vec![];
+    for (i, row) in records.enumerate() {
+        // 表头是第 1 行
+        let row_number = i + 2;
+        match tombstone_from_csv_row(&header, &row) {
+            Ok(tombstone) => candidates.push(tombstone),
+            Err(message) => summary.errors.push(RowError {
+                row: row_number,
+                message,
+            }),
+        }
+    }
+    summary.errored = summary.errors.len();
+
+    let _lock = lock_data_file(&get_tombstone_registry_path())?;
+    let original = read_tombstones()?;
+    let mut registry = original.clone();
+    let valid = candidates.len();
+    let created = upsert_tombstones(&mut registry, candidates);
+    summary.added = created.len();
+    summary.skipped = valid - created.len();
+
+    if summary.added > 0 {
+        backup_file(&get_tombstone_registry_path())?;
+        push_undo_snapshot(&format!("CSV 导入 {} 个墓碑", summary.added), &original)?;
+        write_tombstones(&registry)?;
+    }
+    Ok(summary)
+}
+
+// ========== SARIF 报告 ==========
+
+const SARIF_VERSION: &str = "2.1.0";
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const SARIF_RULE_ID: &str = "code-corpses/zombie-code";
+
+// 置信度高的诈尸按 warning 报，其余为 note
+fn sarif_level(confidence: f64) -> &'static str {
+    if confidence >= 0.8 {
+        "warning"
+    } else {
+        "note"
+    }
+}
+
+fn alert_to_sarif_result(alert: &ZombieAlert) -> serde_json::Value {
+    serde_json::json!({
+        "ruleId": SARIF_RULE_ID,
+        "level": sarif_level(alert.confidence),
+        "message": {
+            "text": format!(
+                "{} 中的代码与已死亡的 {}/{} 相似度 {:.0}%（{}）",
+                alert.zombie_path,
+                alert.corpse_repo,
+                alert.corpse_path,
+                alert.similarity * 100.0,
+                alert.resurrection_type
+            )
+        },
+        "locations": [{
+            "physicalLocation": {
+                "artifactLocation": { "uri": normalize_repo_path(&alert.zombie_path) }
+            },
+            "logicalLocations": [{ "name": alert.zombie_repo, "kind": "module" }]
+        }],
+        "partialFingerprints": { "zombieAlertId": alert.id },
+        "properties": {
+            "confidence": alert.confidence,
+            "similarity": alert.similarity,
+            "resurrectionType": alert.resurrection_type,
+            "zombieRepo": alert.zombie_repo,
+            "corpseRepo": alert.corpse_repo,
+            "corpsePath": alert.corpse_path,
+            "detectedAt": alert.detected_at
+        }
+    })
+}
+
+fn build_sarif(alerts: &[ZombieAlert]) -> serde_json::Value {
+    serde_json::json!({
+        "$schema": SARIF_SCHEMA,
+        "version": SARIF_VERSION,
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "code-corpses",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": [{
+                        "id": SARIF_RULE_ID,
+                        "name": "ZombieCode",
+                        "shortDescription": { "text": "已删除的代码在别处诈尸" }
+                    }]
+                }
+            },
+            "results": alerts.iter().map(alert_to_sarif_result).collect::<Vec<_>>()
+        }]
+    })
+}
+
+// 只检查 SARIF 规范中的必填字段，不做完整 schema 校验
+fn validate_sarif(sarif: &serde_json::Value) -> Result<(), String> {
+    if sarif["version"] != SARIF_VERSION {
+        return Err(String::from("SARIF 缺少 version"));
+    }
+    let runs = sarif["runs"]
+        .as_array()
+        .ok_or_else(|| String::from("SARIF 缺少 runs"))?;
+    for (i, run) in runs.iter().enumerate() {
+        if !run["tool"]["driver"]["name"].is_string() {
+            return Err(format!("runs[{}] 缺少 tool.driver.name", i));
+        }
+        let results = run["results"].as_array().map(Vec::as_slice).unwrap_or_default();
+        for (j, result) in results.iter().enumerate() {
+            if !result["message"]["text"].is_string() {
+                return Err(format!("runs[{}].results[{}] 缺少 message.text", i, j));
+            }
+            if !result["ruleId"].is_string() {
+                return Err(format!("runs[{}].results[{}] 缺少 ruleId", i, j));
+            }
+        }
+    }
+    Ok(())
+}
+
+// format: sarif（供代码扫描面板和 CI 使用）/ json（提醒文件原样输出）
+#[tauri::command]
+pub fn export_alerts_report(format: String) -> Result<String, String> {
+    let alerts = get_zombie_alerts();
+    let report = match format.to_lowercase().as_str() {
+        "sarif" => {
+            let sarif = build_sarif(&alerts.alerts);
+            validate_sarif(&sarif)?;
+            sarif
+        }
+        "json" => serde_json::to_value(&alerts).map_err(|e| format!("序列化提醒失败: {}", e))?,
+        other => return Err(format!("不支持的报告格式: {}（可选 sarif / json）", other)),
+    };
+    serde_json::to_string_pretty(&report).map_err(|e| format!("序列化报告失败: {}", e))
+}
+
+// ========== Atom 订阅 ==========
+
+fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // XML 1.0 不允许的控制字符直接丢弃
+            c if (c as u32) < 0x20 && !matches!(c, '\t' | '\n' | '\r') => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// 最近 limit 个墓碑的 Atom feed。entry 的 id 为 urn:code-corpses:tombstone:<墓碑 id>，
+// feed 的 updated 取最新一次死亡时间（墓地为空时取当前时间）
+#[tauri::command]
+pub fn export_feed(limit: i32) -> Result<String, String> {
+    let mut entries: Vec<(DateTime<Utc>, Tombstone)> = read_tombstones()?
+        .into_iter()
+        .filter_map(|t| parse_timestamp(&t.died_at).map(|at| (at, t)))
+        .collect();
+    entries.sort_by(|a, b| b.0.cmp(&a.0));
+    entries.truncate(limit.max(0) as usize);
+
+    let updated = entries.first().map_or_else(Utc::now, |(at, _)| *at);
+
+    let mut xml = String::new();
+    xml.push_str("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    xml.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+    xml.push_str("  <title>Code Corpses 🪦</title>\n");
+    xml.push_str("  <id>urn:code-corpses:graveyard</id>\n");
+    xml.push_str(&format!("  <updated>{}</updated>\n", updated.to_rfc3339()));
+    xml.push_str(&format!(
+        "  <generator version=\"{}\">code-corpses</generator>\n",
+        env!("CARGO_PKG_VERSION")
+    ));
+
+    for (died_at, t) in &entries {
+        let published = died_at.to_rfc3339();
+        xml.push_str("  <entry>\n");
+        xml.push_str(&format!("    <title>{}</title>\n", xml_escape(&t.name)));
+        xml.push_str(&format!("    <id>urn:code-corpses:tombstone:{}</id>\n", xml_escape(&t.id)));
+        xml.push_str(&format!("    <published>{}</published>\n", published));
+        xml.push_str(&format!("    <updated>{}</updated>\n", published));
+        xml.push_str("    <author><name>code-corpses</name></author>\n");
+        xml.push_str(&format!(
+            "    <content type=\"text\">{}\n\n{}</content>\n",
+            xml_escape(&t.epitaph),
+            xml_escape(&t.cause_of_death)
+        ));
+        xml.push_str("  </entry>\n");
+    }
+    xml.push_str("</feed>\n");
+
+    Ok(xml)
+}
+
+// ========== 时间线 SVG ==========
+
+const TIMELINE_SVG_WIDTH: usize = 800;
+const TIMELINE_SVG_HEIGHT: usize = 320;
+const TIMELINE_MARGIN_LEFT: usize = 50;
+const TIMELINE_MARGIN_RIGHT: usize = 20;
+const TIMELINE_MARGIN_TOP: usize = 40;
+const TIMELINE_MARGIN_BOTTOM: usize = 50;
+// x 轴最多标注的区间数，其余按等间隔跳过
+const TIMELINE_MAX_X_LABELS: usize = 8;
+
+// 手写的柱状图，不依赖图表库。坐标保留两位小数，同样的数据输出逐字节相同
+fn render_timeline_svg(bucket: &str, points: &[TimelinePoint]) -> String {
+    let plot_width = (TIMELINE_SVG_WIDTH - TIMELINE_MARGIN_LEFT - TIMELINE_MARGIN_RIGHT) as f64;
+    let plot_height = (TIMELINE_SVG_HEIGHT - TIMELINE_MARGIN_TOP - TIMELINE_MARGIN_BOTTOM) as f64;
+    let left = TIMELINE_MARGIN_LEFT as f64;
+    let baseline = (TIMELINE_SVG_HEIGHT - TIMELINE_MARGIN_BOTTOM) as f64;
+    let max = points.iter().map(|p| p.deaths).max().unwrap_or(0).max(1);
+    let total: usize = points.iter().map(|p| p.deaths).sum();
+
+    let mut svg = String::new();
+    svg.push_str(&format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\" font-family=\"sans-serif\">\n",
+        w = TIMELINE_SVG_WIDTH,
+        h = TIMELINE_SVG_HEIGHT
+    ));
+    svg.push_str(&format!(
+        "  <rect width=\"{}\" height=\"{}\" fill=\"#1e1e2e\"/>\n",
+        TIMELINE_SVG_WIDTH, TIMELINE_SVG_HEIGHT
+    ));
+    svg.push_str(&format!(
+        "  <text x=\"{}\" y=\"24\" fill=\"#cdd6f4\" font-size=\"16\">{}</text>\n",
+        TIMELINE_MARGIN_LEFT,
+        xml_escape(&format!("Code Corpses timeline ({}, {} deaths)", bucket, total))
+    ));
+
+    // 坐标轴与 y 轴刻度（0 和最大值）
+    svg.push_str(&format!(
+        "  <line x1=\"{l:.2}\" y1=\"{t}\" x2=\"{l:.2}\" y2=\"{b:.2}\" stroke=\"#6c7086\"/>\n",
+        l = left,
+        t = TIMELINE_MARGIN_TOP,
+        b = baseline
+    ));
+    svg.push_str(&format!(
+        "  <line x1=\"{l:.2}\" y1=\"{b:.2}\" x2=\"{r:.2}\" y2=\"{b:.2}\" stroke=\"#6c7086\"/>\n",
+        l = left,
+        r = left + plot_width,
+        b = baseline
+    ));
+    for (value, y) in [(0, baseline), (max, TIMELINE_MARGIN_TOP as f64)] {
+        svg.push_str(&format!(
+            "  <text x=\"{:.2}\" y=\"{:.2}\" fill=\"#a6adc8\" font-size=\"11\" text-anchor=\"end\">{}</text>\n",
+            left - 6.0,
+            y + 4.0,
+            value
+        ));
+    }
+
+    if !points.is_empty() {
+        let slot = plot_width / points.len() as f64;
+        let bar_width = (slot * 0.8).max(1.0);
+        let label_every = points.len().div_ceil(TIMELINE_MAX_X_LABELS).max(1);
+        for (i, point) in points.iter().enumerate() {
+            let height = point.deaths as f64 / max as f64 * plot_height;
+            let x = left + i as f64 * slot + (slot - bar_width) / 2.0;
+            if point.deaths > 0 {
+                svg.push_str(&format!(
+                    "  <rect x=\"{:.2}\" y=\"{:.2}\" width=\"{:.2}\" height=\"{:.2}\" fill=\"#f38ba8\"><title>{}: {}</title></rect>\n",
+                    x,
+                    baseline - height,
+                    bar_width,
+                    height,
+                    xml_escape(&point.period),
+                    point.deaths
+                ));
+            }
+            if i % label_every == 0 || i == points.len() - 1 {
+                svg.push_str(&format!(
+                    "  <text x=\"{:.2}\" y=\"{:.2}\" fill=\"#a6adc8\" font-size=\"10\" text-anchor=\"middle\">{}</text>\n",
+                    x + bar_width / 2.0,
+                    baseline + 16.0,
+                    xml_escape(&point.period)
+                ));
+            }
+        }
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+// 死亡时间线的独立 SVG（可直接保存或内嵌到报告），数据来自 get_death_timeline
+#[tauri::command]
+pub fn export_timeline_svg(bucket: String) -> Result<String, String> {
+    let bucket = bucket.trim().to_lowercase();
+    let points = get_death_timeline(bucket.clone())?;
+    Ok(render_timeline_svg(&bucket, &points))
+}
+
+// ========== 死亡证明 ==========
+
+// 证明编号由 id 的哈希决定，同一个墓碑每次生成都一样
+fn certificate_number(id: &str) -> String {
+    let hex = format!("{:016X}", stable_hash(id));
+    format!("CC-{}-{}-{}-{}", &hex[0..4], &hex[4..8], &hex[8..12], &hex[12..16])
+}
+
+// 日期按显示时区只取年月日；无法解析时原样输出
+fn certificate_date(raw: &str) -> String {
+    parse_timestamp(raw)
+        .map(|at| date_in(&display_tz(), at).format("%Y-%m-%d").to_string())
+        .unwrap_or_else(|| raw.to_string())
+}
+
+fn render_death_certificate(t: &Tombstone) -> String {
+    let mut rows = vec![
+        ("Name of the deceased", t.name.clone()),
+        ("Original location", t.original_path.clone()),
+        ("Language", t.language.clone().unwrap_or_else(|| String::from("Unknown"))),
+        ("Lines of code", t.line_count.to_string()),
+        ("Cause of death", t.cause_of_death.clone()),
+        ("Date of death", certificate_date(&t.died_at)),
+    ];
+    if let Some(repo) = &t.repo {
+        rows.insert(2, ("Repository", repo.clone()));
+    }
+    if let Some(resurrected_at) = &t.resurrected_at {
+        rows.push(("Resurrected", certificate_date(resurrected_at)));
+    }
+
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str(&format!("<title>Certificate of Death: {}</title>\n", xml_escape(&t.name)));
+    html.push_str(
+        "<style>\n\
+         body { margin: 0; background: #f4efe1; font-family: Georgia, 'Times New Roman', serif; color: #2b2b2b; }\n\
+         .certificate { width: 760px; margin: 40px auto; padding: 48px 56px; background: #fffdf6; \
+         border: 12px double #5b4a2f; text-align: center; }\n\
+         h1 { margin: 0; font-size: 34px; letter-spacing: 4px; text-transform: uppercase; }\n\
+         .subtitle { margin: 8px 0 32px; font-style: italic; }\n\
+         table { width: 100%; border-collapse: collapse; text-align: left; }\n\
+         th { width: 40%; padding: 8px; font-weight: normal; font-variant: small-caps; color: #5b4a2f; }\n\
+         td { padding: 8px; border-bottom: 1px dotted #5b4a2f; }\n\
+         .epitaph { margin: 32px 0 24px; font-size: 18px; font-style: italic; }\n\
+         .number { font-family: 'Courier New', monospace; font-size: 13px; letter-spacing: 1px; }\n\
+         @media print { body { background: none; } .certificate { margin: 0 auto; } }\n\
+         </style>\n",
+    );
+    html.push_str("</head>\n<body>\n<div class=\"certificate\">\n");
+    html.push_str("  <h1>Certificate of Death</h1>\n");
+    html.push_str("  <p class=\"subtitle\">Issued by the Registrar of the Code Cemetery</p>\n");
+    html.push_str("  <table>\n");
+    for (label, value) in &rows {
+        html.push_str(&format!(
+            "    <tr><th>{}</th><td>{}</td></tr>\n",
+            label,
+            xml_escape(value)
+        ));
+    }
+    html.push_str("  </table>\n");
+    html.push_str(&format!("  <p class=\"epitaph\">&ldquo;{}&rdquo;</p>\n", xml_escape(&t.epitaph)));
+    html.push_str(&format!(
+        "  <p class=\"number\">Certificate No. {}</p>\n",
+        certificate_number(&t.id)
+    ));
+    html.push_str("</div>\n</body>\n</html>\n");
+    html
+}
+
+// 可打印的 HTML 死亡证明，前端直接打印或另存；除时区设置外输出完全由墓碑内容决定
+#[tauri::command]
+pub fn generate_death_certificate(id: String) -> Result<String, String> {
+    let tombstone = find_tombstone(&id)?;
+    Ok(render_death_certificate(&tombstone))
+}
+
+// ========== HTML 导出 ==========
+
+// 按死亡年份（显示时区）分组，年份从新到旧，组内按死亡时间从新到旧；
+// 死亡时间无法解析的放在最后的 Unknown 组
+fn group_tombstones_by_year(tombstones: Vec<Tombstone>) -> Vec<(String, Vec<Tombstone>)> {
+    use chrono::Datelike;
+    let tz = display_tz();
+    let mut by_year: BTreeMap<i32, Vec<(DateTime<Utc>, Tombstone)>> = BTreeMap::new();
+    let mut unknown = vec![];
+    for t in tombstones {
+        match parse_timestamp(&t.died_at) {
+            Some(at) => by_year.entry(date_in(&tz, at).year()).or_default().push((at, t)),
+            None => unknown.push(t),
+        }
+    }
+
+    let mut groups: Vec<(String, Vec<Tombstone>)> = by_year
+        .into_iter()
+        .rev()
+        .map(|(year, mut entries)| {
+            entries.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.id.cmp(&b.1.id)));
+            (year.to_string(), entries.into_iter().map(|(_, t)| t).collect())
+        })
+        .collect();
+    if !unknown.is_empty() {
+        unknown.sort_by(|a, b| a.id.cmp(&b.id));
+        groups.push((String::from("Unknown"), unknown));
+    }
+    groups
+}
+
+fn render_graveyard_html(stats: &Stats, tombstones: Vec<Tombstone>) -> String {
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str("<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n");
+    html.push_str("<title>Code Corpses graveyard</title>\n");
+    html.push_str(
+        "<style>\n\
+         body { margin: 0 auto; max-width: 860px; padding: 32px 20px; background: #1e1e2e; color: #cdd6f4; \
+         font-family: -apple-system, 'Segoe UI', Helvetica, Arial, sans-serif; }\n\
+         h1 { margin-bottom: 8px; }\n\
+         .stats { display: flex; flex-wrap: wrap; gap: 12px; margin: 16px 0 32px; padding: 0; list-style: none; }\n\
+         .stats li { padding: 10px 14px; background: #313244; border-radius: 8px; }\n\
+         .stats b { display: block; font-size: 20px; color: #f38ba8; }\n\
+         h2 { border-bottom: 1px solid #45475a; padding-bottom: 4px; }\n\
+         .tomb { margin: 12px 0; padding: 12px 16px; background: #313244; border-left: 4px solid #6c7086; border-radius: 4px; }\n\
+         .tomb.resurrected { border-left-color: #a6e3a1; }\n\
+         .tomb h3 { margin: 0 0 4px; font-size: 16px; }\n\
+         .meta { font-size: 12px; color: #a6adc8; }\n\
+         .epitaph { margin: 8px 0 0; font-style: italic; }\n\
+         </style>\n",
+    );
+    html.push_str("</head>\n<body>\n<h1>🪦 Code Corpses graveyard</h1>\n<ul class=\"stats\">\n");
+    for (label, value) in [
+        ("Assets", stats.total_assets),
+        ("Alive", stats.alive_assets),
+        ("Dead", stats.dead_assets),
+        ("Tombstones", stats.total_tombstones),
+        ("Resurrected", stats.resurrected),
+    ] {
+        html.push_str(&format!("  <li><b>{}</b>{}</li>\n", value, label));
+    }
+    html.push_str("</ul>\n");
+
+    for (year, group) in group_tombstones_by_year(tombstones) {
+        html.push_str(&format!("<h2>{} <small>({})</small></h2>\n", xml_escape(&year), group.len()));
+        for t in group {
+            let class = if t.resurrected_at.is_some() { "tomb resurrected" } else { "tomb" };
+            html.push_str(&format!("<div class=\"{}\">\n", class));
+            html.push_str(&format!("  <h3>{}</h3>\n", xml_escape(&t.name)));
+            let mut meta = vec![
+                xml_escape(&t.original_path),
+                format!("{} lines", t.line_count),
+                xml_escape(t.language.as_deref().unwrap_or("Unknown")),
+                format!("died {}", xml_escape(&certificate_date(&t.died_at))),
+            ];
+            if let Some(resurrected_at) = &t.resurrected_at {
+                meta.push(format!("resurrected {}", xml_escape(&certificate_date(resurrected_at))));
+            }
+            html.push_str(&format!("  <div class=\"meta\">{}</div>\n", meta.join(" · ")));
+            html.push_str(&format!(
+                "  <div class=\"meta\">Cause of death: {}</div>\n",
+                xml_escape(&t.cause_of_death)
+            ));
+            html.push_str(&format!("  <p class=\"epitaph\">{}</p>\n", xml_escape(&t.epitaph)));
+            html.push_str("</div>\n");
+        }
+    }
+
+    html.push_str(&format!(
+        "<p class=\"meta\">Generated by code-corpses {}</p>\n</body>\n</html>\n",
+        env!("CARGO_PKG_VERSION")
+    ));
+    html
+}
+
+// 自包含的静态 HTML 页面（内联 CSS，无外部资源），可直接发布；用户文本全部转义
+#[tauri::command]
+pub fn export_html() -> Result<String, String> {
+    let tombstones = read_tombstones()?;
+    Ok(render_graveyard_html(&get_stats(), tombstones))
+}
+
+// ========== 分享卡片 ==========
+
+const CARD_WIDTH: u32 = 600;
+const CARD_HEIGHT: u32 = 315;
+
+// 打包进二进制的 DejaVu Sans Mono（许可见 fonts/LICENSE-DejaVu.txt），不依赖系统字体，
+// 同一份数据在任何机器上渲染出同样的卡片
+const CARD_FONT: &[u8] = include_bytes!("../fonts/DejaVuSansMono.ttf");
+
+fn card_font() -> Result<&'static fontdue::Font, String> {
+    static FONT: OnceLock<Result<fontdue::Font, String>> = OnceLock::new();
+    FONT.get_or_init(|| {
+        fontdue::Font::from_bytes(CARD_FONT, fontdue::FontSettings::default())
+            .map_err(|e| format!("加载卡片字体失败: {}", e))
+    })
+    .as_ref()
+    .map_err(Clone::clone)
+}
+
+// y 为文字顶端；超出卡片宽度的字符直接截断。按覆盖率把文字颜色混合到（不透明的）背景上
+fn draw_text(
+    pixmap: &mut tiny_skia::Pixmap,
+    font: &fontdue::Font,
+    text: &str,
+    x: u32,
+    y: u32,
+    px: f32,
+    rgb: (u8, u8, u8),
+) {
+    let ascent = font.horizontal_line_metrics(px).map_or(px, |m| m.ascent);
+    let baseline = y as i32 + ascent.round() as i32;
+    let (width, height) = (pixmap.width() as i32, pixmap.height() as i32);
+    let mut pen = x as f32;
+
+    for c in text.chars() {
+        let (metrics, coverage) = font.rasterize(c, px);
+        if pen + metrics.advance_width > CARD_WIDTH as f32 {
+            break;
+        }
+        let left = pen.round() as i32 + metrics.xmin;
+        let top = baseline - metrics.height as i32 - metrics.ymin;
+        for (i, &alpha) in coverage.iter().enumerate() {
+            let (px_x, px_y) = (left + (i % metrics.width) as i32, top + (i / metrics.width) as i32);
+            if alpha == 0 || px_x < 0 || px_y < 0 || px_x >= width || px_y >= height {
+                continue;
+            }
+            let index = (px_y * width + px_x) as usize;
+            let under = pixmap.pixels()[index];
+            let blend = |fg: u8, bg: u8| ((fg as u32 * alpha as u32 + bg as u32 * (255 - alpha as u32)) / 255) as u8;
+            if let Some(color) = tiny_skia::PremultipliedColorU8::from_rgba(
+                blend(rgb.0, under.red()),
+                blend(rgb.1, under.green()),
+                blend(rgb.2, under.blue()),
+                255,
+            ) {
+                pixmap.pixels_mut()[index] = color;
+            }
+        }
+        pen += metrics.advance_width;
+    }
+}
+
+// 渲染一张 600x315 的分享卡片（PNG），内容只取决于墓地数据
+#[tauri::command]
+pub fn generate_graveyard_card() -> Result<Vec<u8>, String> {
+    let corpses: Vec<Tombstone> = read_tombstones()?
+        .into_iter()
+        .filter(|t| t.resurrected_at.is_none())
+        .collect();
+    let dead_lines: usize = corpses.iter().map(|t| t.line_count).sum();
+
+    let mut by_language: HashMap<String, usize> = HashMap::new();
+    for t in &corpses {
+        let language = t.language.clone().unwrap_or_else(|| String::from("Unknown"));
+        *by_language.entry(language).or_insert(0) += 1;
+    }
+    // 数量相同时按名字排序，保证输出确定
+    let top_language = by_language
+        .into_iter()
+        .max_by(|a, b| a.1.cmp(&b.1).then(b.0.cmp(&a.0)))
+        .map(|(language, _)| language)
+        .unwrap_or_else(|| String::from("-"));
+
+    let mut pixmap = tiny_skia::Pixmap::new(CARD_WIDTH, CARD_HEIGHT)
+        .ok_or_else(|| String::from("创建画布失败"))?;
+    pixmap.fill(tiny_skia::Color::from_rgba8(30, 27, 46, 255));
+
+    let mut accent = tiny_skia::Paint::default();
+    accent.set_color_rgba8(108, 92, 231, 255);
+    if let Some(bar) = tiny_skia::Rect::from_xywh(0.0, 0.0, 12.0, CARD_HEIGHT as f32) {
+        pixmap.fill_rect(bar, &accent, tiny_skia::Transform::identity(), None);
+    }
+
+    let font = card_font()?;
+    draw_text(&mut pixmap, font, "CODE CORPSES", 40, 36, 48.0, (255, 255, 255));
+    draw_text(&mut pixmap, font, &format!("Corpses: {}", corpses.len()), 40, 130, 24.0, (223, 230, 233));
+    draw_text(&mut pixmap, font, &format!("Dead lines: {}", dead_lines), 40, 170, 24.0, (223, 230, 233));
+    draw_text(&mut pixmap, font, &format!("Top language: {}", top_language), 40, 210, 24.0, (223, 230, 233));
+    draw_text(&mut pixmap, font, "RIP", 40, 270, 16.0, (108, 92, 231));
+
+    pixmap
+        .encode_png()
+        .map_err(|e| format!("编码 PNG 失败: {}", e))
+}
+
+// ========== GitHub API ==========
+
+const GITHUB_API: &str = "https://api.github.com";
+const GITHUB_PAGE_SIZE: usize = 100;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GhRepo {
+    pub name: String,
+    pub full_name: String,
+    #[serde(default)]
+    pub private: bool,
+    #[serde(default)]
+    pub visibility: Option<String>,
+    #[serde(default)]
+    pub default_branch: Option<String>,
+    #[serde(default)]
+    pub archived: bool,
+}
+
+const DEFAULT_USER_AGENT: &str = concat!("code-corpses/", env!("CARGO_PKG_VERSION"));
+const DEFAULT_REQUEST_TIMEOUT_SECS: u64 = 30;
+const MIN_REQUEST_TIMEOUT_SECS: u64 = 5;
+const MAX_REQUEST_TIMEOUT_SECS: u64 = 300;
+
+fn request_user_agent(config: &Config) -> &str {
+    config
+        .user_agent
+        .as_deref()
+        .map(str::trim)
+        .filter(|ua| !ua.is_empty())
+        .unwrap_or(DEFAULT_USER_AGENT)
+}
+
+fn request_timeout(config: &Config) -> std::time::Duration {
+    std::time::Duration::from_secs(
+        config
+            .request_timeout_secs
+            .clamp(MIN_REQUEST_TIMEOUT_SECS, MAX_REQUEST_TIMEOUT_SECS),
+    )
+}
+
+// 扫描、组织校验、配额查询等所有 GitHub 请求共用；超时防止代理吞掉请求时扫描一直挂着
+fn github_client(config: &Config) -> Result<reqwest::Client, String> {
+    reqwest::Client::builder()
+        .user_agent(request_user_agent(config))
+        .timeout(request_timeout(config))
+        .build()
+        .map_err(|e| format!("创建 HTTP 客户端失败: {}", e))
+}
+
+fn github_get(client: &reqwest::Client, token: Option<&str>, url: &str) -> reqwest::RequestBuilder {
+    let request = client
+        .get(url)
+        .header("Accept", "application/vnd.github+json")
+        .header("X-GitHub-Api-Version", "2022-11-28");
+    match token {
+        Some(token) => request.bearer_auth(token),
+        None => request,
+    }
+}
+
+// ========== 速率限制节流 ==========
+
+// 剩余配额低于上限的这个比例后开始把请求均匀摊到重置前，而不是一口气用完再失败
+const THROTTLE_PACING_RATIO: f64 = 0.2;
+// 单次等待的上限；GitHub 的配额窗口是一小时
+const THROTTLE_MAX_WAIT_SECS: i64 = 3600;
+
+// 所有 GitHub 请求共享的配额桶：令牌数即剩余配额，到 reset 时补满到 limit。
+// 数值以每次响应的 x-ratelimit-* 头为准，两次响应之间由 acquire 预扣，
+// 手动扫描和定时扫描同时进行时也不会超发。
+// API 辅助函数拿不到 AppHandle，所以放在静态变量里而不是 AppState
+struct ThrottleState {
+    limit: Option<u64>,
+    remaining: u64,
+    reset: i64,
+    next_slot: Option<DateTime<Utc>>,
+}
+
+static THROTTLE: Mutex<ThrottleState> = Mutex::new(ThrottleState {
+    limit: None,
+    remaining: 0,
+    reset: 0,
+    next_slot: None,
+});
+
+fn throttle_state() -> std::sync::MutexGuard<'static, ThrottleState> {
+    THROTTLE.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+// 预扣一个令牌并返回需要等待的时间。还没见过响应头时不限制
+fn throttle_reserve(now: DateTime<Utc>) -> std::time::Duration {
+    let mut state = throttle_state();
+    let Some(limit) = state.limit else {
+        return std::time::Duration::ZERO;
+    };
+    if state.reset <= now.timestamp() {
+        state.remaining = limit;
+        state.next_slot = None;
+    }
+
+    let reset_at = DateTime::<Utc>::from_timestamp(state.reset, 0).unwrap_or(now);
+    let slot = if state.remaining == 0 {
+        // 配额用完：所有请求都等到重置，之后由第一批响应头重新校准
+        reset_at
+    } else if (state.remaining as f64) < limit as f64 * THROTTLE_PACING_RATIO {
+        let spacing = (reset_at - now) / state.remaining as i32;
+        let slot = state.next_slot.filter(|s| *s > now).unwrap_or(now);
+        state.next_slot = Some(slot + spacing);
+        slot
+    } else {
+        now
+    };
+    state.remaining = state.remaining.saturating_sub(1);
+
+    (slot - now)
+        .min(Duration::seconds(THROTTLE_MAX_WAIT_SECS))
+        .to_std()
+        .unwrap_or_default()
+}
+
+fn header_number<T: std::str::FromStr>(response: &reqwest::Response, name: &str) -> Option<T> {
+    response.headers().get(name)?.to_str().ok()?.parse().ok()
+}
+
+fn throttle_observe(response: &reqwest::Response) {
+    let (Some(limit), Some(remaining), Some(reset)) = (
+        header_number::<u64>(response, "x-ratelimit-limit"),
+        header_number::<u64>(response, "x-ratelimit-remaining"),
+        header_number::<i64>(response, "x-ratelimit-reset"),
+    ) else {
+        return;
+    };
+    let mut state = throttle_state();
+    // 新窗口开始时清掉上个窗口的排队节奏
+    if reset != state.reset {
+        state.next_slot = None;
+    }
+    state.limit = Some(limit);
+    state.remaining = remaining;
+    state.reset = reset;
+}
+
+// 所有 GitHub API 请求都经过这里：先从配额桶取令牌（必要时等待），再用响应头校准
+async fn github_send(request: reqwest::RequestBuilder) -> reqwest::Result<reqwest::Response> {
+    let wait = throttle_reserve(Utc::now());
+    if !wait.is_zero() {
+        println!("⏳ GitHub 配额紧张，等待 {} 秒后再请求", wait.as_secs());
+        tokio::time::sleep(wait).await;
+    }
+    let response = request.send().await?;
+    throttle_observe(&response);
+    if let Err(e) = record_quota_point() {
+        eprintln!("⚠️ 记录配额历史失败: {}", e);
+    }
+    Ok(response)
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ThrottleStatus {
+    // 还没有任何 GitHub 响应时为 false，其余字段无意义
+    pub known: bool,
+    pub limit: u64,
+    pub tokens: u64,
+    pub refill_at: Option<String>,
+    pub refill_in_secs: u64,
+    pub pacing: bool,
+}
+
+#[tauri::command]
+pub fn get_throttle_status() -> ThrottleStatus {
+    let state = throttle_state();
+    let now = Utc::now();
+    let Some(limit) = state.limit else {
+        return ThrottleStatus {
+            known: false,
+            limit: 0,
+            tokens: 0,
+            refill_at: None,
+            refill_in_secs: 0,
+            pacing: false,
+        };
+    };
+
+    let refilled = state.reset <= now.timestamp();
+    let tokens = if refilled { limit } else { state.remaining };
+    ThrottleStatus {
+        known: true,
+        limit,
+        tokens,
+        refill_at: DateTime::<Utc>::from_timestamp(state.reset, 0).map(format_ts),
+        refill_in_secs: (state.reset - now.timestamp()).max(0) as u64,
+        pacing: (tokens as f64) < limit as f64 * THROTTLE_PACING_RATIO,
+    }
+}
+
+// ========== 配额历史 ==========
+
+const QUOTA_HISTORY_LIMIT: usize = 1000;
+// 同一配额窗口内间隔不到这个时间的请求合并成一个点（更新最后一个点），
+// 一次扫描的连续请求不会把历史刷满
+const QUOTA_POINT_MIN_GAP_SECS: i64 = 60;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct QuotaPoint {
+    pub recorded_at: String,
+    pub limit: u64,
+    pub remaining: u64,
+    pub reset_at: String,
+}
+
+fn get_quota_history_path() -> PathBuf {
+    app_data_dir().0.join("quota-history.json")
+}
+
+fn read_quota_history() -> Result<Vec<QuotaPoint>, String> {
+    let path = get_quota_history_path();
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("读取配额历史失败: {}", e))?;
+    serde_json::from_str(&content)
+        .map_err(|e| format!("解析配额历史失败: {}", e))
+}
+
+// 追加一个点：与上一个点同窗口且间隔太短时覆盖上一个点；超过上限时丢弃最旧的
+fn push_quota_point(history: &mut Vec<QuotaPoint>, point: QuotaPoint, now: DateTime<Utc>) {
+    let merge = history.last().is_some_and(|last| {
+        last.reset_at == point.reset_at
+            && parse_timestamp(&last.recorded_at)
+                .is_some_and(|at| now - at < Duration::seconds(QUOTA_POINT_MIN_GAP_SECS))
+    });
+    if merge {
+        history.pop();
+    }
+    history.push(point);
+
+    if history.len() > QUOTA_HISTORY_LIMIT {
+        let overflow = history.len() - QUOTA_HISTORY_LIMIT;
+        history.drain(..overflow);
+    }
+}
+
+// 用节流状态里最新的响应头数据记一个点；还没见过响应头时什么都不做
+fn record_quota_point() -> Result<(), String> {
+    let (limit, remaining, reset) = {
+        let state = throttle_state();
+        let Some(limit) = state.limit else {
+            return Ok(());
+        };
+        (limit, state.remaining, state.reset)
+    };
+    let now = Utc::now();
+    let point = QuotaPoint {
+        recorded_at: now.to_rfc3339(),
+        limit,
+        remaining,
+        reset_at: DateTime::<Utc>::from_timestamp(reset, 0).map(|t| t.to_rfc3339()).unwrap_or_default(),
+    };
+
+    let path = get_quota_history_path();
+    let _lock = lock_data_file(&path)?;
+    let mut history = read_quota_history()?;
+    push_quota_point(&mut history, point, now);
+    let content = serde_json::to_string(&history)
+        .map_err(|e| format!("序列化配额历史失败: {}", e))?;
+    write_bytes_atomic(&path, content.as_bytes())
+}
+
+// 按时间顺序返回最近 limit 个配额点，供面板画消耗曲线
+#[tauri::command]
+pub fn get_quota_history(limit: usize) -> Result<Vec<QuotaPoint>, String> {
+    let mut history = read_quota_history()?;
+    if history.len() > limit {
+        history.drain(..history.len() - limit);
+    }
+    Ok(history)
+}
+
+// type=all 会列出令牌有权限的公开、私有和内部仓库；关闭 include_private 时只请求公开仓库
+fn repo_list_type(include_private: bool) -> &'static str {
+    if include_private {
+        "all"
+    } else {
+        "public"
+    }
+}
+
+// 请求了 type=public 也再按可见性过滤一遍，不依赖接口参数的行为
+fn filter_repo_visibility(repos: Vec<GhRepo>, include_private: bool) -> Vec<GhRepo> {
+    if include_private {
+        return repos;
+    }
+    repos
+        .into_iter()
+        .filter(|r| !r.private && r.visibility.as_deref().map_or(true, |v| v == "public"))
+        .collect()
+}
+
+async fn list_org_repos(
+    client: &reqwest::Client,
+    token: Option<&str>,
+    org: &str,
+    include_private: bool,
+) -> Result<Vec<GhRepo>, String> {
+    let mut repos = vec![];
+    let mut page = 1;
+
+    loop {
+        let page_param = page.to_string();
+        let per_page = GITHUB_PAGE_SIZE.to_string();
+        let request = github_get(client, token, &format!("{}/orgs/{}/repos", GITHUB_API, org)).query(&[
+            ("type", repo_list_type(include_private)),
+            ("per_page", per_page.as_str()),
+            ("page", page_param.as_str()),
+        ]);
+        let response = github_send(request)
+            .await
+            .map_err(|e| format!("请求仓库列表失败: {}", e))?;
+
+        let status = response.status();
+        if status == reqwest::StatusCode::NOT_FOUND {
+            return Err(format!("组织不存在或无权访问: {}", org));
+        }
+        if !status.is_success() {
+            return Err(format!("获取仓库列表失败: HTTP {}", status));
+        }
+
+        let batch: Vec<GhRepo> = response
+            .json()
+            .await
+            .map_err(|e| format!("解析仓库列表失败: {}", e))?;
+        let done = batch.len() < GITHUB_PAGE_SIZE;
+        repos.extend(batch);
+        if done {
+            break;
+        }
+        page += 1;
+    }
+
+    Ok(filter_repo_visibility(repos, include_private))
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GhTreeEntry {
+    pub path: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub sha: String,
+    #[serde(default)]
+    pub size: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct GhTree {
+    #[serde(default)]
+    tree: Vec<GhTreeEntry>,
+    #[serde(default)]
+    truncated: bool,
+}
+
+// 一次请求取回整个仓库的文件树（只保留文件），空仓库返回空列表
+async fn fetch_repo_files(
+    client: &reqwest::Client,
+    token: Option<&str>,
+    repo: &GhRepo,
+) -> Result<Vec<GhTreeEntry>, String> {
+    let branch = repo.default_branch.as_deref().unwrap_or("HEAD");
+    let url = format!("{}/repos/{}/git/trees/{}", GITHUB_API, repo.full_name, branch);
+    let response = github_send(github_get(client, token, &url).query(&[("recursive", "1")]))
+        .await
+        .map_err(|e| format!("请求 {} 的文件树失败: {}", repo.full_name, e))?;
+
+    // 空仓库没有任何提交，接口返回 404 或 409
+    let status = response.status();
+    if status == reqwest::StatusCode::NOT_FOUND || status == reqwest::StatusCode::CONFLICT {
+        return Ok(vec![]);
+    }
+    if !status.is_success() {
+        return Err(format!("获取 {} 的文件树失败: HTTP {}", repo.full_name, status));
+    }
+
+    let tree: GhTree = response
+        .json()
+        .await
+        .map_err(|e| format!("解析 {} 的文件树失败: {}", repo.full_name, e))?;
+    if tree.truncated {
+        eprintln!("⚠️ {} 的文件树过大，结果被截断", repo.full_name);
+    }
+    Ok(tree.tree.into_iter().filter(|e| e.kind == "blob").collect())
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RateLimit {
+    pub limit: u64,
+    pub remaining: u64,
+    pub reset: i64,
+}
+
+// /rate_limit 本身不消耗配额
+async fn fetch_rate_limit(client: &reqwest::Client, token: Option<&str>) -> Result<RateLimit, String> {
+    let response = github_send(github_get(client, token, &format!("{}/rate_limit", GITHUB_API)))
+        .await
+        .map_err(|e| format!("请求速率限制失败: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("获取速率限制失败: HTTP {}", response.status()));
+    }
+
+    let body: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| format!("解析速率限制失败: {}", e))?;
+    serde_json::from_value(body["resources"]["core"].clone())
+        .map_err(|e| format!("解析速率限制失败: {}", e))
+}
+
+enum OrgCheck {
+    Exists,
+    NotFound,
+    Unverified(String),
+}
+
+// 先查组织，404 再按个人账号查；网络错误、限流等情况视为无法验证
+async fn check_org_exists(client: &reqwest::Client, token: &str, org: &str) -> OrgCheck {
+    for kind in ["orgs", "users"] {
+        let url = format!("{}/{}/{}", GITHUB_API, kind, org);
+        match github_send(github_get(client, Some(token), &url)).await {
+            Ok(response) if response.status().is_success() => return OrgCheck::Exists,
+            Ok(response) if response.status() == reqwest::StatusCode::NOT_FOUND => continue,
+            Ok(response) => return OrgCheck::Unverified(format!("HTTP {}", response.status())),
+            Err(e) => return OrgCheck::Unverified(e.to_string()),
+        }
+    }
+    OrgCheck::NotFound
+}
+
+// 有令牌时先确认组织/用户存在再保存；无令牌或无法联网时直接保存，并记 target_org_verified = false
+#[tauri::command]
+pub async fn set_target_org(org: String) -> Result<(), String> {
+    let org = org.trim().to_string();
+    if org.is_empty() || !org.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err(format!("组织名无效: {}", org));
+    }
+
+    let mut config = load_config()?;
+    let resolved = resolve_config()?;
+    let token = resolved.github_token.clone().filter(|t| !t.is_empty());
+
+    let verified = match token {
+        Some(token) => match check_org_exists(&github_client(&resolved)?, &token, &org).await {
+            OrgCheck::Exists => true,
+            OrgCheck::NotFound => return Err(format!("找不到组织或用户: {}", org)),
+            OrgCheck::Unverified(reason) => {
+                eprintln!("⚠️ 无法验证组织 {}: {}，先保存", org, reason);
+                false
+            }
+        },
+        None => false,
+    };
+
+    config.target_org = org;
+    config.target_org_verified = verified;
+    save_config(&config)
+}
+
+// ========== 组织成员缓存 ==========
+
+const MEMBERS_CACHE_TTL_SECS: i64 = 24 * 3600;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Member {
+    pub login: String,
+    pub id: u64,
+    #[serde(default)]
+    pub avatar_url: Option<String>,
+    #[serde(default)]
+    pub html_url: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct MembersCache {
+    org: String,
+    fetched_at: String,
+    members: Vec<Member>,
+}
+
+fn get_members_cache_path() -> PathBuf {
+    app_data_dir().0.join("org-members.json")
+}
+
+// 缓存属于当前 target_org 且未超过 TTL 时返回其成员
+fn read_fresh_members(org: &str) -> Option<Vec<Member>> {
+    let content = fs::read_to_string(get_members_cache_path()).ok()?;
+    let cache: MembersCache = serde_json::from_str(&content).ok()?;
+    let fetched_at = parse_timestamp(&cache.fetched_at)?;
+    let fresh = Utc::now() - fetched_at < Duration::seconds(MEMBERS_CACHE_TTL_SECS);
+    (fresh && cache.org.eq_ignore_ascii_case(org)).then_some(cache.members)
+}
+
+async fn list_org_members(client: &reqwest::Client, token: &str, org: &str) -> Result<Vec<Member>, String> {
+    let mut members = vec![];
+    let mut page = 1;
+    loop {
+        let page_param = page.to_string();
+        let per_page = GITHUB_PAGE_SIZE.to_string();
+        let request = github_get(client, Some(token), &format!("{}/orgs/{}/members", GITHUB_API, org))
+            .query(&[("per_page", per_page.as_str()), ("page", page_param.as_str())]);
+        let response = github_send(request)
+            .await
+            .map_err(|e| format!("请求成员列表失败: {}", e))?;
+
+        let status = response.status();
+        if status == reqwest::StatusCode::NOT_FOUND {
+            return Err(format!("组织不存在或无权访问: {}", org));
+        }
+        if !status.is_success() {
+            return Err(format!("获取成员列表失败: HTTP {}", status));
+        }
+
+        let batch: Vec<Member> = response
+            .json()
+            .await
+            .map_err(|e| format!("解析成员列表失败: {}", e))?;
+        let done = batch.len() < GITHUB_PAGE_SIZE;
+        members.extend(batch);
+        if done {
+            break;
+        }
+        page += 1;
+    }
+    Ok(members)
+}
+
+// 重新拉取 target_org 的成员并写入缓存（显式刷新，忽略 TTL），需要令牌。
+// 请求经过共享的配额节流
+#[tauri::command]
+pub async fn fetch_org_members() -> Result<Vec<Member>, String> {
+    let config = resolve_config()?;
+    let token = config
+        .github_token
+        .clone()
+        .filter(|t| !t.is_empty())
+        .ok_or_else(|| String::from("获取组织成员需要 GitHub 令牌"))?;
+
+    let client = github_client(&config)?;
+    let members = list_org_members(&client, &token, &config.target_org).await?;
+
+    let cache = MembersCache {
+        org: config.target_org.clone(),
+        fetched_at: Utc::now().to_rfc3339(),
+        members: members.clone(),
+    };
+    let content = serde_json::to_string_pretty(&cache)
+        .map_err(|e| format!("序列化成员缓存失败: {}", e))?;
+    write_bytes_atomic(&get_members_cache_path(), content.as_bytes())?;
+    Ok(members)
+}
+
+// 优先使用缓存；缓存过期、不存在或属于其他组织时重新拉取
+#[tauri::command]
+pub async fn get_cached_members() -> Result<Vec<Member>, String> {
+    let org = resolve_config()?.target_org;
+    match read_fresh_members(&org) {
+        Some(members) => Ok(members),
+        None => fetch_org_members().await,
+    }
+}
+
+// ========== 刷新墓碑元数据 ==========
+
+// 与 TypeScript 端 asset-index.ts 的 LANG_MAP 保持一致
+fn language_for_path(path: &str) -> Option<String> {
+    let ext = Path::new(path).extension()?.to_str()?.to_lowercase();
+    let language = match ext.as_str() {
+        "ts" | "tsx" => "TypeScript",
+        "js" | "jsx" => "JavaScript",
+        "py" => "Python",
+        "go" => "Go",
+        "rs" => "Rust",
+        "java" => "Java",
+        "cpp" | "cc" | "cxx" => "C++",
+        "c" | "h" => "C",
+        "swift" => "Swift",
+        "kt" => "Kotlin",
+        "rb" => "Ruby",
+        "php" => "PHP",
+        "sh" | "bash" => "Shell",
+        "css" => "CSS",
+        "scss" => "SCSS",
+        "less" => "LESS",
+        "html" => "HTML",
+        "json" => "JSON",
+        "yaml" | "yml" => "YAML",
+        "md" => "Markdown",
+        "sql" => "SQL",
+        "xml" => "XML",
+        "toml" => "TOML",
+        _ => return None,
+    };
+    Some(language.to_string())
+}
+
+#[derive(Deserialize)]
+struct GhCommitRef {
+    sha: String,
+}
+
+#[derive(Deserialize)]
+struct GhCommit {
+    sha: String,
+    #[serde(default)]
+    parents: Vec<GhCommitRef>,
+}
+
+#[derive(Deserialize)]
+struct GhContent {
+    #[serde(default)]
+    content: String,
+}
+
+// repo 可以是 owner/name，也可以只写仓库名（此时归属 target_org）
+fn repo_full_name(repo: &str, target_org: &str) -> String {
+    if repo.contains('/') {
+        repo.to_string()
+    } else {
+        format!("{}/{}", target_org, repo)
+    }
+}
+
+// 取 path 在 git_ref 时的文件内容；该版本中不存在时返回 None
+async fn fetch_file_at(
+    client: &reqwest::Client,
+    token: &str,
+    repo: &str,
+    path: &str,
+    git_ref: &str,
+) -> Result<Option<String>, String> {
+    let url = format!("{}/repos/{}/contents/{}", GITHUB_API, repo, path);
+    let response = github_send(github_get(client, Some(token), &url).query(&[("ref", git_ref)]))
+        .await
+        .map_err(|e| format!("请求文件内容失败: {}", e))?;
+
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    if !response.status().is_success() {
+        return Err(format!("获取文件内容失败: HTTP {}", response.status()));
+    }
+
+    let content: GhContent = response
+        .json()
+        .await
+        .map_err(|e| format!("解析文件内容失败: {}", e))?;
+    let bytes = decode_gh_content(&content.content)?;
+    Ok(Some(String::from_utf8_lossy(&bytes).into_owned()))
+}
+
+// contents / blobs 接口返回的 base64 内容每 60 个字符带一个换行
+fn decode_gh_content(content: &str) -> Result<Vec<u8>, String> {
+    use base64::Engine;
+    let encoded: String = content.split_whitespace().collect();
+    base64::engine::general_purpose::STANDARD
+        .decode(encoded)
+        .map_err(|e| format!("解码文件内容失败: {}", e))
+}
+
+// 取回过的墓碑源码缓存在 .cemetery/sources/ 下，文件名由 id 的哈希决定
+fn tombstone_source_cache_path(id: &str) -> PathBuf {
+    get_base_path()
+        .join(".cemetery/sources")
+        .join(format!("{:016x}.txt", stable_hash(id)))
+}
+
+fn cached_tombstone_source(id: &str) -> Option<String> {
+    fs::read_to_string(tombstone_source_cache_path(id)).ok()
+}
+
+// 远程文件内容按 blob sha 缓存在 .cemetery/blobs/ 下；sha 由内容决定，缓存不会过期
+fn blob_cache_path(sha: &str) -> PathBuf {
+    get_base_path().join(".cemetery/blobs").join(sha)
+}
+
+// 按 blob sha 取远程文件内容，优先读缓存；缓存未命中时需要令牌
+async fn fetch_blob(
+    client: &reqwest::Client,
+    token: Option<&str>,
+    repo: &str,
+    sha: &str,
+) -> Result<Vec<u8>, String> {
+    if sha.is_empty() || !sha.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("无效的 blob sha: {}", sha));
+    }
+    let cache = blob_cache_path(sha);
+    if let Ok(bytes) = fs::read(&cache) {
+        return Ok(bytes);
+    }
+    let token = token.ok_or_else(|| String::from("获取远程文件内容需要 GitHub 令牌"))?;
+    let bytes = fetch_blob_uncached(client, token, repo, sha).await?;
+    if let Err(e) = write_bytes_atomic(&cache, &bytes) {
+        eprintln!("⚠️ 缓存远程文件内容失败: {}", e);
+    }
+    Ok(bytes)
+}
+
+async fn fetch_blob_uncached(client: &reqwest::Client, token: &str, repo: &str, sha: &str) -> Result<Vec<u8>, String> {
+    let url = format!("{}/repos/{}/git/blobs/{}", GITHUB_API, repo, sha);
+    let response = github_send(github_get(client, Some(token), &url))
+        .await
+        .map_err(|e| format!("请求远程文件内容失败: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("获取远程文件内容失败: HTTP {}", response.status()));
+    }
+    let blob: GhContent = response
+        .json()
+        .await
+        .map_err(|e| format!("解析远程文件内容失败: {}", e))?;
+    decode_gh_content(&blob.content)
+}
+
+fn find_tombstone(id: &str) -> Result<Tombstone, String> {
+    read_tombstones()?
+        .into_iter()
+        .find(|t| t.id == id)
+        .ok_or_else(|| format!("墓碑不存在: {}", id))
+}
+
+// 从 GitHub 取回墓碑原文件删除前的版本并写入缓存，需要令牌
+async fn fetch_tombstone_source(config: &Config, tombstone: &Tombstone) -> Result<String, String> {
+    let token = config
+        .github_token
+        .clone()
+        .filter(|t| !t.is_empty())
+        .ok_or_else(|| String::from("获取墓碑源码需要 GitHub 令牌"))?;
+    let repo = tombstone
+        .repo
+        .as_deref()
+        .map(|r| repo_full_name(r, &config.target_org))
+        .ok_or_else(|| format!("墓碑 {} 没有记录所属仓库，无法获取源码", tombstone.id))?;
+    let path = repo_path(&tombstone.original_path);
+
+    let client = github_client(config)?;
+    let request = github_get(&client, Some(&token), &format!("{}/repos/{}/commits", GITHUB_API, repo))
+        .query(&[("path", path.as_str()), ("per_page", "1")]);
+    let response = github_send(request)
+        .await
+        .map_err(|e| format!("请求提交历史失败: {}", e))?;
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Err(format!("仓库不存在或无权访问: {}", repo));
+    }
+    if !response.status().is_success() {
+        return Err(format!("获取提交历史失败: HTTP {}", response.status()));
+    }
+    let commits: Vec<GhCommit> = response
+        .json()
+        .await
+        .map_err(|e| format!("解析提交历史失败: {}", e))?;
+    let last = commits
+        .first()
+        .ok_or_else(|| format!("{} 中找不到 {} 的任何提交", repo, path))?;
+
+    // 最近一次提交如果就是删除它的提交，文件只存在于其父提交中
+    let mut content = fetch_file_at(&client, &token, &repo, &path, &last.sha).await?;
+    if content.is_none() {
+        if let Some(parent) = last.parents.first() {
+            content = fetch_file_at(&client, &token, &repo, &path, &parent.sha).await?;
+        }
+    }
+    let content = content.ok_or_else(|| format!("{} 中已找不到 {} 删除前的版本", repo, path))?;
+
+    if let Err(e) = write_bytes_atomic(&tombstone_source_cache_path(&tombstone.id), content.as_bytes()) {
+        eprintln!("⚠️ 缓存墓碑源码失败: {}", e);
+    }
+    Ok(content)
+}
+
+// 重新取回原文件（删除它的那次提交之前的版本），只更新 line_count 和 language，
+// 复活信息和标签保持不变
+#[tauri::command]
+pub async fn refresh_tombstone(id: String) -> Result<Tombstone, String> {
+    let config = resolve_config()?;
+    let tombstone = find_tombstone(&id)?;
+    let content = fetch_tombstone_source(&config, &tombstone).await?;
+    let path = repo_path(&tombstone.original_path);
+
+    let _lock = lock_data_file(&get_tombstone_registry_path())?;
+    let mut tombstones = read_tombstones()?;
+    let entry = tombstones
+        .iter_mut()
+        .find(|t| t.id == id)
+        .ok_or_else(|| format!("墓碑不存在: {}", id))?;
+    entry.line_count = content.lines().count();
+    entry.language = language_for_path(&path).or_else(|| entry.language.clone());
+    let refreshed = entry.clone();
+
+    write_tombstones(&tombstones)?;
+    Ok(refreshed)
+}
+
+// 粘贴的片段与墓碑原文件的相似度（0–1），使用配置的相似度算法。
+// 优先用缓存的源码，没有缓存时才联网获取
+#[tauri::command]
+pub async fn score_against_tombstone(id: String, snippet: String) -> Result<f64, String> {
+    if snippet.trim().is_empty() {
+        return Err(String::from("片段不能为空"));
+    }
+    let config = resolve_config()?;
+    let source = match cached_tombstone_source(&id) {
+        Some(source) => source,
+        None => fetch_tombstone_source(&config, &find_tombstone(&id)?).await?,
+    };
+    Ok(similarity_score(config.similarity_algo, &source, &snippet))
+}
+
+// ========== 忽略规则 ==========
+
+// 扫描忽略规则，gitignore 语法，路径相对于项目根目录。
+// 先加入配置中的 ignore_globs，再加入 .cemetery/.cemeteryignore：
+// 和 .gitignore 一样后出现的规则优先，因此文件里的规则（包括 `!` 取反）可以覆盖配置。
+// 每次扫描开始时重新读取，修改文件无需重启
+fn load_ignore_rules(config: &Config) -> Result<ignore::gitignore::Gitignore, String> {
+    let mut builder = ignore::gitignore::GitignoreBuilder::new(get_base_path());
+    for glob in &config.ignore_globs {
+        builder
+            .add_line(None, glob)
+            .map_err(|e| format!("无效的忽略规则 {}: {}", glob, e))?;
+    }
+
+    let file = get_cemetery_ignore_path();
+    if file.exists() {
+        if let Some(e) = builder.add(&file) {
+            return Err(format!("读取 {} 失败: {}", file.display(), e));
+        }
+    }
+
+    builder
+        .build()
+        .map_err(|e| format!("构建忽略规则失败: {}", e))
+}
+
+fn is_ignored(rules: &ignore::gitignore::Gitignore, location: &str) -> bool {
+    let path = Path::new(location);
+    let relative = match path.strip_prefix(rules.path()) {
+        Ok(relative) => relative.to_path_buf(),
+        // 项目外的绝对路径不受忽略规则约束
+        Err(_) if path.has_root() => return false,
+        Err(_) => PathBuf::from(repo_path(location)),
+    };
+    rules.matched_path_or_any_parents(&relative, false).is_ignore()
+}
+
+// ========== 内容哈希 ==========
+
+const MAX_HASHED_FILE_BYTES: u64 = 5 * 1024 * 1024;
+
+// 对空白不敏感的内容指纹：按任意空白切分后用单个空格连接，再取 SHA-256
+fn content_hash(content: &str) -> String {
+    use sha2::{Digest, Sha256};
+    let normalized = content.split_whitespace().collect::<Vec<_>>().join(" ");
+    format!("{:x}", Sha256::digest(normalized.as_bytes()))
+}
+
+// 为本地仍存在的资产重算 content_hash 并写回索引。已删除的文件保留最后一次的哈希，
+// 立碑时带进墓碑；只改 content_hash 字段，其他字段原样保留
+fn refresh_asset_hashes() -> Result<usize, String> {
+    let path = get_asset_index_path();
+    let _lock = lock_data_file(&path)?;
+    if !data_file_exists(&path) {
+        return Ok(0);
+    }
+
+    let mut assets: serde_json::Value = serde_json::from_str(&read_data_file(&path)?)
+        .map_err(|e| format!("解析资产索引失败: {}", e))?;
+    let Some(list) = assets.as_array_mut() else {
+        return Ok(0);
+    };
+
+    let mut updated = 0;
+    for asset in list.iter_mut() {
+        let Some(location) = asset["location"].as_str() else {
+            continue;
+        };
+        let file = Path::new(location);
+        let readable = fs::metadata(file).map_or(false, |m| m.is_file() && m.len() <= MAX_HASHED_FILE_BYTES);
+        if !readable {
+            continue;
+        }
+        let Ok(content) = fs::read_to_string(file) else {
+            continue;
+        };
+
+        let hash = content_hash(&content);
+        if asset["content_hash"].as_str() != Some(hash.as_str()) {
+            asset["content_hash"] = serde_json::json!(hash);
+            updated += 1;
+        }
+    }
+
+    if updated > 0 {
+        write_json_atomic(&path, &assets)?;
+    }
+    Ok(updated)
+}
+
+// 为还没有 content_hash 的存活远程资产（新出现的，或 blob_sha 变了的）按 blob 取内容算哈希，
+// 按 scan_concurrency 并发。取内容期间不持有索引锁，写回时只填仍然缺哈希且 blob_sha 未变的条目。
+// 全量扫描的 blob 不进缓存，避免把整个组织的代码存到本地
+async fn hash_remote_blobs(config: &Config) -> Result<usize, String> {
+    let Some(token) = config.github_token.clone().filter(|t| !t.is_empty()) else {
+        return Ok(0);
+    };
+    let mut pending: Vec<(String, String)> = read_assets()?
+        .into_iter()
+        .filter(|a| a.alive && a.content_hash.is_none() && remote_repo_path(&a.location).is_some())
+        .filter(|a| a.line_count as u64 * REMOTE_BYTES_PER_LINE <= MAX_HASHED_FILE_BYTES)
+        .filter_map(|a| Some((repo_full_name(a.repo.as_deref()?, &config.target_org), a.blob_sha?)))
+        .collect();
+    pending.sort_by(|a, b| a.1.cmp(&b.1));
+    pending.dedup_by(|a, b| a.1 == b.1);
+    if pending.is_empty() {
+        return Ok(0);
+    }
+
+    let client = github_client(config)?;
+    let mut hashes: HashMap<String, String> = HashMap::new();
+    for chunk in pending.chunks(config.scan_concurrency.max(1)) {
+        let mut tasks = tokio::task::JoinSet::new();
+        for (repo, sha) in chunk.iter().cloned() {
+            let client = client.clone();
+            let token = token.clone();
+            tasks.spawn(async move {
+                let result = fetch_blob_uncached(&client, &token, &repo, &sha).await;
+                (sha, result)
+            });
+        }
+        while let Some(joined) = tasks.join_next().await {
+            match joined {
+                Ok((sha, Ok(bytes))) => {
+                    hashes.insert(sha, content_hash(&String::from_utf8_lossy(&bytes)));
+                }
+                Ok((sha, Err(e))) => eprintln!("⚠️ 计算 blob {} 的哈希失败: {}", sha, e),
+                Err(e) => eprintln!("⚠️ 哈希任务异常退出: {}", e),
+            }
+        }
+    }
+
+    let path = get_asset_index_path();
+    let _lock = lock_data_file(&path)?;
+    let mut assets = read_asset_values()?;
+    let mut updated = 0;
+    for asset in assets.iter_mut() {
+        let Some(hash) = asset["blob_sha"].as_str().and_then(|sha| hashes.get(sha)) else {
+            continue;
+        };
+        if asset["content_hash"].is_string() {
+            continue;
+        }
+        asset["content_hash"] = serde_json::json!(hash);
+        updated += 1;
+    }
+    if updated > 0 {
+        write_json_atomic(&path, &assets)?;
+    }
+    Ok(updated)
+}
+
+// 手动重算内容哈希：本地文件全部重算，远程文件只算缺哈希的。返回更新的资产数
+#[tauri::command]
+pub async fn refresh_content_hashes() -> Result<usize, String> {
+    let config = resolve_config()?;
+    let local = refresh_asset_hashes()?;
+    let remote = hash_remote_blobs(&config).await?;
+    Ok(local + remote)
+}
+
+// 按内容哈希查资产（包括已死亡的），新出现的文件可以据此立刻匹配到一模一样的墓碑
+#[tauri::command]
+pub fn find_by_hash(hash: String) -> Vec<Asset> {
+    let hash = hash.trim().to_lowercase();
+    if hash.is_empty() {
+        return vec![];
+    }
+    read_assets()
+        .unwrap_or_else(|e| {
+            eprintln!("⚠️ {}", e);
+            vec![]
+        })
+        .into_iter()
+        .filter(|a| a.content_hash.as_deref() == Some(hash.as_str()))
+        .collect()
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct UndeadMatch {
+    pub asset: Asset,
+    pub tombstone_id: String,
+    pub tombstone_name: String,
+    pub original_path: String,
+    pub now_at: String,
+}
+
+// 内容哈希与某个未复活墓碑的死亡时哈希相同、但仍然存活的资产：代码被宣告死亡，
+// 副本却还在别处运行。已复活的墓碑不算（那正是预期的去向）。
+// 资产或墓碑都还没有哈希时返回空列表，并提示重新扫描
+#[tauri::command]
+pub fn find_undead_assets() -> Vec<UndeadMatch> {
+    let assets = read_assets().unwrap_or_else(|e| {
+        eprintln!("⚠️ {}", e);
+        vec![]
+    });
+    let mut dead_by_hash: HashMap<String, Vec<Tombstone>> = HashMap::new();
+    for t in load_tombstones_or_log() {
+        if t.resurrected_at.is_some() {
+            continue;
+        }
+        if let Some(hash) = t.content_hash.clone() {
+            dead_by_hash.entry(hash).or_default().push(t);
+        }
+    }
+
+    if dead_by_hash.is_empty() || !assets.iter().any(|a| a.content_hash.is_some()) {
+        eprintln!("⚠️ 资产或墓碑缺少内容哈希，重新扫描后再查找");
+        return vec![];
+    }
+
+    let mut matches = vec![];
+    for asset in assets.into_iter().filter(|a| a.alive) {
+        let Some(tombstones) = asset.content_hash.as_ref().and_then(|h| dead_by_hash.get(h)) else {
+            continue;
+        };
+        for t in tombstones {
+            matches.push(UndeadMatch {
+                now_at: asset.location.clone(),
+                tombstone_id: t.id.clone(),
+                tombstone_name: t.name.clone(),
+                original_path: t.original_path.clone(),
+                asset: asset.clone(),
+            });
+        }
+    }
+    matches.sort_by(|a, b| a.now_at.cmp(&b.now_at).then_with(|| a.tombstone_id.cmp(&b.tombstone_id)));
+    matches
+}
+
+// ========== 埋葬 ==========
+
+// 墓碑 id 由 repo + 规范化路径决定：同一个文件重复扫描、失败重试都得到同一个 id
+fn tombstone_key(repo: Option<&str>, original_path: &str) -> String {
+    format!(
+        "{}:{}",
+        repo.unwrap_or("").to_lowercase(),
+        repo_path(original_path)
+    )
+}
+
+fn tombstone_id_for(repo: Option<&str>, original_path: &str) -> String {
+    format!("tomb-{:016x}", stable_hash(&tombstone_key(repo, original_path)))
+}
+
+// 只插入 id 和 repo+路径 都未出现过的墓碑；已有墓碑原样保留（包括复活信息）。返回新增的墓碑
+fn upsert_tombstones(registry: &mut Vec<Tombstone>, candidates: Vec<Tombstone>) -> Vec<Tombstone> {
+    let mut known_ids: HashSet<String> = registry.iter().map(|t| t.id.clone()).collect();
+    let mut known_keys: HashSet<String> = registry
+        .iter()
+        .map(|t| tombstone_key(t.repo.as_deref(), &t.original_path))
+        .collect();
+
+    let mut created = vec![];
+    for candidate in candidates {
+        let key = tombstone_key(candidate.repo.as_deref(), &candidate.original_path);
+        if known_ids.contains(&candidate.id) || known_keys.contains(&key) {
+            continue;
+        }
+        known_ids.insert(candidate.id.clone());
+        known_keys.insert(key);
+        registry.push(candidate.clone());
+        created.push(candidate);
+    }
+    created
+}
+
+fn tombstone_from_asset(asset: &Asset, repo: Option<&str>, cause: &str) -> Tombstone {
+    let cause = cause.to_string();
+    Tombstone {
+        id: tombstone_id_for(repo, &asset.location),
+        name: asset.name.clone(),
+        epitaph: suggest_epitaph(
+            asset.name.clone(),
+            cause.clone(),
+            asset.language.clone(),
+            Some(asset.line_count),
+        ),
+        cause_of_death: cause,
+        tags: asset.tags.clone(),
+        original_path: remote_repo_path(&asset.location)
+            .map_or_else(|| asset.location.clone(), String::from),
+        language: asset.language.clone(),
+        line_count: asset.line_count,
+        died_at: Utc::now().to_rfc3339(),
+        resurrected_at: None,
+        resurrected_to: None,
+        repo: repo.map(String::from),
+        pinned: false,
+        content_hash: asset.content_hash.clone(),
+        attachments: vec![],
+        author: asset.author.clone(),
+    }
+}
+
+// ========== 死亡规则 ==========
+
+// 文件没被删除、但满足条件也算死亡。一条规则内的条件同时成立才命中，多条规则任一命中即可；
+// 没有任何条件的规则永不命中，避免一条空规则把所有文件都埋掉
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct DeathRule {
+    pub name: String,
+    // 最后修改距今至少这么多天；拿不到修改时间的文件不算命中
+    #[serde(default)]
+    pub min_age_days: Option<u64>,
+    #[serde(default)]
+    pub min_lines: Option<usize>,
+    #[serde(default)]
+    pub max_lines: Option<usize>,
+    // gitignore 语法，相对项目根目录
+    #[serde(default)]
+    pub path_glob: Option<String>,
+    #[serde(default)]
+    pub language: Option<String>,
+}
+
+impl DeathRule {
+    fn has_conditions(&self) -> bool {
+        self.min_age_days.is_some()
+            || self.min_lines.is_some()
+            || self.max_lines.is_some()
+            || self.path_glob.as_deref().is_some_and(|g| !g.trim().is_empty())
+            || self.language.as_deref().is_some_and(|l| !l.trim().is_empty())
+    }
+}
+
+struct CompiledDeathRule {
+    rule: DeathRule,
+    glob: Option<ignore::gitignore::Gitignore>,
+}
+
+fn compile_death_rules(rules: &[DeathRule]) -> Result<Vec<CompiledDeathRule>, String> {
+    rules
+        .iter()
+        .filter(|r| r.has_conditions())
+        .map(|rule| {
+            let glob = match rule.path_glob.as_deref().map(str::trim).filter(|g| !g.is_empty()) {
+                Some(glob) => {
+                    let mut builder = ignore::gitignore::GitignoreBuilder::new(get_base_path());
+                    builder
+                        .add_line(None, glob)
+                        .map_err(|e| format!("死亡规则 {} 的路径 {} 无效: {}", rule.name, glob, e))?;
+                    Some(
+                        builder
+                            .build()
+                            .map_err(|e| format!("死亡规则 {} 的路径 {} 无效: {}", rule.name, glob, e))?,
+                    )
+                }
+                None => None,
+            };
+            Ok(CompiledDeathRule {
+                rule: rule.clone(),
+                glob,
+            })
+        })
+        .collect()
+}
+
+// 最后修改时间：优先用索引里的 updated_at，没有时读本地文件的 mtime
+fn asset_last_modified(asset: &Asset) -> Option<DateTime<Utc>> {
+    if let Some(at) = asset.updated_at.as_deref().and_then(parse_timestamp) {
+        return Some(at);
+    }
+    let path = Path::new(&asset.location);
+    if !path.is_absolute() {
+        return None;
+    }
+    fs::metadata(path).and_then(|m| m.modified()).ok().map(DateTime::<Utc>::from)
+}
+
+fn death_rule_matches(compiled: &CompiledDeathRule, asset: &Asset, now: DateTime<Utc>) -> bool {
+    let rule = &compiled.rule;
+    if let Some(days) = rule.min_age_days {
+        let old_enough = asset_last_modified(asset).is_some_and(|at| now - at >= Duration::days(days as i64));
+        if !old_enough {
+            return false;
+        }
+    }
+    if rule.min_lines.is_some_and(|min| asset.line_count < min) {
+        return false;
+    }
+    if rule.max_lines.is_some_and(|max| asset.line_count > max) {
+        return false;
+    }
+    if let Some(language) = rule.language.as_deref().map(str::trim).filter(|l| !l.is_empty()) {
+        if !language_key(&asset.language).eq_ignore_ascii_case(language) {
+            return false;
+        }
+    }
+    if let Some(glob) = &compiled.glob {
+        if !is_ignored(glob, &asset.location) {
+            return false;
+        }
+    }
+    true
+}
+
+fn matching_death_rule<'a>(
+    rules: &'a [CompiledDeathRule],
+    asset: &Asset,
+    now: DateTime<Utc>,
+) -> Option<&'a CompiledDeathRule> {
+    rules.iter().find(|r| death_rule_matches(r, asset, now))
+}
+
+// 按当前配置的死亡规则判断单个资产；已死亡或豁免的资产不参与规则判断，返回 false
+#[tauri::command]
+pub fn evaluate_death_rules(asset: Asset) -> Result<bool, String> {
+    let rules = compile_death_rules(&resolve_config()?.death_rules)?;
+    Ok(asset.alive && !asset.exempt && matching_death_rule(&rules, &asset, Utc::now()).is_some())
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DeathRuleMatch {
+    pub asset_id: String,
+    pub location: String,
+    pub rule: String,
+}
+
+// 设置页的规则试跑：返回样本中会被规则判死的资产及命中的规则，不写任何文件
+#[tauri::command]
+pub fn test_death_rules(sample: Vec<Asset>) -> Result<Vec<DeathRuleMatch>, String> {
+    let rules = compile_death_rules(&resolve_config()?.death_rules)?;
+    let now = Utc::now();
+    Ok(sample
+        .iter()
+        .filter(|a| a.alive && !a.exempt)
+        .filter_map(|a| {
+            matching_death_rule(&rules, a, now).map(|rule| DeathRuleMatch {
+                asset_id: a.id.clone(),
+                location: a.location.clone(),
+                rule: rule.rule.name.clone(),
+            })
+        })
+        .collect())
+}
+
+// ========== 改名检测 ==========
+
+const DEFAULT_RENAME_SIMILARITY_THRESHOLD: f64 = 0.8;
+
+fn path_basename(path: &str) -> String {
+    normalize_repo_path(path)
+        .rsplit('/')
+        .next()
+        .unwrap_or_default()
+        .to_lowercase()
+}
+
+// 资产索引里没有已删除文件的内容，只能用元数据判断：
+// 文件名相似度占 0.6，行数接近程度（小/大）占 0.4
+fn metadata_similarity(path_a: &str, lines_a: usize, path_b: &str, lines_b: usize) -> f64 {
+    let name = similarity_score(SimAlgo::Levenshtein, &path_basename(path_a), &path_basename(path_b));
+    let larger = lines_a.max(lines_b);
+    let size = if larger == 0 {
+        1.0
+    } else {
+        lines_a.min(lines_b) as f64 / larger as f64
+    };
+    0.6 * name + 0.4 * size
+}
+
+// 语言不同直接判 0
+fn rename_score(dead: &Asset, alive: &Asset) -> f64 {
+    if dead.language != alive.language || dead.location == alive.location {
+        return 0.0;
+    }
+    metadata_similarity(&dead.location, dead.line_count, &alive.location, alive.line_count)
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Candidate {
+    pub tombstone_id: String,
+    pub tombstone_name: String,
+    pub original_path: String,
+    pub asset_location: String,
+    // content_hash / path / similar
+    pub reason: String,
+    pub confidence: f64,
+}
+
+// 墓碑与存活资产的匹配度：内容哈希相同 1.0，规范化路径相同 0.9，
+// 否则按 metadata_similarity（与 rename_score 同一套公式），语言不同为 0
+fn candidate_score(t: &Tombstone, asset: &Asset) -> (f64, &'static str) {
+    if t.content_hash.is_some() && t.content_hash == asset.content_hash {
+        return (1.0, "content_hash");
+    }
+    if repo_path(&t.original_path) == repo_path(&asset.location) {
+        return (0.9, "path");
+    }
+    if t.language != asset.language {
+        return (0.0, "similar");
+    }
+    let score = metadata_similarity(&t.original_path, t.line_count, &asset.location, asset.line_count);
+    (score, "similar")
+}
+
+// 原代码似乎已经重新出现、但还没有标记复活的墓碑，每个墓碑只给出得分最高的资产。
+// 只读；前端确认后把 (tombstone_id, asset_location) 交给 bulk_resurrect。
+// 仅凭相似度的候选需要达到 rename_similarity_threshold
+#[tauri::command]
+pub fn get_resurrection_candidates() -> Vec<Candidate> {
+    let threshold = resolve_config()
+        .map(|c| c.rename_similarity_threshold)
+        .unwrap_or(DEFAULT_RENAME_SIMILARITY_THRESHOLD);
+    let assets: Vec<Asset> = read_assets()
+        .unwrap_or_else(|e| {
+            eprintln!("⚠️ {}", e);
+            vec![]
+        })
+        .into_iter()
+        .filter(|a| a.alive)
+        .collect();
+
+    let mut candidates: Vec<Candidate> = load_tombstones_or_log()
+        .into_iter()
+        .filter(|t| t.resurrected_at.is_none())
+        .filter_map(|t| {
+            let (asset, (confidence, reason)) = assets
+                .iter()
+                .map(|a| (a, candidate_score(&t, a)))
+                .max_by(|a, b| a.1 .0.total_cmp(&b.1 .0))?;
+            if reason == "similar" && confidence < threshold {
+                return None;
+            }
+            Some(Candidate {
+                tombstone_id: t.id,
+                tombstone_name: t.name,
+                original_path: t.original_path,
+                asset_location: asset.location.clone(),
+                reason: reason.to_string(),
+                confidence,
+            })
+        })
+        .collect();
+
+    candidates.sort_by(|a, b| {
+        b.confidence
+            .total_cmp(&a.confidence)
+            .then_with(|| a.tombstone_id.cmp(&b.tombstone_id))
+    });
+    candidates
+}
+
+// ========== 复活风险 ==========
+
+const NECROMANCY_TOP_LIMIT: usize = 20;
+// 各因素的权重，合计为 1，得分落在 0–1
+const NECRO_WEIGHT_SIZE: f64 = 0.25;
+const NECRO_WEIGHT_UTILITY: f64 = 0.2;
+const NECRO_WEIGHT_RECENCY: f64 = 0.25;
+const NECRO_WEIGHT_LANGUAGE: f64 = 0.3;
+// 行数等于这个值时体积因素为 0.5，越小越接近 1
+const NECRO_SMALL_LINES: f64 = 100.0;
+// 死亡这么多天后新近因素衰减到 1/e
+const NECRO_RECENCY_DAYS: f64 = 90.0;
+const NECRO_UTILITY_TAGS: &[&str] = &["util", "utils", "helper", "helpers", "common", "shared", "lib"];
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RiskFactor {
+    // size / utility / recency / language
+    pub name: String,
+    pub value: f64,
+    pub weight: f64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RiskScore {
+    pub tombstone_id: String,
+    pub tombstone_name: String,
+    pub score: f64,
+    pub factors: Vec<RiskFactor>,
+}
+
+// 各语言的历史复活率，加一平滑 (复活数 + 1) / (墓碑数 + 2)：样本少的语言趋向 0.5，不会一两个样本就定成 0 或 1
+fn language_resurrection_rates(tombstones: &[Tombstone]) -> HashMap<String, f64> {
+    let mut counts: HashMap<String, (usize, usize)> = HashMap::new();
+    for t in tombstones {
+        let entry = counts.entry(language_key(&t.language)).or_insert((0, 0));
+        entry.0 += 1;
+        if t.resurrected_at.is_some() {
+            entry.1 += 1;
+        }
+    }
+    counts
+        .into_iter()
+        .map(|(language, (total, resurrected))| (language, (resurrected as f64 + 1.0) / (total as f64 + 2.0)))
+        .collect()
+}
+
+// 复活风险 = Σ 权重 × 因素值，每个因素都在 0–1：
+// size = 1 / (1 + 行数 / NECRO_SMALL_LINES)，小文件更容易被复制回来；
+// utility = 带工具类标签时为 1；recency = exp(-死亡天数 / NECRO_RECENCY_DAYS)；
+// language = 该语言的历史复活率
+fn necromancy_risk(t: &Tombstone, language_rate: f64, now: DateTime<Utc>) -> (f64, Vec<RiskFactor>) {
+    let size = 1.0 / (1.0 + t.line_count as f64 / NECRO_SMALL_LINES);
+    let utility = if t
+        .tags
+        .iter()
+        .any(|tag| NECRO_UTILITY_TAGS.contains(&tag.trim().to_lowercase().as_str()))
+    {
+        1.0
+    } else {
+        0.0
+    };
+    let days_dead = parse_timestamp(&t.died_at)
+        .map(|died| (now - died).num_seconds().max(0) as f64 / 86400.0)
+        .unwrap_or(f64::INFINITY);
+    let recency = (-days_dead / NECRO_RECENCY_DAYS).exp();
+
+    let factors = vec![
+        RiskFactor { name: String::from("size"), value: size, weight: NECRO_WEIGHT_SIZE },
+        RiskFactor { name: String::from("utility"), value: utility, weight: NECRO_WEIGHT_UTILITY },
+        RiskFactor { name: String::from("recency"), value: recency, weight: NECRO_WEIGHT_RECENCY },
+        RiskFactor { name: String::from("language"), value: language_rate, weight: NECRO_WEIGHT_LANGUAGE },
+    ];
+    let score = factors.iter().map(|f| f.weight * f.value).sum::<f64>().clamp(0.0, 1.0);
+    (score, factors)
+}
+
+// 预测哪些还躺着的墓碑最可能被复活，返回风险最高的 NECROMANCY_TOP_LIMIT 个及各因素明细
+#[tauri::command]
+pub fn get_necromancy_risk() -> Vec<RiskScore> {
+    let tombstones = load_tombstones_or_log();
+    let rates = language_resurrection_rates(&tombstones);
+    let now = Utc::now();
+
+    let mut scores: Vec<RiskScore> = tombstones
+        .iter()
+        .filter(|t| t.resurrected_at.is_none())
+        .map(|t| {
+            let rate = rates.get(&language_key(&t.language)).copied().unwrap_or(0.5);
+            let (score, factors) = necromancy_risk(t, rate, now);
+            RiskScore {
+                tombstone_id: t.id.clone(),
+                tombstone_name: t.name.clone(),
+                score,
+                factors,
+            }
+        })
+        .collect();
+
+    scores.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.tombstone_id.cmp(&b.tombstone_id)));
+    scores.truncate(NECROMANCY_TOP_LIMIT);
+    scores
+}
+
+// 为每个已死亡资产找得分最高且不低于阈值的存活资产，视为被移动/改名而不是死亡。
+// 每个存活资产最多认领一个死亡资产。返回 (旧路径, 新路径)
+fn detect_renames(assets: &[Asset], threshold: f64) -> Vec<(String, String)> {
+    let alive: Vec<&Asset> = assets.iter().filter(|a| a.alive).collect();
+    let mut claimed: HashSet<usize> = HashSet::new();
+    let mut moves = vec![];
+
+    for dead in assets.iter().filter(|a| !a.alive) {
+        let best = alive
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !claimed.contains(i))
+            .map(|(i, candidate)| (i, rename_score(dead, candidate)))
+            .filter(|(_, score)| *score >= threshold)
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+
+        if let Some((i, _)) = best {
+            claimed.insert(i);
+            moves.push((dead.location.clone(), alive[i].location.clone()));
+        }
+    }
+    moves
+}
+
+// 把资产索引中已死亡（alive = false）的资产落成墓碑，可安全重复执行。
+// 还活着但命中死亡规则的文件以 stale 为死因一并立碑。
+// 被识别为改名/移动的文件只记录日志，不立碑；dry_run 时只返回将会新增的墓碑，不写注册表
+fn bury_dead_assets(
+    assets: &[Asset],
+    rename_threshold: f64,
+    death_rules: &[CompiledDeathRule],
+    dry_run: bool,
+) -> Result<Vec<Tombstone>, String> {
+    let moved: HashSet<String> = detect_renames(assets, rename_threshold)
+        .into_iter()
+        .map(|(from, to)| {
+            println!("📦 {} 已移动到 {}，不立碑", from, to);
+            from
+        })
+        .collect();
+
+    let now = Utc::now();
+    let candidates: Vec<Tombstone> = assets
+        .iter()
+        .filter(|a| !a.exempt && a.tombstone_id.is_none())
+        .filter_map(|a| {
+            if !a.alive {
+                (!moved.contains(&a.location)).then(|| tombstone_from_asset(a, a.repo.as_deref(), "deleted"))
+            } else {
+                let rule = matching_death_rule(death_rules, a, now)?;
+                println!("🥀 {} 命中死亡规则 {}", a.location, rule.rule.name);
+                Some(tombstone_from_asset(a, a.repo.as_deref(), "stale"))
+            }
+        })
+        .collect();
+    if candidates.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let keys: HashSet<String> = candidates
+        .iter()
+        .map(|t| tombstone_key(t.repo.as_deref(), &t.original_path))
+        .collect();
+    let _lock = lock_data_file(&get_tombstone_registry_path())?;
+    let mut registry = read_tombstones()?;
+    let mut created = upsert_tombstones(&mut registry, candidates);
+    if dry_run {
+        return Ok(created);
+    }
+    if !created.is_empty() {
+        fill_git_authors(&mut registry, &mut created);
+        write_tombstones(&registry)?;
+    }
+    // 新立的和早已存在的墓碑都记到资产上
+    mark_buried_assets(&registry, &keys)?;
+    Ok(created)
+}
+
+// 给 keys 对应、注册表里已有墓碑的资产写上 tombstoneId；调用方持有注册表锁
+fn mark_buried_assets(registry: &[Tombstone], keys: &HashSet<String>) -> Result<usize, String> {
+    let ids: HashMap<String, &str> = registry
+        .iter()
+        .map(|t| (tombstone_key(t.repo.as_deref(), &t.original_path), t.id.as_str()))
+        .filter(|(key, _)| keys.contains(key))
+        .collect();
+    if ids.is_empty() {
+        return Ok(0);
+    }
+
+    let path = get_asset_index_path();
+    let _lock = lock_data_file(&path)?;
+    let mut assets = read_asset_values()?;
+    let mut marked = 0;
+    for asset in assets.iter_mut() {
+        if ["tombstoneId", "tombstone_id"].iter().any(|k| !asset[*k].is_null()) {
+            continue;
+        }
+        let Some(location) = asset["location"].as_str() else {
+            continue;
+        };
+        let key = tombstone_key(asset["repo"].as_str(), location);
+        if let Some(id) = ids.get(&key) {
+            asset["tombstoneId"] = serde_json::json!(id);
+            marked += 1;
+        }
+    }
+    if marked > 0 {
+        write_json_atomic(&path, &assets)?;
+    }
+    Ok(marked)
+}
+
+// 本地文件删除后，从所在 git 仓库找最后一次改动它的提交作者（删除提交本身也算）。
+// 文件所在目录可能也被删了，沿父目录向上找第一个存在的目录执行 git；
+// 不是本地路径、不在 git 仓库里或没装 git 时返回 None
+fn last_git_author(location: &str) -> Option<String> {
+    let path = Path::new(location);
+    if location.contains("://") || !path.is_absolute() {
+        return None;
+    }
+    let dir = path.ancestors().skip(1).find(|p| p.is_dir())?;
+    let output = std::process::Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["log", "-1", "--format=%an", "--"])
+        .arg(path)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let author = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!author.is_empty()).then_some(author)
+}
+
+// 只为本次新立的墓碑查作者，已有墓碑和 API 扫描来的（没有本地路径）保持 None
+fn fill_git_authors(registry: &mut [Tombstone], created: &mut [Tombstone]) {
+    for t in created.iter_mut().filter(|t| t.author.is_none()) {
+        let Some(author) = last_git_author(&t.original_path) else {
+            continue;
+        };
+        if let Some(entry) = registry.iter_mut().find(|r| r.id == t.id) {
+            entry.author = Some(author.clone());
+        }
+        t.author = Some(author);
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AuthorStat {
+    pub author: String,
+    pub tombstones: usize,
+    pub dead_lines: usize,
+}
+
+// 各作者名下的墓碑数与死代码行数（删除 ≠ 写烂，只是谁最后碰过它）。
+// 没有作者信息的归入 unknown；按墓碑数、再按行数降序
+#[tauri::command]
+pub fn get_author_stats() -> Vec<AuthorStat> {
+    let mut by_author: HashMap<String, AuthorStat> = HashMap::new();
+    for t in load_tombstones_or_log() {
+        let author = t
+            .author
+            .as_deref()
+            .map(str::trim)
+            .filter(|a| !a.is_empty())
+            .unwrap_or("unknown")
+            .to_string();
+        let stat = by_author.entry(author.clone()).or_insert(AuthorStat {
+            author,
+            tombstones: 0,
+            dead_lines: 0,
+        });
+        stat.tombstones += 1;
+        stat.dead_lines += t.line_count;
+    }
+
+    let mut stats: Vec<AuthorStat> = by_author.into_values().collect();
+    stats.sort_by(|a, b| {
+        b.tombstones
+            .cmp(&a.tombstones)
+            .then(b.dead_lines.cmp(&a.dead_lines))
+            .then_with(|| a.author.cmp(&b.author))
+    });
+    stats
+}
+
+// ========== 统计历史 ==========
+
+const STATS_HISTORY_LIMIT: usize = 365;
+
+#[derive(Serialize, Deserialize)]
+pub struct StatsSnapshot {
+    pub recorded_at: String,
+    #[serde(flatten)]
+    pub stats: Stats,
+}
+
+fn read_stats_history() -> Result<Vec<StatsSnapshot>, String> {
+    let path = get_stats_history_path();
+    if !data_file_exists(&path) {
+        return Ok(vec![]);
+    }
+    let content = read_data_file(&path)?;
+    serde_json::from_str(&content)
+        .map_err(|e| format!("解析统计历史失败: {}", e))
+}
+
+// 同一天只保留最新的一条，总数超过 STATS_HISTORY_LIMIT 时丢弃最旧的
+fn push_stats_snapshot(history: &mut Vec<StatsSnapshot>, stats: Stats, now: DateTime<Utc>) {
+    let today = now.date_naive();
+    if history
+        .last()
+        .and_then(|last| parse_timestamp(&last.recorded_at))
+        .is_some_and(|last| last.date_naive() == today)
+    {
+        history.pop();
+    }
+    history.push(StatsSnapshot {
+        recorded_at: now.to_rfc3339(),
+        stats,
+    });
+
+    if history.len() > STATS_HISTORY_LIMIT {
+        let overflow = history.len() - STATS_HISTORY_LIMIT;
+        history.drain(..overflow);
+    }
+}
+
+fn append_stats_snapshot(stats: Stats) -> Result<(), String> {
+    let _lock = lock_data_file(&get_stats_history_path())?;
+    let mut history = read_stats_history()?;
+    push_stats_snapshot(&mut history, stats, Utc::now());
+    write_json_atomic(&get_stats_history_path(), &history)
+}
+
+#[tauri::command]
+pub fn record_stats_snapshot() -> Result<(), String> {
+    append_stats_snapshot(get_stats())
+}
+
+// 按时间顺序返回最近 limit 条快照，供趋势图使用
+#[tauri::command]
+pub fn get_stats_history(limit: usize) -> Result<Vec<StatsSnapshot>, String> {
+    let mut history = read_stats_history()?;
+    if history.len() > limit {
+        history.drain(..history.len() - limit);
+    }
+    Ok(history)
+}
+
+// ========== 扫描命令 ==========
+
+#[tauri::command]
+pub async fn trigger_scan() -> Result<ScanResult, String> {
+    let config = resolve_config()?;
+    scan_org(&config, &config.target_org, false).await
+}
+
+// 临时扫描另一个组织，不修改保存的 target_org；其他设置（令牌、忽略规则等）照常生效。
+// dry_run 时不写任何数据文件，只报告将会新增的墓碑数
+#[tauri::command]
+pub async fn trigger_scan_org(org: String, dry_run: bool) -> Result<ScanResult, String> {
+    let org = org.trim().to_string();
+    if org.is_empty() {
+        return Err(String::from("组织名不能为空"));
+    }
+    let config = resolve_config()?;
+    scan_org(&config, &org, dry_run).await
+}
+
+async fn scan_org(config: &Config, org: &str, dry_run: bool) -> Result<ScanResult, String> {
+    let Some(token) = config.github_token.as_deref().filter(|t| !t.is_empty()) else {
+        return Err(format!(
+            "未配置 GitHub 令牌，无法扫描组织 {}：请在设置中填写令牌或设置 GITHUB_TOKEN 环境变量",
+            org
+        ));
+    };
+    println!("🔄 开始扫描组织 {}{}...", org, if dry_run { "（预演）" } else { "" });
+
+    // 枚举组织的仓库（是否包含私有/内部仓库由 include_private 决定）
+    let client = github_client(config)?;
+    let listed = list_org_repos(&client, Some(token), org, config.include_private).await?;
+    println!("📚 {} 下共有 {} 个仓库", org, listed.len());
+    let repos = listed.len();
+    if !dry_run {
+        match assign_asset_repos(&listed) {
+            Ok(0) => {}
+            Ok(assigned) => println!("🏷️ 为 {} 个资产补上所属仓库", assigned),
+            Err(e) => eprintln!("⚠️ 补充资产仓库失败: {}", e),
+        }
+    }
+
+    let mut burial = ScanBurial::new(config, dry_run)?;
+    let scanned = walk_org_repos(&client, token, org, &listed, config.scan_concurrency, &mut burial).await?;
+
+    // 已经不在组织里的仓库（删除或转走）整体标记死亡；列出的仓库已在遍历中处理过，
+    // 其中取文件树失败的本次不参与死亡判定
+    let org_prefix = format!("https://github.com/{}/", org).to_lowercase();
+    let listed_prefixes: Vec<String> = listed
+        .iter()
+        .map(|r| format!("https://github.com/{}/", r.full_name).to_lowercase())
+        .collect();
+    burial.merge(vec![], |location: &str| {
+        let location = location.to_lowercase();
+        location.starts_with(&org_prefix) && !listed_prefixes.iter().any(|p| location.starts_with(p))
+    })?;
+
+    if !dry_run {
+        if let Err(e) = refresh_asset_hashes() {
+            eprintln!("⚠️ 更新内容哈希失败: {}", e);
+        }
+        match hash_remote_blobs(config).await {
+            Ok(0) => {}
+            Ok(hashed) => println!("🔢 为 {} 个远程文件算出内容哈希", hashed),
+            Err(e) => eprintln!("⚠️ 计算远程文件哈希失败: {}", e),
+        }
+    }
+
+    // 最后整体补扫一遍：消失仓库里的文件、本地资产，以及命中死亡规则的存活资产
+    burial.bury(|_: &str| true)?;
+    let buried_count = burial.buried.len();
+
+    if dry_run {
+        return Ok(ScanResult {
+            success: true,
+            scanned,
+            zombies: burial.newly_dead,
+            repos,
+            message: tr_args("scan.dry_run", &[("count", buried_count.to_string())]),
+        });
+    }
+
+    // 外部扫描器失败不影响本次扫描：记为后台错误，统计快照照常记录
+    if let Some(cmd) = config.external_scanner_cmd.as_deref().filter(|c| !c.trim().is_empty()) {
+        match run_external_scanner(cmd).await.and_then(ingest_alerts) {
+            Ok(added) => println!("🧟 外部扫描器新增 {} 条提醒", added),
+            Err(e) => match APP_HANDLE.get() {
+                Some(app) => record_background_error(app, "external-scanner", e),
+                None => eprintln!("⚠️ 外部扫描器失败: {}", e),
+            },
+        }
+    }
+
+    if let Err(e) = append_stats_snapshot(get_stats()) {
+        eprintln!("⚠️ 记录统计快照失败: {}", e);
+    }
+    emit_stats_updated();
+
+    let zombies = burial.newly_dead;
+    println!("✅ 扫描完成！遍历 {} 个文件，新发现 {} 个死亡文件", scanned, zombies);
+
+    Ok(ScanResult {
+        success: true,
+        scanned,
+        zombies,
+        repos,
+        message: tr_args(
+            "scan.complete",
+            &[("files", scanned.to_string()), ("count", zombies.to_string())],
+        ),
+    })
+}
+
+// ========== 远程扫描 ==========
+
+// 估算行数用：文件树只给字节数，按平均每行这么多字节折算
+const REMOTE_BYTES_PER_LINE: u64 = 40;
+
+#[derive(Serialize, Clone, Debug)]
+pub struct ScanProgress {
+    pub org: String,
+    pub repo: Option<String>,
+    pub repos_done: usize,
+    pub repos_total: usize,
+    pub files_walked: usize,
+}
+
+fn emit_scan_progress(progress: &ScanProgress) {
+    if let Some(app) = APP_HANDLE.get() {
+        if let Err(e) = app.emit("scan-progress", progress) {
+            eprintln!("⚠️ 推送 scan-progress 失败: {}", e);
+        }
+    }
+}
+
+// 远程文件的 location 用 blob/HEAD 形式：始终指向默认分支，默认分支改名也不会让整个仓库“死掉”
+fn remote_location(repo: &GhRepo, path: &str) -> String {
+    format!("https://github.com/{}/blob/HEAD/{}", repo.full_name, path)
+}
+
+fn remote_asset(repo: &GhRepo, entry: &GhTreeEntry) -> Asset {
+    let location = remote_location(repo, &entry.path);
+    Asset {
+        id: format!("gh-{:016x}", stable_hash(&location)),
+        name: path_basename(&entry.path),
+        r#type: String::from("file"),
+        language: language_for_path(&entry.path),
+        tags: vec![],
+        alive: true,
+        line_count: entry.size.map_or(0, |size| size.div_ceil(REMOTE_BYTES_PER_LINE) as usize),
+        exempt: false,
+        content_hash: None,
+        repo: Some(repo.full_name.clone()),
+        author: None,
+        updated_at: None,
+        blob_sha: Some(entry.sha.clone()),
+        tombstone_id: None,
+        location,
+    }
+}
+
+// 按 scan_concurrency 并发拉取各仓库的文件树。每完成一个仓库就合并进索引、为其中死亡的文件立碑
+// 并推送 tombstone-created，然后推送一次 scan-progress；大组织不必等整个遍历结束才看到新墓碑。
+// 文件树取失败的仓库：本次没扫到不代表文件死了，不参与死亡判定。返回遍历到的文件数
+async fn walk_org_repos(
+    client: &reqwest::Client,
+    token: &str,
+    org: &str,
+    repos: &[GhRepo],
+    concurrency: usize,
+    burial: &mut ScanBurial,
+) -> Result<usize, String> {
+    let mut progress = ScanProgress {
+        org: org.to_string(),
+        repo: None,
+        repos_done: 0,
+        repos_total: repos.len(),
+        files_walked: 0,
+    };
+    emit_scan_progress(&progress);
+
+    for chunk in repos.chunks(concurrency.max(1)) {
+        let mut tasks = tokio::task::JoinSet::new();
+        for repo in chunk {
+            let client = client.clone();
+            let token = token.to_string();
+            let repo = repo.clone();
+            tasks.spawn(async move {
+                let files = fetch_repo_files(&client, Some(&token), &repo).await;
+                (repo, files)
+            });
+        }
+        while let Some(joined) = tasks.join_next().await {
+            let (repo, files) = match joined {
+                Ok(result) => result,
+                Err(e) => {
+                    eprintln!("⚠️ 扫描任务异常退出: {}", e);
+                    continue;
+                }
+            };
+            match files {
+                Ok(files) => {
+                    progress.files_walked += files.len();
+                    let assets = files.iter().map(|entry| remote_asset(&repo, entry)).collect();
+                    let repo_prefix = format!("https://github.com/{}/", repo.full_name).to_lowercase();
+                    let in_repo = |location: &str| location.to_lowercase().starts_with(&repo_prefix);
+                    burial.merge(assets, in_repo)?;
+                    burial.bury(in_repo)?;
+                }
+                Err(e) => eprintln!("⚠️ {}", e),
+            }
+            progress.repos_done += 1;
+            progress.repo = Some(repo.full_name);
+            emit_scan_progress(&progress);
+        }
+    }
+    Ok(progress.files_walked)
+}
+
+// 一次组织扫描的埋葬状态，在逐仓库的合并和埋葬之间共享
+struct ScanBurial {
+    ignore_rules: ignore::gitignore::Gitignore,
+    death_rules: Vec<CompiledDeathRule>,
+    rename_threshold: f64,
+    dry_run: bool,
+    // 预演时索引不写回，合并结果留在内存里，后面的仓库接着合并
+    preview: Option<Vec<serde_json::Value>>,
+    // 本次扫描中新出现的 blob sha：死亡文件的 sha 在其中时视为移动。
+    // 跨仓库移动只有目标仓库先于来源仓库遍历完时才能识别
+    added_shas: HashSet<String>,
+    newly_dead: usize,
+    // 已立碑（预演时是将会立碑）的墓碑 id，避免整体补扫时重复计数
+    buried: HashSet<String>,
+}
+
+impl ScanBurial {
+    fn new(config: &Config, dry_run: bool) -> Result<Self, String> {
+        Ok(ScanBurial {
+            ignore_rules: load_ignore_rules(config)?,
+            death_rules: compile_death_rules(&config.death_rules)?,
+            rename_threshold: config.rename_similarity_threshold,
+            dry_run,
+            preview: None,
+            added_shas: HashSet::new(),
+            newly_dead: 0,
+            buried: HashSet::new(),
+        })
+    }
+
+    // 把本次遍历到的文件合并进资产索引。in_scope 范围内这次没扫到的远程资产标记死亡，
+    // 但 blob sha 和本次扫描新出现的文件相同的视为移动：直接从索引移除旧条目，不立碑
+    fn merge(&mut self, assets: Vec<Asset>, in_scope: impl Fn(&str) -> bool) -> Result<(), String> {
+        let path = get_asset_index_path();
+        let (mut existing, _lock) = match self.preview.take() {
+            Some(values) => (values, None),
+            None => {
+                let lock = lock_data_file(&path)?;
+                (read_asset_values()?, Some(lock))
+            }
+        };
+        let original_len = existing.len();
+        let was_alive: Vec<bool> = existing.iter().map(|a| a["alive"].as_bool().unwrap_or(false)).collect();
+
+        let summary = merge_asset_values(&mut existing, assets, in_scope)?;
+
+        self.added_shas.extend(
+            existing[original_len..]
+                .iter()
+                .filter_map(|a| a["blob_sha"].as_str().map(String::from)),
+        );
+        let moved: HashSet<usize> = (0..original_len)
+            .filter(|&i| was_alive[i] && !existing[i]["alive"].as_bool().unwrap_or(false))
+            .filter(|&i| existing[i]["blob_sha"].as_str().is_some_and(|sha| self.added_shas.contains(sha)))
+            .collect();
+        for &i in &moved {
+            println!("📦 {} 已移动，不立碑", existing[i]["location"].as_str().unwrap_or_default());
+        }
+        let mut index = 0;
+        existing.retain(|_| {
+            let keep = !moved.contains(&index);
+            index += 1;
+            keep
+        });
+
+        let newly_dead = summary.marked_dead - moved.len();
+        self.newly_dead += newly_dead;
+        if summary.added + summary.marked_dead + moved.len() > 0 {
+            println!(
+                "🗂️ 资产索引：新增 {}，更新 {}，新死亡 {}，移动 {}",
+                summary.added,
+                summary.updated,
+                newly_dead,
+                moved.len()
+            );
+        }
+
+        if self.dry_run {
+            self.preview = Some(existing);
+            return Ok(());
+        }
+        if summary.added + summary.updated + summary.marked_dead > 0 {
+            backup_file(&path)?;
+            write_json_atomic(&path, &existing)?;
+        }
+        Ok(())
+    }
+
+    // 为 in_scope 范围内（忽略规则之外）的死亡资产和命中死亡规则的资产立碑，推送 tombstone-created。
+    // 资产索引读不了（例如格式不兼容）时跳过埋葬，不让整个扫描失败
+    fn bury(&mut self, in_scope: impl Fn(&str) -> bool) -> Result<(), String> {
+        let values = match &self.preview {
+            Some(values) => Ok(values.clone()),
+            None => read_asset_values(),
+        };
+        let assets = values.and_then(|values| {
+            values
+                .into_iter()
+                .map(serde_json::from_value::<Asset>)
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| format!("解析资产索引失败: {}", e))
+        });
+        let assets: Vec<Asset> = match assets {
+            Ok(assets) => assets
+                .into_iter()
+                .filter(|a| in_scope(&a.location) && !is_ignored(&self.ignore_rules, &a.location))
+                .collect(),
+            Err(e) => {
+                eprintln!("⚠️ 跳过埋葬: {}", e);
+                return Ok(());
+            }
+        };
+
+        let buried = bury_dead_assets(&assets, self.rename_threshold, &self.death_rules, self.dry_run)?;
+        let created: Vec<Tombstone> = buried.into_iter().filter(|t| self.buried.insert(t.id.clone())).collect();
+        if !created.is_empty() && !self.dry_run {
+            println!("⚰️ 新埋葬 {} 个墓碑", created.len());
+            emit_tombstones_created(&created);
+        }
+        Ok(())
+    }
+}
+
+// 资产属于哪个仓库：GitHub 地址直接取 owner/name；本地路径中有且只有一个仓库名作为目录出现时取该仓库。
+// 匹配不唯一时不猜
+fn repo_for_location(location: &str, repos: &[GhRepo]) -> Option<String> {
+    let lower = location.to_lowercase();
+    if let Some(rest) = lower.split("github.com/").nth(1) {
+        let full_name: String = rest.split('/').take(2).collect::<Vec<_>>().join("/");
+        return repos
+            .iter()
+            .find(|r| r.full_name.to_lowercase() == full_name)
+            .map(|r| r.full_name.clone());
+    }
+
+    let normalized = normalize_repo_path(&lower);
+    let segments: Vec<&str> = normalized.split('/').collect();
+    let dirs = &segments[..segments.len().saturating_sub(1)];
+    let mut matches = repos.iter().filter(|r| dirs.contains(&r.name.to_lowercase().as_str()));
+    match (matches.next(), matches.next()) {
+        (Some(repo), None) => Some(repo.full_name.clone()),
+        _ => None,
+    }
+}
+
+// 扫描时用枚举到的仓库列表为还没有 repo 的资产补上所属仓库；已有的值不覆盖
+fn assign_asset_repos(repos: &[GhRepo]) -> Result<usize, String> {
+    let path = get_asset_index_path();
+    let _lock = lock_data_file(&path)?;
+    let mut assets = read_asset_values()?;
+
+    let mut assigned = 0;
+    for asset in assets.iter_mut() {
+        if asset["repo"].as_str().is_some_and(|r| !r.is_empty()) {
+            continue;
+        }
+        let Some(location) = asset["location"].as_str() else {
+            continue;
+        };
+        if let Some(repo) = repo_for_location(location, repos) {
+            asset["repo"] = serde_json::json!(repo);
+            assigned += 1;
+        }
+    }
+    if assigned > 0 {
+        write_json_atomic(&path, &assets)?;
+    }
+    Ok(assigned)
+}
+
+// ========== 扫描预估 ==========
+
+const DEFAULT_SCAN_CONCURRENCY: usize = 4;
+const SCAN_ESTIMATE_SAMPLE_REPOS: usize = 3;
+// 单次 API 请求的平均耗时与每个文件的本地比对耗时（经验值）
+const AVG_REQUEST_SECS: f64 = 0.5;
+const PER_FILE_SECS: f64 = 0.001;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ScanEstimate {
+    pub repo_count: usize,
+    pub avg_files_per_repo: f64,
+    pub estimated_calls: u64,
+    pub estimated_secs: u64,
+    pub will_hit_rate_limit: bool,
+}
+
+// 扫描的 API 开销：仓库列表分页 + 每个仓库一次文件树请求。
+// 抽样几个仓库的文件树估算平均文件数，按 scan_concurrency 并发和剩余配额外推耗时；
+// 配额不够时把等待配额重置的时间也算进去
+#[tauri::command]
+pub async fn estimate_scan(org: String) -> Result<ScanEstimate, String> {
+    let config = resolve_config()?;
+    let token = config.github_token.as_deref().filter(|t| !t.is_empty());
+    let client = github_client(&config)?;
+
+    let repos = list_org_repos(&client, token, &org, config.include_private).await?;
+    let list_calls = (repos.len() / GITHUB_PAGE_SIZE + 1) as u64;
+
+    let mut sampled_files = 0;
+    let sample: Vec<&GhRepo> = repos.iter().take(SCAN_ESTIMATE_SAMPLE_REPOS).collect();
+    for repo in &sample {
+        sampled_files += fetch_repo_files(&client, token, repo).await?.len();
+    }
+    let avg_files_per_repo = if sample.is_empty() {
+        0.0
+    } else {
+        sampled_files as f64 / sample.len() as f64
+    };
+
+    let estimated_calls = list_calls + repos.len() as u64;
+    let concurrency = config.scan_concurrency.max(1) as f64;
+    let mut estimated_secs = (estimated_calls as f64 / concurrency).ceil() * AVG_REQUEST_SECS
+        + avg_files_per_repo * repos.len() as f64 * PER_FILE_SECS;
+
+    let rate_limit = fetch_rate_limit(&client, token).await?;
+    let will_hit_rate_limit = estimated_calls > rate_limit.remaining;
+    if will_hit_rate_limit {
+        estimated_secs += (rate_limit.reset - Utc::now().timestamp()).max(0) as f64;
+    }
+
+    Ok(ScanEstimate {
+        repo_count: repos.len(),
+        avg_files_per_repo,
+        estimated_calls,
+        estimated_secs: estimated_secs.ceil() as u64,
+        will_hit_rate_limit,
+    })
+}
+
+// ========== 相似度算法 ==========
+
+// 可选的相似度算法（速度 vs 对重排的敏感度）：
+// - Jaccard: 3-token 滑窗 (shingle) 集合的交并比。速度快，能容忍少量改名和插入，
+//   大段重排只会部分降分。默认算法，适合大多数代码库。
+// - Levenshtein: 折叠空白后的字符级编辑距离。对细微改动最敏感，但复杂度 O(n·m)，
+//   且挪动一个函数就会大幅降分。输入会截断到 LEVENSHTEIN_MAX_CHARS 个字符。
+// - TokenCosine: token 词频向量的余弦相似度。完全忽略顺序，对重排免疫，
+//   但词汇相近的不同代码也可能得到高分。
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum SimAlgo {
+    #[default]
+    Jaccard,
+    Levenshtein,
+    TokenCosine,
+}
+
+const SHINGLE_SIZE: usize = 3;
+const LEVENSHTEIN_MAX_CHARS: usize = 4000;
+
+fn tokenize(source: &str) -> Vec<String> {
+    source
+        .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|t| !t.is_empty())
+        .map(|t| t.to_lowercase())
+        .collect()
+}
+
+fn shingles(tokens: &[String]) -> HashSet<String> {
+    if tokens.len() < SHINGLE_SIZE {
+        return tokens.iter().cloned().collect();
+    }
+    tokens.windows(SHINGLE_SIZE).map(|w| w.join(" ")).collect()
+}
+
+fn jaccard_similarity(a: &str, b: &str) -> f64 {
+    let sa = shingles(&tokenize(a));
+    let sb = shingles(&tokenize(b));
+    if sa.is_empty() && sb.is_empty() {
+        return 1.0;
+    }
+    let intersection = sa.intersection(&sb).count();
+    let union = sa.union(&sb).count();
+    intersection as f64 / union as f64
+}
+
+fn levenshtein_similarity(a: &str, b: &str) -> f64 {
+    // 折叠空白，避免缩进差异主导编辑距离
+    let collapse = |s: &str| -> Vec<char> {
+        s.split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .chars()
+            .take(LEVENSHTEIN_MAX_CHARS)
+            .collect()
+    };
+    let a = collapse(a);
+    let b = collapse(b);
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut curr = vec![0; b.len() + 1];
+    for (i, ca) in a.iter().enumerate() {
+        curr[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let cost = if ca == cb { 0 } else { 1 };
+            curr[j + 1] = (prev[j + 1] + 1).min(curr[j] + 1).min(prev[j] + cost);
+        }
+        std::mem::swap(&mut prev, &mut curr);
+    }
+
+    1.0 - prev[b.len()] as f64 / a.len().max(b.len()) as f64
+}
+
+fn term_frequencies(source: &str) -> HashMap<String, f64> {
+    let mut freq = HashMap::new();
+    for token in tokenize(source) {
+        *freq.entry(token).or_insert(0.0) += 1.0;
+    }
+    freq
+}
+
+fn token_cosine_similarity(a: &str, b: &str) -> f64 {
+    let fa = term_frequencies(a);
+    let fb = term_frequencies(b);
+    if fa.is_empty() && fb.is_empty() {
+        return 1.0;
+    }
+
+    let dot: f64 = fa
+        .iter()
+        .filter_map(|(token, x)| fb.get(token).map(|y| x * y))
+        .sum();
+    let norm_a = fa.values().map(|x| x * x).sum::<f64>().sqrt();
+    let norm_b = fb.values().map(|x| x * x).sum::<f64>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return 0.0;
+    }
+    (dot / (norm_a * norm_b)).min(1.0)
+}
+
+// 所有相似度计算（交互命令与本地诈尸检测）都走这里，保证使用同一个配置的算法
+pub fn similarity_score(algo: SimAlgo, a: &str, b: &str) -> f64 {
+    match algo {
+        SimAlgo::Jaccard => jaccard_similarity(a, b),
+        SimAlgo::Levenshtein => levenshtein_similarity(a, b),
+        SimAlgo::TokenCosine => token_cosine_similarity(a, b),
+    }
+}
+
+#[tauri::command]
+pub fn compute_similarity(a: String, b: String) -> Result<f64, String> {
+    let config = resolve_config()?;
+    Ok(similarity_score(config.similarity_algo, &a, &b))
+}
+
+// ========== 诈尸提醒功能 ==========
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ZombieAlert {
+    pub id: String,
+    pub corpse_repo: String,
+    pub corpse_path: String,
+    pub zombie_repo: String,
+    pub zombie_path: String,
+    pub similarity: f64,
+    pub resurrection_type: String,
+    pub confidence: f64,
+    pub detected_at: String,
+    pub notified: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ZombieAlerts {
+    pub alerts: Vec<ZombieAlert>,
+    pub last_check: String,
+    pub total_alerts: usize,
+    pub unread_count: usize,
+}
+
+fn get_zombie_alerts_path() -> PathBuf {
+    app_data_dir().0.join("zombie-alerts.json")
+}
+
+#[tauri::command]
+pub fn get_zombie_alerts() -> ZombieAlerts {
+    let path = get_zombie_alerts_path();
+
+    if resolve_config().map_or(false, |c| c.prune_orphan_alerts_on_load) {
+        if let Err(e) = prune_orphan_alerts() {
+            eprintln!("⚠️ 清理孤儿提醒失败: {}", e);
+        }
+    }
+
+    let content = if data_file_exists(&path) {
+        read_data_file(&path)
+            .map_err(|e| eprintln!("⚠️ {}", e))
+            .ok()
+    } else {
+        None
+    };
+
+    if let Some(content) = content {
+        if let Ok(data) = serde_json::from_str::<serde_json::Value>(&content) {
+            let alerts: Vec<ZombieAlert> = data["alerts"]
+                .as_array()
+                .map(|arr| {
+                    arr.iter()
+                        .filter_map(|v| serde_json::from_value(v.clone()).ok())
+                        .collect()
+                })
+                .unwrap_or_default();
+
+            let total_alerts = alerts.len();
+            let unread_count = alerts.iter().filter(|a| !a.notified).count();
+
+            return ZombieAlerts {
+                alerts,
+                last_check: data["last_check"]
+                    .as_str()
+                    .map(String::from)
+                    .unwrap_or_else(|| tr("alerts.never_checked")),
+                total_alerts,
+                unread_count,
+            };
+        }
+    }
+
+    ZombieAlerts {
+        alerts: vec![],
+        last_check: tr("alerts.never_checked"),
+        total_alerts: 0,
+        unread_count: 0,
+    }
+}
+
+#[tauri::command]
+pub fn mark_alert_read(alert_id: String) -> Result<(), String> {
+    let path = get_zombie_alerts_path();
+    let _lock = lock_data_file(&path)?;
+    
+    if !data_file_exists(&path) {
+        return Ok(());
+    }
+    
+    let content = read_data_file(&path)?;
+    
+    let mut data: serde_json::Value = serde_json::from_str(&content)
+        .map_err(|e| e.to_string())?;
+    
+    if let Some(alerts) = data["alerts"].as_array_mut() {
+        for alert in alerts {
+            if alert["id"] == alert_id {
+                alert["notified"] = serde_json::json!(true);
+                break;
+            }
+        }
+    }
+    
+    // 经由数据文件写入工具，保持与 compress_data 设置一致的存储形式
+    write_json_atomic(&path, &data)
+}
+
+#[tauri::command]
+pub fn clear_all_alerts() -> Result<(), String> {
+    let alerts_data = ZombieAlerts {
+        alerts: vec![],
+        last_check: Utc::now().to_rfc3339(),
+        total_alerts: 0,
+        unread_count: 0,
+    };
+    
+    let path = get_zombie_alerts_path();
+    let _lock = lock_data_file(&path)?;
+    write_json_atomic(&path, &alerts_data)
+}
+
+// 统一路径写法，便于比较：去掉开头的 ./ 和 /，反斜杠换成 /
+fn normalize_repo_path(path: &str) -> String {
+    let path = path.trim().replace('\\', "/");
+    path.trim_start_matches("./").trim_start_matches('/').to_string()
+}
+
+// 远程资产的 location 是 https://github.com/<owner>/<repo>/blob/<ref>/<path>，
+// 取出其中的仓库内路径；不是这种形式就返回 None
+fn remote_repo_path(location: &str) -> Option<&str> {
+    let rest = location.trim().strip_prefix("https://github.com/")?;
+    let mut parts = rest.splitn(5, '/');
+    match (parts.next(), parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some(_), Some(_), Some("blob"), Some(_), Some(path)) if !path.is_empty() => Some(path),
+        _ => None,
+    }
+}
+
+// 墓碑、忽略规则和 GitHub API 统一使用的仓库内路径：远程 URL 去掉前缀，再做规范化。
+// 旧版本把 URL 直接存进了 original_path，这里同样能还原
+fn repo_path(location: &str) -> String {
+    normalize_repo_path(remote_repo_path(location).unwrap_or(location))
+}
+
+fn alert_has_tombstone(alert: &serde_json::Value, tombstones: &[Tombstone]) -> bool {
+    let corpse_path = repo_path(alert["corpse_path"].as_str().unwrap_or(""));
+    let corpse_repo = alert["corpse_repo"].as_str().unwrap_or("");
+    tombstones.iter().any(|t| {
+        repo_path(&t.original_path) == corpse_path
+            && (corpse_repo.is_empty()
+                || t.repo.as_deref().map_or(true, |r| r.eq_ignore_ascii_case(corpse_repo)))
+    })
+}
+
+// 删除 corpse_repo / corpse_path 已找不到对应墓碑的提醒，并重算计数。
+// 注册表不存在时不做任何事，避免把所有提醒都当成孤儿
+#[tauri::command]
+pub fn prune_orphan_alerts() -> Result<usize, String> {
+    let path = get_zombie_alerts_path();
+    if !data_file_exists(&path) || !data_file_exists(&get_tombstone_registry_path()) {
+        return Ok(0);
+    }
+
+    let tombstones = read_tombstones()?;
+    let _lock = lock_data_file(&path)?;
+    let mut data: serde_json::Value = serde_json::from_str(&read_data_file(&path)?)
+        .map_err(|e| format!("解析诈尸提醒失败: {}", e))?;
+
+    let Some(alerts) = data["alerts"].as_array_mut() else {
+        return Ok(0);
+    };
+    let before = alerts.len();
+    alerts.retain(|alert| alert_has_tombstone(alert, &tombstones));
+    let pruned = before - alerts.len();
+    if pruned == 0 {
+        return Ok(0);
+    }
+
+    let total = alerts.len();
+    let unread = alerts.iter().filter(|a| !a["notified"].as_bool().unwrap_or(false)).count();
+    data["total_alerts"] = serde_json::json!(total);
+    data["unread_count"] = serde_json::json!(unread);
+
+    write_json_atomic(&path, &data)?;
+    Ok(pruned)
+}
+
+// role = "corpse": 从 repo 死掉后诈尸到别处的代码；role = "zombie": 在 repo 里诈尸的代码
+#[tauri::command]
+pub fn get_alerts_for_repo(repo: String, role: String) -> Result<Vec<ZombieAlert>, String> {
+    let by_corpse = match role.as_str() {
+        "corpse" => true,
+        "zombie" => false,
+        other => return Err(format!("未知的 role: {}（可选 corpse / zombie）", other)),
+    };
+
+    let mut alerts: Vec<ZombieAlert> = get_zombie_alerts()
+        .alerts
+        .into_iter()
+        .filter(|a| {
+            let candidate = if by_corpse { &a.corpse_repo } else { &a.zombie_repo };
+            candidate.eq_ignore_ascii_case(&repo)
+        })
+        .collect();
+
+    alerts.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+    Ok(alerts)
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AlertTypeGroup {
+    pub resurrection_type: String,
+    pub count: usize,
+    pub avg_confidence: f64,
+}
+
+// 按 resurrection_type（复制粘贴 / 重构 / 重新引入…）聚合提醒
+#[tauri::command]
+pub fn get_alerts_by_type() -> Vec<AlertTypeGroup> {
+    let mut groups: HashMap<String, (usize, f64)> = HashMap::new();
+    for alert in get_zombie_alerts().alerts {
+        let entry = groups.entry(alert.resurrection_type).or_insert((0, 0.0));
+        entry.0 += 1;
+        entry.1 += alert.confidence;
+    }
+
+    let mut result: Vec<AlertTypeGroup> = groups
+        .into_iter()
+        .map(|(resurrection_type, (count, total_confidence))| AlertTypeGroup {
+            resurrection_type,
+            count,
+            avg_confidence: total_confidence / count as f64,
+        })
+        .collect();
+
+    result.sort_by(|a, b| {
+        b.count
+            .cmp(&a.count)
+            .then_with(|| a.resurrection_type.cmp(&b.resurrection_type))
+    });
+    result
+}
+
+// 把新提醒合并进提醒文件：按 id 去重，已有提醒（包括已读状态）保持不变。返回新增数量
+fn ingest_alerts(incoming: Vec<ZombieAlert>) -> Result<usize, String> {
+    let path = get_zombie_alerts_path();
+    let _lock = lock_data_file(&path)?;
+
+    let mut data: serde_json::Value = if data_file_exists(&path) {
+        serde_json::from_str(&read_data_file(&path)?)
+            .map_err(|e| format!("解析诈尸提醒失败: {}", e))?
+    } else {
+        serde_json::json!({ "alerts": [] })
+    };
+    if !data["alerts"].is_array() {
+        data["alerts"] = serde_json::json!([]);
+    }
+
+    let alerts = data["alerts"].as_array_mut().expect("alerts 已确保为数组");
+    let mut known: HashSet<String> = alerts
+        .iter()
+        .filter_map(|a| a["id"].as_str().map(String::from))
+        .collect();
+
+    let mut added = 0;
+    for alert in incoming {
+        if !known.insert(alert.id.clone()) {
+            continue;
+        }
+        alerts.push(serde_json::to_value(&alert).map_err(|e| format!("序列化提醒失败: {}", e))?);
+        added += 1;
+    }
+
+    let total = alerts.len();
+    let unread = alerts
+        .iter()
+        .filter(|a| !a["notified"].as_bool().unwrap_or(false))
+        .count();
+    data["total_alerts"] = serde_json::json!(total);
+    data["unread_count"] = serde_json::json!(unread);
+    data["last_check"] = serde_json::json!(Utc::now().to_rfc3339());
+
+    write_json_atomic(&path, &data)?;
+    Ok(added)
+}
+
+// ========== 诈尸检测 ==========
+
+// 低于这个相似度不报
+const ZOMBIE_MIN_SIMILARITY: f64 = 0.5;
+const ZOMBIE_VERBATIM_SIMILARITY: f64 = 0.95;
+const ZOMBIE_REFACTORED_SIMILARITY: f64 = 0.75;
+// 较短一方的 token 数达到这个值时置信度不再打折；几行的小文件撞车的概率太高
+const ZOMBIE_CONFIDENT_TOKENS: usize = 50;
+// 行数相差超过这个倍数的文件对不比较
+const ZOMBIE_MAX_SIZE_RATIO: usize = 4;
+
+fn resurrection_type(similarity: f64) -> &'static str {
+    if similarity >= ZOMBIE_VERBATIM_SIMILARITY {
+        "verbatim"
+    } else if similarity >= ZOMBIE_REFACTORED_SIMILARITY {
+        "refactored"
+    } else {
+        "partial"
+    }
+}
+
+// 置信度 = 相似度 × min(1, 较短一方 token 数 / ZOMBIE_CONFIDENT_TOKENS)
+fn zombie_confidence(similarity: f64, corpse: &str, zombie: &str) -> f64 {
+    let shorter = tokenize(corpse).len().min(tokenize(zombie).len());
+    similarity * (shorter as f64 / ZOMBIE_CONFIDENT_TOKENS as f64).min(1.0)
+}
+
+fn comparable_sizes(a: usize, b: usize) -> bool {
+    let (small, large) = (a.min(b).max(1), a.max(b).max(1));
+    large <= small * ZOMBIE_MAX_SIZE_RATIO
+}
+
+// 拿每个未复活墓碑的原文件（优先用缓存，没有时用令牌从 GitHub 取）与仍存活的文件比较，
+// 每个墓碑只报告最相似的一个文件。语言不同或大小悬殊的文件对直接跳过；
+// 远程资产只在通过这两道筛选后才按 blob_sha 取内容（带缓存），同一个 blob 只取一次。
+// 结果合并进 zombie-alerts.json，已有提醒的已读状态保留
+#[tauri::command]
+pub async fn detect_zombies() -> Result<Vec<ZombieAlert>, String> {
+    let config = resolve_config()?;
+    let (local, remote): (Vec<Asset>, Vec<Asset>) = read_assets()?
+        .into_iter()
+        .filter(|a| a.alive)
+        .partition(|a| remote_repo_path(&a.location).is_none());
+    let live: Vec<(Asset, String)> = local
+        .into_iter()
+        .filter_map(|a| {
+            let file = Path::new(&a.location);
+            if !fs::metadata(file).map_or(false, |m| m.is_file() && m.len() <= MAX_HASHED_FILE_BYTES) {
+                return None;
+            }
+            let content = fs::read_to_string(file).ok()?;
+            Some((a, content))
+        })
+        .collect();
+    let remote: Vec<Asset> = remote
+        .into_iter()
+        .filter(|a| a.repo.is_some() && a.blob_sha.is_some())
+        .filter(|a| a.line_count as u64 * REMOTE_BYTES_PER_LINE <= MAX_HASHED_FILE_BYTES)
+        .collect();
+    let token = config.github_token.clone().filter(|t| !t.is_empty());
+    let client = github_client(&config)?;
+    // blob sha → 内容；取不到的记为 None，不再重试
+    let mut blobs: HashMap<String, Option<String>> = HashMap::new();
+
+    let mut found = vec![];
+    for tombstone in read_tombstones()?.into_iter().filter(|t| t.resurrected_at.is_none()) {
+        let source = match cached_tombstone_source(&tombstone.id) {
+            Some(source) => source,
+            None => match fetch_tombstone_source(&config, &tombstone).await {
+                Ok(source) => source,
+                Err(e) => {
+                    eprintln!("⚠️ 跳过墓碑 {}: {}", tombstone.id, e);
+                    continue;
+                }
+            },
+        };
+        let corpse_lines = source.lines().count();
+        let same_language =
+            |a: &Asset| a.language.is_none() || tombstone.language.is_none() || a.language == tombstone.language;
+
+        // 远程资产的行数是按大小估算的，足够用来粗筛
+        for asset in remote
+            .iter()
+            .filter(|a| same_language(a) && comparable_sizes(corpse_lines, a.line_count))
+        {
+            let (Some(repo), Some(sha)) = (asset.repo.as_deref(), asset.blob_sha.as_deref()) else {
+                continue;
+            };
+            if blobs.contains_key(sha) {
+                continue;
+            }
+            let repo = repo_full_name(repo, &config.target_org);
+            let content = match fetch_blob(&client, token.as_deref(), &repo, sha).await {
+                Ok(bytes) => Some(String::from_utf8_lossy(&bytes).into_owned()),
+                Err(e) => {
+                    eprintln!("⚠️ 跳过远程文件 {}: {}", asset.location, e);
+                    None
+                }
+            };
+            blobs.insert(sha.to_string(), content);
+        }
+        let remote_live = remote
+            .iter()
+            .filter_map(|a| Some((a, blobs.get(a.blob_sha.as_deref()?)?.as_ref()?)));
+
+        let best = live
+            .iter()
+            .map(|(a, content)| (a, content))
+            .chain(remote_live)
+            .filter(|(a, _)| same_language(a))
+            .filter(|(_, content)| comparable_sizes(corpse_lines, content.lines().count()))
+            .map(|(a, content)| (a, content, similarity_score(config.similarity_algo, &source, content)))
+            .max_by(|a, b| a.2.total_cmp(&b.2));
+        let Some((asset, content, similarity)) = best else {
+            continue;
+        };
+        if similarity < ZOMBIE_MIN_SIMILARITY {
+            continue;
+        }
+
+        found.push(ZombieAlert {
+            id: format!("zombie-{:016x}", stable_hash(&format!("{}|{}", tombstone.id, asset.location))),
+            corpse_repo: tombstone.repo.clone().unwrap_or_default(),
+            corpse_path: repo_path(&tombstone.original_path),
+            zombie_repo: asset.repo.clone().unwrap_or_default(),
+            zombie_path: asset.location.clone(),
+            similarity,
+            resurrection_type: resurrection_type(similarity).to_string(),
+            confidence: zombie_confidence(similarity, &source, content),
+            detected_at: Utc::now().to_rfc3339(),
+            notified: false,
+        });
+    }
+
+    let added = ingest_alerts(found.clone())?;
+    println!("🧟 诈尸检测发现 {} 处，新增 {} 条提醒", found.len(), added);
+    Ok(found)
+}
+
+// ========== 数据体检 ==========
+
+const PATH_SAMPLE_SIZE: usize = 50;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+    Info,
+}
+
+// fix_command 为可以修复该问题的命令名，前端据此显示“修复”按钮
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Finding {
+    pub severity: Severity,
+    pub code: String,
+    pub message: String,
+    pub count: usize,
+    pub fix_command: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ValidationReport {
+    pub checked_at: String,
+    pub errors: usize,
+    pub warnings: usize,
+    pub findings: Vec<Finding>,
+}
+
+fn finding(severity: Severity, code: &str, message: String, count: usize, fix_command: Option<&str>) -> Finding {
+    Finding {
+        severity,
+        code: code.to_string(),
+        message,
+        count,
+        fix_command: fix_command.map(String::from),
+    }
+}
+
+// 一次性检查所有数据文件，只读不改。各项检查互相独立，前面失败不影响后面
+#[tauri::command]
+pub fn validate_dataset() -> ValidationReport {
+    let mut findings = vec![];
+
+    // 1. 配置与数据文件能否解析
+    let config_path = get_config_path();
+    if config_path.exists() {
+        let parsed = fs::read_to_string(&config_path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| serde_json::from_str::<Config>(&content).map_err(|e| e.to_string()));
+        if let Err(e) = parsed {
+            findings.push(finding(Severity::Error, "config_parse", format!("配置文件无法解析: {}", e), 1, Some("save_config")));
+        }
+    }
+    let mut values = HashMap::new();
+    for path in data_file_paths() {
+        match read_json_value(&path) {
+            Ok(value) => {
+                values.insert(path, value);
+            }
+            Err(e) => findings.push(finding(Severity::Error, "file_parse", e, 1, Some("import_bundle"))),
+        }
+    }
+    let value_of = |path: &Path| values.get(path).cloned().unwrap_or(serde_json::Value::Null);
+
+    // 2. 结构是否符合当前版本的数据格式
+    let registry = value_of(&get_tombstone_registry_path());
+    let tombstones: Vec<Tombstone> = if registry.is_null() {
+        vec![]
+    } else {
+        match serde_json::from_value(registry) {
+            Ok(tombstones) => tombstones,
+            Err(e) => {
+                findings.push(finding(Severity::Error, "registry_schema", format!("墓碑注册表格式不符: {}", e), 1, None));
+                vec![]
+            }
+        }
+    };
+    let assets = value_of(&get_asset_index_path());
+    if !assets.is_null() && !assets.is_array() {
+        findings.push(finding(Severity::Error, "assets_schema", String::from("资产索引不是数组"), 1, None));
+    }
+    let alerts = value_of(&get_zombie_alerts_path());
+    if !alerts.is_null() && !alerts["alerts"].is_array() {
+        findings.push(finding(Severity::Error, "alerts_schema", String::from("诈尸提醒缺少 alerts 数组"), 1, Some("clear_all_alerts")));
+    }
+
+    // 3. 孤儿提醒（注册表存在时才有意义）
+    if data_file_exists(&get_tombstone_registry_path()) {
+        let orphans = alerts["alerts"]
+            .as_array()
+            .map_or(0, |list| list.iter().filter(|a| !alert_has_tombstone(a, &tombstones)).count());
+        if orphans > 0 {
+            findings.push(finding(
+                Severity::Warning,
+                "orphan_alerts",
+                format!("{} 条提醒找不到对应的墓碑", orphans),
+                orphans,
+                Some("prune_orphan_alerts"),
+            ));
+        }
+    }
+
+    // 4. 重复数据
+    let mut seen_ids = HashSet::new();
+    let duplicate_ids = tombstones.iter().filter(|t| !seen_ids.insert(t.id.as_str())).count();
+    if duplicate_ids > 0 {
+        findings.push(finding(Severity::Error, "duplicate_tombstone_ids", format!("{} 个墓碑 id 重复", duplicate_ids), duplicate_ids, None));
+    }
+    let asset_list = assets.as_array().cloned().unwrap_or_default();
+    let duplicate_assets: usize = group_assets_by_location(&asset_list)
+        .values()
+        .filter(|indices| indices.len() > 1)
+        .map(|indices| indices.len() - 1)
+        .sum();
+    if duplicate_assets > 0 {
+        findings.push(finding(
+            Severity::Warning,
+            "duplicate_assets",
+            format!("{} 条资产的 location 重复", duplicate_assets),
+            duplicate_assets,
+            Some("dedupe_assets"),
+        ));
+    }
+
+    // 5. 无法解析的日期
+    let bad_dates = tombstones
+        .iter()
+        .filter(|t| {
+            parse_timestamp(&t.died_at).is_none()
+                || t.resurrected_at.as_deref().is_some_and(|at| parse_timestamp(at).is_none())
+        })
+        .count();
+    if bad_dates > 0 {
+        findings.push(finding(Severity::Warning, "unparseable_dates", format!("{} 个墓碑的日期无法解析", bad_dates), bad_dates, None));
+    }
+
+    // 6. 抽样检查存活资产的本地路径是否还在
+    let local_paths: Vec<&str> = asset_list
+        .iter()
+        .filter(|a| a["alive"].as_bool().unwrap_or(false))
+        .filter_map(|a| a["location"].as_str())
+        .filter(|location| Path::new(location).has_root())
+        .take(PATH_SAMPLE_SIZE)
+        .collect();
+    let missing = local_paths.iter().filter(|location| !Path::new(location).exists()).count();
+    if missing > 0 {
+        findings.push(finding(
+            Severity::Info,
+            "missing_paths",
+            format!("抽样的 {} 个存活资产中有 {} 个本地路径已不存在", local_paths.len(), missing),
+            missing,
+            Some("trigger_scan"),
+        ));
+    }
+
+    ValidationReport {
+        checked_at: Utc::now().to_rfc3339(),
+        errors: findings.iter().filter(|f| f.severity == Severity::Error).count(),
+        warnings: findings.iter().filter(|f| f.severity == Severity::Warning).count(),
+        findings,
+    }
+}
+
+// ========== 配置诊断 ==========
+
+// fix 为建议调用的设置命令，前端据此跳到对应的设置项
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ConfigIssue {
+    pub severity: Severity,
+    pub field: String,
+    pub message: String,
+    pub fix: Option<String>,
+}
+
+fn config_issue(severity: Severity, field: &str, message: String, fix: Option<&str>) -> ConfigIssue {
+    ConfigIssue {
+        severity,
+        field: field.to_string(),
+        message,
+        fix: fix.map(String::from),
+    }
+}
+
+fn diagnose_config_value(config: &Config) -> Vec<ConfigIssue> {
+    let mut issues = vec![];
+
+    if config.github_token.as_deref().is_some_and(|t| t.trim().is_empty()) {
+        issues.push(config_issue(
+            Severity::Warning,
+            "github_token",
+            String::from("令牌字段存在但为空，扫描会按未登录处理（配额更低、看不到私有仓库）"),
+            Some("update_github_token"),
+        ));
+    }
+
+    if config.target_org.trim().is_empty() {
+        issues.push(config_issue(
+            Severity::Error,
+            "target_org",
+            String::from("没有设置目标组织，定时扫描无事可做"),
+            Some("set_target_org"),
+        ));
+    }
+
+    if !(MIN_SCAN_INTERVAL_SECS..=MAX_SCAN_INTERVAL_SECS).contains(&config.scan_interval) {
+        let (value, unit) = humanize_interval(config.scan_interval);
+        issues.push(config_issue(
+            Severity::Warning,
+            "scan_interval",
+            format!(
+                "扫描间隔 {} {} 超出允许范围，实际会被限制在 {} 到 {} 秒之间",
+                value, unit, MIN_SCAN_INTERVAL_SECS, MAX_SCAN_INTERVAL_SECS
+            ),
+            Some("set_scan_interval"),
+        ));
+    }
+
+    if let Some(webhook) = config.report_webhook.as_deref().filter(|w| !w.trim().is_empty()) {
+        let problem = match reqwest::Url::parse(webhook.trim()) {
+            Ok(url) if matches!(url.scheme(), "http" | "https") => None,
+            Ok(url) => Some(format!("Webhook 只支持 http/https，当前为 {}", url.scheme())),
+            Err(e) => Some(format!("Webhook 地址无效: {}", e)),
+        };
+        if let Some(message) = problem {
+            issues.push(config_issue(Severity::Error, "report_webhook", message, Some("test_report_webhook")));
+        }
+    }
+
+    let cemetery = get_base_path().join(".cemetery");
+    if !cemetery.is_dir() {
+        issues.push(config_issue(
+            Severity::Warning,
+            "cemetery_path",
+            format!("墓地目录 {} 不存在，统计和埋葬都会是空的", cemetery.display()),
+            None,
+        ));
+    }
+    if let Some(templates) = config.epitaph_templates_path.as_deref().filter(|p| !p.trim().is_empty()) {
+        if !Path::new(templates).is_file() {
+            issues.push(config_issue(
+                Severity::Warning,
+                "epitaph_templates_path",
+                format!("墓志铭模板文件 {} 不存在，将使用内置模板", templates),
+                Some("save_config"),
+            ));
+        }
+    }
+
+    for glob in &config.ignore_globs {
+        let mut builder = ignore::gitignore::GitignoreBuilder::new(get_base_path());
+        let error = builder.add_line(None, glob).err().or_else(|| builder.build().err());
+        if let Some(e) = error {
+            issues.push(config_issue(
+                Severity::Error,
+                "ignore_globs",
+                format!("忽略规则 {} 无法解析: {}", glob, e),
+                Some("save_config"),
+            ));
+        }
+    }
+
+    for rule in &config.death_rules {
+        if !rule.has_conditions() {
+            issues.push(config_issue(
+                Severity::Warning,
+                "death_rules",
+                format!("死亡规则 {} 没有任何条件，不会生效", rule.name),
+                Some("save_config"),
+            ));
+        } else if let Err(e) = compile_death_rules(std::slice::from_ref(rule)) {
+            issues.push(config_issue(Severity::Error, "death_rules", e, Some("save_config")));
+        }
+    }
+
+    issues
+}
+
+// 只读的配置体检，给设置页的健康指示用；修复仍然通过各自的设置命令
+#[tauri::command]
+pub fn diagnose_config() -> Vec<ConfigIssue> {
+    match load_config() {
+        Ok(config) => diagnose_config_value(&config),
+        Err(e) => vec![config_issue(Severity::Error, "config", e, Some("save_config"))],
+    }
+}
+
+
+// ========== 外部扫描器 ==========
+
+const EXTERNAL_SCANNER_TIMEOUT_SECS: u64 = 300;
+const EXTERNAL_SCANNER_STDERR_LIMIT: usize = 2000;
+
+// external_scanner_cmd 约定：
+// - 命令按空白拆分（不经过 shell），资产索引文件的路径作为最后一个参数追加；stdin 为空
+// - 成功时退出码为 0，stdout 输出 JSON：ZombieAlert 数组，或 {"alerts": [ZombieAlert...]}
+//   （即 zombie-alerts.json 的格式），字段见 ZombieAlert
+// - 超过 EXTERNAL_SCANNER_TIMEOUT_SECS 秒会被终止；失败时 stderr 会出现在错误信息里
+async fn run_external_scanner(cmd: &str) -> Result<Vec<ZombieAlert>, String> {
+    let mut parts = cmd.split_whitespace();
+    let program = parts
+        .next()
+        .ok_or_else(|| String::from("external_scanner_cmd 为空"))?;
+
+    let mut command = tokio::process::Command::new(program);
+    command
+        .args(parts)
+        .arg(get_asset_index_path())
+        .stdin(std::process::Stdio::null())
+        .kill_on_drop(true);
+
+    let output = tokio::time::timeout(
+        std::time::Duration::from_secs(EXTERNAL_SCANNER_TIMEOUT_SECS),
+        command.output(),
+    )
+    .await
+    .map_err(|_| format!("外部扫描器超过 {} 秒未结束", EXTERNAL_SCANNER_TIMEOUT_SECS))?
+    .map_err(|e| format!("启动外部扫描器失败: {}", e))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let stderr: String = stderr.trim().chars().take(EXTERNAL_SCANNER_STDERR_LIMIT).collect();
+        return Err(format!("外部扫描器失败（{}）: {}", output.status, stderr));
+    }
+
+    let value: serde_json::Value = serde_json::from_slice(&output.stdout)
+        .map_err(|e| format!("外部扫描器输出不是有效 JSON: {}", e))?;
+    let alerts = match value {
+        serde_json::Value::Object(mut object) => object
+            .remove("alerts")
+            .unwrap_or(serde_json::Value::Array(vec![])),
+        other => other,
+    };
+    serde_json::from_value(alerts).map_err(|e| format!("外部扫描器输出的提醒格式无效: {}", e))
+}
+
+// ========== 实用命令 ==========
+
+// 前端日志。level 为 trace / debug / info / warn / error（不区分大小写），缺省或无法识别时按 info；
+// context 为任意 JSON，原样附在日志里
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct LogEntry {
+    #[serde(default)]
+    pub level: Option<String>,
+    pub message: String,
+    #[serde(default)]
+    pub context: Option<serde_json::Value>,
+}
+
+// 单次 log_batch 最多处理的条数，多出的只记一条汇总，防止前端刷屏
+const LOG_BATCH_LIMIT: usize = 200;
+
+fn log_level(level: Option<&str>) -> tracing::Level {
+    match level.map(|l| l.trim().to_lowercase()).as_deref() {
+        Some("trace") => tracing::Level::TRACE,
+        Some("debug") => tracing::Level::DEBUG,
+        Some("warn") | Some("warning") => tracing::Level::WARN,
+        Some("error") => tracing::Level::ERROR,
+        _ => tracing::Level::INFO,
+    }
+}
+
+fn emit_log_entry(entry: &LogEntry) {
+    let context = entry
+        .context
+        .as_ref()
+        .map(|c| c.to_string())
+        .unwrap_or_default();
+    let message = entry.message.as_str();
+    match log_level(entry.level.as_deref()) {
+        tracing::Level::TRACE => tracing::trace!(target: "frontend", context = %context, "{}", message),
+        tracing::Level::DEBUG => tracing::debug!(target: "frontend", context = %context, "{}", message),
+        tracing::Level::WARN => tracing::warn!(target: "frontend", context = %context, "{}", message),
+        tracing::Level::ERROR => tracing::error!(target: "frontend", context = %context, "{}", message),
+        _ => tracing::info!(target: "frontend", context = %context, "{}", message),
+    }
+}
+
+#[tauri::command]
+pub fn log_message(entry: LogEntry) {
+    emit_log_entry(&entry);
+}
+
+// 一次 IPC 写入多条日志；超过 LOG_BATCH_LIMIT 的部分丢弃并记录丢弃数
+#[tauri::command]
+pub fn log_batch(entries: Vec<LogEntry>) {
+    for entry in entries.iter().take(LOG_BATCH_LIMIT) {
+        emit_log_entry(entry);
+    }
+    if entries.len() > LOG_BATCH_LIMIT {
+        tracing::warn!(
+            target: "frontend",
+            "前端日志过多，丢弃 {} 条",
+            entries.len() - LOG_BATCH_LIMIT
+        );
+    }
+}
+
+#[tauri::command]
+pub fn get_version() -> String {
+    env!("CARGO_PKG_VERSION").to_string()
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BuildInfo {
+    pub version: String,
+    pub git_hash: String,
+    pub target: String,
+    pub profile: String,
+    pub tauri_version: String,
+    pub os: String,
+    pub arch: String,
+}
+
+// 编译期信息来自 build.rs，os/arch 为运行时实际值，附在问题反馈里
+#[tauri::command]
+pub fn get_build_info() -> BuildInfo {
+    BuildInfo {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        git_hash: option_env!("CODE_CORPSES_GIT_HASH").unwrap_or("unknown").to_string(),
+        target: option_env!("CODE_CORPSES_TARGET").unwrap_or("unknown").to_string(),
+        profile: option_env!("CODE_CORPSES_PROFILE").unwrap_or("unknown").to_string(),
+        tauri_version: tauri::VERSION.to_string(),
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+    }
+}
+
+#[tauri::command]
+pub fn set_autostart(enabled: bool) -> Result<(), String> {
+    #[cfg(target_os = "macos")]
+    {
+        use std::process::Command;
+        let app_path = std::env::current_executable()
+            .map_err(|e| e.to_string())?
+            .to_string_lossy()
+            .into_owned();
+        
+        if enabled {
+            let script = format!(
+                r#"tell application "System Events" to make login item at end with properties {{path: "{}", name: "Code Corpses", hidden: false}}"#,
+                app_path
+            );
+            Command::new("osascript")
+                .args(&["-e", &script])
+                .output()
+                .map_err(|e| e.to_string())?;
+        } else {
+            let script = r#"tell application "System Events" to delete login item "Code Corpses""#;
+            Command::new("osascript")
+                .args(&["-e", script])
+                .output()
+                .ok();
+        }
+    }
+    
+    #[cfg(target_os = "windows")]
+    {
+        use winreg::RegKey;
+        use winreg::enums::*;
+        
+        let key = RegKey::predef(HKEY_CURRENT_USER)
+            .open_subkey("Software\\Microsoft\\Windows\\CurrentVersion\\Run")
+            .map_err(|e| e.to_string())?;
+        
+        let exe_path = std::env::current_executable()
+            .map_err(|e| e.to_string())?
+            .to_string_lossy()
+            .into_owned();
+        
+        if enabled {
+            key.set_value("CodeCorpses", &exe_path)
+                .map_err(|e| e.to_string())?;
+        } else {
+            key.delete_value("CodeCorpses").ok();
+        }
+    }
+    
+    Ok(())
+}
+
+const REPORT_RECENT_LIMIT: i32 = 10;
+const DISCORD_EMBED_COLOR: u32 = 0x6c5ce7;
+
+fn report_summary(stats: &Stats) -> String {
+    tr_args(
+        "report.summary",
+        &[
+            ("total", stats.total_assets.to_string()),
+            ("alive", stats.alive_assets.to_string()),
+            ("dead", stats.dead_assets.to_string()),
+            ("tombstones", stats.total_tombstones.to_string()),
+            ("resurrected", stats.resurrected.to_string()),
+        ],
+    )
+}
+
+fn report_recent_lines(corpses: &[Tombstone]) -> Vec<String> {
+    corpses
+        .iter()
+        .map(|t| format!("• {} — {}", t.name, t.cause_of_death))
+        .collect()
+}
+
+// 报告的各种载荷格式：text 为纯文本，slack 为 Block Kit JSON，discord 为 embed JSON
+fn render_report(target: &str, stats: &Stats, corpses: &[Tombstone]) -> Result<String, String> {
+    let title = tr("report.title");
+    let summary = report_summary(stats);
+    let recent = report_recent_lines(corpses);
+
+    let payload = match target {
+        "text" => {
+            let mut text = format!("{}\n\n{}", title, summary);
+            if !recent.is_empty() {
+                text.push_str(&format!("\n\n{}:\n{}", tr("report.recent"), recent.join("\n")));
+            }
+            return Ok(text);
+        }
+        "slack" => {
+            let mut blocks = vec![
+                serde_json::json!({
+                    "type": "header",
+                    "text": { "type": "plain_text", "text": title, "emoji": true }
+                }),
+                serde_json::json!({
+                    "type": "section",
+                    "text": { "type": "mrkdwn", "text": summary }
+                }),
+            ];
+            if !recent.is_empty() {
+                blocks.push(serde_json::json!({ "type": "divider" }));
+                blocks.push(serde_json::json!({
+                    "type": "section",
+                    "text": {
+                        "type": "mrkdwn",
+                        "text": format!("*{}*\n{}", tr("report.recent"), recent.join("\n"))
+                    }
+                }));
+            }
+            serde_json::json!({ "text": summary, "blocks": blocks })
+        }
+        "discord" => {
+            let mut fields = vec![];
+            if !recent.is_empty() {
+                fields.push(serde_json::json!({
+                    "name": tr("report.recent"),
+                    "value": recent.join("\n"),
+                    "inline": false
+                }));
+            }
+            serde_json::json!({
+                "embeds": [{
+                    "title": title,
+                    "description": summary,
+                    "color": DISCORD_EMBED_COLOR,
+                    "fields": fields,
+                    "footer": { "text": stats.last_scan }
+                }]
+            })
+        }
+        other => return Err(format!("未知的报告目标: {}（可选 text / slack / discord）", other)),
+    };
+    serde_json::to_string_pretty(&payload).map_err(|e| format!("序列化报告失败: {}", e))
+}
+
+// 设置页的实时预览：渲染当前统计对应的载荷，不发送任何请求
+#[tauri::command]
+pub fn preview_report(target: String) -> Result<String, String> {
+    let stats = get_stats();
+    let corpses = get_recent_corpses(REPORT_RECENT_LIMIT, None);
+    render_report(&target.to_lowercase(), &stats, &corpses)
+}
+
+// 配置了 report_webhook 时把报告 POST 过去，返回渲染好的载荷
+#[tauri::command]
+pub async fn send_report() -> Result<String, String> {
+    let stats = get_stats();
+    let corpses = get_recent_corpses(REPORT_RECENT_LIMIT, None);
+    let body = render_report("text", &stats, &corpses)?;
+
+    let config = resolve_config()?;
+    if let Some(url) = config.report_webhook.as_deref().map(str::trim).filter(|u| !u.is_empty()) {
+        let status = post_webhook(url, body.clone(), config.webhook_secret.as_deref()).await?;
+        if !(200..300).contains(&status) {
+            return Err(format!("Webhook 返回 HTTP {}", status));
+        }
+    }
+    Ok(body)
+}
+
+const WEBHOOK_TIMEOUT_SECS: u64 = 10;
+const WEBHOOK_MAX_REDIRECTS: usize = 5;
+const WEBHOOK_SIGNATURE_HEADER: &str = "X-Corpses-Signature";
+
+// 与 GitHub 的 X-Hub-Signature-256 同一方案：
+// 以 webhook_secret 的 UTF-8 字节为密钥，对请求体原始字节（即实际发送的 JSON 文本，不做任何规范化）
+// 计算 HMAC-SHA256，头部值为 "sha256=" 加 64 位小写十六进制摘要。
+// 接收方应对收到的原始请求体重算并做常量时间比较
+fn sign_webhook_payload(secret: &str, body: &[u8]) -> String {
+    use hmac::{Hmac, Mac};
+    let mut mac = Hmac::<sha2::Sha256>::new_from_slice(secret.as_bytes())
+        .expect("HMAC 接受任意长度的密钥");
+    mac.update(body);
+    let digest: String = mac
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    format!("sha256={}", digest)
+}
+
+fn webhook_client() -> Result<reqwest::Client, String> {
+    reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::limited(WEBHOOK_MAX_REDIRECTS))
+        .timeout(std::time::Duration::from_secs(WEBHOOK_TIMEOUT_SECS))
+        .build()
+        .map_err(|e| format!("创建 HTTP 客户端失败: {}", e))
+}
+
+// 发送 JSON 请求体，有密钥时附带签名头；返回 HTTP 状态码
+async fn post_webhook(url: &str, body: String, secret: Option<&str>) -> Result<u16, String> {
+    let parsed = reqwest::Url::parse(url.trim())
+        .map_err(|e| format!("Webhook 地址无效: {}", e))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(format!("Webhook 只支持 http/https，收到: {}", parsed.scheme()));
+    }
+
+    let mut request = webhook_client()?
+        .post(parsed)
+        .header(reqwest::header::CONTENT_TYPE, "application/json");
+    if let Some(secret) = secret.filter(|s| !s.is_empty()) {
+        request = request.header(WEBHOOK_SIGNATURE_HEADER, sign_webhook_payload(secret, body.as_bytes()));
+    }
+
+    let response = request.body(body).send().await.map_err(|e| {
+        if e.is_timeout() {
+            format!("Webhook 请求超时 ({} 秒)", WEBHOOK_TIMEOUT_SECS)
+        } else if e.is_redirect() {
+            format!("Webhook 重定向超过 {} 次", WEBHOOK_MAX_REDIRECTS)
+        } else {
+            format!("Webhook 请求失败: {}", e)
+        }
+    })?;
+
+    Ok(response.status().as_u16())
+}
+
+// 保存前先试发一条消息，返回 HTTP 状态码给设置页确认；保存仍然走 save_config。
+// 已保存 webhook_secret 时同样签名，方便接收方一并验证校验逻辑
+#[tauri::command]
+pub async fn test_report_webhook(url: String) -> Result<u16, String> {
+    // 同时带上 Slack (text) 和 Discord (content) 识别的字段
+    let text = tr("webhook.test");
+    let payload = serde_json::json!({
+        "text": text,
+        "content": text,
+    });
+    let body = serde_json::to_string(&payload).map_err(|e| format!("序列化测试消息失败: {}", e))?;
+
+    let secret = resolve_config().ok().and_then(|c| c.webhook_secret);
+    post_webhook(&url, body, secret.as_deref()).await
+}
+
+// ========== 后台任务 ==========
+
+const MIN_SCAN_INTERVAL_SECS: u64 = 60;
+const MAX_SCAN_INTERVAL_SECS: u64 = 30 * 86400;
+
+const INTERVAL_UNITS: [(&str, u64); 3] = [("days", 86400), ("hours", 3600), ("minutes", 60)];
+
+fn interval_unit_secs(unit: &str) -> Result<u64, String> {
+    INTERVAL_UNITS
+        .iter()
+        .find(|(name, _)| *name == unit)
+        .map(|(_, secs)| *secs)
+        .ok_or_else(|| format!("未知的时间单位: {}（可选 minutes / hours / days）", unit))
+}
+
+// 取能整除的最大单位，都除不尽时退回秒
+fn humanize_interval(secs: u64) -> (u64, String) {
+    INTERVAL_UNITS
+        .iter()
+        .find(|(_, unit_secs)| secs > 0 && secs % unit_secs == 0)
+        .map(|(name, unit_secs)| (secs / unit_secs, name.to_string()))
+        .unwrap_or((secs, String::from("seconds")))
+}
+
+// 按人类单位设置扫描间隔，换算后限制在 [MIN, MAX] 内并保存，返回实际生效的秒数
+#[tauri::command]
+pub fn set_scan_interval(value: u64, unit: String) -> Result<u64, String> {
+    let secs = value
+        .checked_mul(interval_unit_secs(&unit)?)
+        .unwrap_or(u64::MAX)
+        .clamp(MIN_SCAN_INTERVAL_SECS, MAX_SCAN_INTERVAL_SECS);
+
+    let mut config = load_config()?;
+    config.scan_interval = secs;
+    save_config(&config)?;
+    Ok(secs)
+}
+
+#[tauri::command]
+pub fn get_scan_interval_human() -> Result<(u64, String), String> {
+    let config = resolve_config()?;
+    Ok(humanize_interval(config.scan_interval))
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct BackgroundError {
+    pub source: String,
+    pub message: String,
+    pub occurred_at: String,
+}
+
+#[derive(Default)]
+pub struct AppState {
+    pub last_error: Mutex<Option<BackgroundError>>,
+    pub notifications: Mutex<NotificationState>,
+    pub registry_watcher: Mutex<Option<notify::RecommendedWatcher>>,
+    pub one_shot: Mutex<Option<tauri::async_runtime::JoinHandle<()>>>,
+    pub scheduler_paused: std::sync::atomic::AtomicBool,
+}
+
+// 后台任务没有调用方可以接收 Err，失败都记录到这里并通知前端
+fn record_background_error(app: &tauri::AppHandle, source: &str, message: String) {
+    eprintln!("⚠️ 后台任务 {} 失败: {}", source, message);
+
+    let error = BackgroundError {
+        source: source.to_string(),
+        message,
+        occurred_at: Utc::now().to_rfc3339(),
+    };
+    if let Ok(mut last) = app.state::<AppState>().last_error.lock() {
+        *last = Some(error.clone());
+    }
+    app.emit("background-error", &error).ok();
+}
+
+#[tauri::command]
+pub fn get_last_background_error(state: tauri::State<'_, AppState>) -> Option<BackgroundError> {
+    state.last_error.lock().ok().and_then(|last| last.clone())
+}
+
+// 定时扫描的组织及其间隔：target_org 加上 orgs 中的组织（同名只取一次），
+// 没有单独设置间隔的使用全局 scan_interval
+fn scheduled_orgs(config: &Config) -> Vec<(String, u64)> {
+    let clamp = |secs: u64| secs.clamp(MIN_SCAN_INTERVAL_SECS, MAX_SCAN_INTERVAL_SECS);
+    let mut scheduled: Vec<(String, u64)> = vec![];
+    let target = OrgConfig {
+        org: config.target_org.clone(),
+        interval_secs: None,
+    };
+
+    for entry in config.orgs.iter().chain(std::iter::once(&target)) {
+        if entry.org.trim().is_empty() || scheduled.iter().any(|(org, _)| org.eq_ignore_ascii_case(&entry.org)) {
+            continue;
+        }
+        scheduled.push((entry.org.clone(), clamp(entry.interval_secs.unwrap_or(config.scan_interval))));
+    }
+    scheduled
+}
+
+// 最早到期的组织；时间相同时按组织名排序，保证结果确定
+fn next_due_org(next_runs: &HashMap<String, DateTime<Utc>>) -> Option<(String, DateTime<Utc>)> {
+    next_runs
+        .iter()
+        .min_by(|a, b| a.1.cmp(b.1).then_with(|| a.0.cmp(b.0)))
+        .map(|(org, at)| (org.clone(), *at))
+}
+
+// 每个组织各自记录下次运行时间；每轮重新读取配置（最多等待 MIN_SCAN_INTERVAL_SECS），
+// 新增的组织从现在起计时，被移除的组织不再调度。扫描后为新出现的未读提醒发通知
+fn spawn_scan_scheduler(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut next_runs: HashMap<String, DateTime<Utc>> = HashMap::new();
+        loop {
+            let config = resolve_config().unwrap_or_default();
+            let orgs = scheduled_orgs(&config);
+            let now = Utc::now();
+            next_runs.retain(|org, _| orgs.iter().any(|(o, _)| o == org));
+            for (org, interval) in &orgs {
+                next_runs
+                    .entry(org.clone())
+                    .or_insert(now + Duration::seconds(*interval as i64));
+            }
+
+            if app.state::<AppState>().scheduler_paused.load(std::sync::atomic::Ordering::Relaxed) {
+                tokio::time::sleep(std::time::Duration::from_secs(MIN_SCAN_INTERVAL_SECS)).await;
+                continue;
+            }
+
+            let Some((org, due)) = next_due_org(&next_runs) else {
+                tokio::time::sleep(std::time::Duration::from_secs(MIN_SCAN_INTERVAL_SECS)).await;
+                continue;
+            };
+            if due > now {
+                let wait = (due - now)
+                    .to_std()
+                    .unwrap_or_default()
+                    .min(std::time::Duration::from_secs(MIN_SCAN_INTERVAL_SECS));
+                tokio::time::sleep(wait).await;
+                continue;
+            }
+
+            let seen = unread_alert_ids();
+            match scan_org(&config, &org, false).await {
+                Ok(_) => notify_new_alerts(&app, &seen),
+                Err(e) => record_background_error(&app, "scheduled-scan", format!("{}: {}", org, e)),
+            }
+            if let Err(e) = apply_retention() {
+                record_background_error(&app, "retention", e);
+            }
+
+            let interval = orgs
+                .iter()
+                .find(|(o, _)| *o == org)
+                .map_or(config.scan_interval, |(_, interval)| *interval);
+            next_runs.insert(org, Utc::now() + Duration::seconds(interval as i64));
+        }
+    });
+}
+
+// 设置某个组织的扫描间隔，组织不在 orgs 中时自动加入；返回限制范围后的秒数
+#[tauri::command]
+pub fn set_org_interval(org: String, secs: u64) -> Result<u64, String> {
+    let org = org.trim().to_string();
+    if org.is_empty() {
+        return Err(String::from("组织名不能为空"));
+    }
+    let secs = secs.clamp(MIN_SCAN_INTERVAL_SECS, MAX_SCAN_INTERVAL_SECS);
+
+    let mut config = load_config()?;
+    match config.orgs.iter_mut().find(|o| o.org.eq_ignore_ascii_case(&org)) {
+        Some(entry) => entry.interval_secs = Some(secs),
+        None => config.orgs.push(OrgConfig {
+            org,
+            interval_secs: Some(secs),
+        }),
+    }
+    save_config(&config)?;
+    Ok(secs)
+}
+
+// ========== 单次定时扫描 ==========
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct OneShotScan {
+    at: String,
+}
+
+fn get_one_shot_path() -> PathBuf {
+    app_data_dir().0.join("one-shot-scan.json")
+}
+
+fn read_one_shot() -> Result<Option<DateTime<Utc>>, String> {
+    let path = get_one_shot_path();
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("读取单次扫描计划失败: {}", e))?;
+    let plan: OneShotScan = serde_json::from_str(&content)
+        .map_err(|e| format!("解析单次扫描计划失败: {}", e))?;
+    DateTime::parse_from_rfc3339(&plan.at)
+        .map(|at| Some(at.with_timezone(&Utc)))
+        .map_err(|e| format!("单次扫描时间无效: {}", e))
+}
+
+fn clear_one_shot_file() {
+    let path = get_one_shot_path();
+    if path.exists() {
+        if let Err(e) = fs::remove_file(&path) {
+            eprintln!("⚠️ 删除单次扫描计划失败: {}", e);
+        }
+    }
+}
+
+// 替换掉已有的单次计划（同一时间只保留一个），到点后扫描 target_org 一次并清除计划
+fn arm_one_shot(app: &tauri::AppHandle, at: DateTime<Utc>) {
+    let handle = app.clone();
+    let task = tauri::async_runtime::spawn(async move {
+        let wait = (at - Utc::now()).to_std().unwrap_or_default();
+        tokio::time::sleep(wait).await;
+
+        clear_one_shot_file();
+        let config = resolve_config().unwrap_or_default();
+        let seen = unread_alert_ids();
+        match scan_org(&config, &config.target_org, false).await {
+            Ok(_) => notify_new_alerts(&handle, &seen),
+            Err(e) => record_background_error(&handle, "one-shot-scan", e),
+        }
+        handle.state::<AppState>().one_shot.lock().unwrap_or_else(|e| e.into_inner()).take();
+    });
+
+    let state = app.state::<AppState>();
+    if let Some(previous) = state.one_shot.lock().unwrap_or_else(|e| e.into_inner()).replace(task) {
+        previous.abort();
+    }
+}
+
+// 启动时恢复上次保存的计划；时间已过的直接丢弃，不补跑
+fn rearm_one_shot(app: &tauri::AppHandle) {
+    match read_one_shot() {
+        Ok(Some(at)) if at > Utc::now() => {
+            println!("⏰ 恢复单次扫描计划: {}", format_ts(at));
+            arm_one_shot(app, at);
+        }
+        Ok(Some(_)) => clear_one_shot_file(),
+        Ok(None) => {}
+        Err(e) => {
+            eprintln!("⚠️ {}", e);
+            clear_one_shot_file();
+        }
+    }
+}
+
+// 在指定时间（RFC3339）扫描一次，与定时扫描互不影响；计划写入数据目录，重启后继续有效
+#[tauri::command]
+pub fn schedule_one_shot_scan(app: tauri::AppHandle, at: String) -> Result<(), String> {
+    let at = DateTime::parse_from_rfc3339(at.trim())
+        .map_err(|e| format!("时间格式无效（需要 RFC3339）: {}", e))?
+        .with_timezone(&Utc);
+    if at <= Utc::now() {
+        return Err(format!("扫描时间 {} 已经过去", format_ts(at)));
+    }
+
+    let content = serde_json::to_string_pretty(&OneShotScan { at: at.to_rfc3339() })
+        .map_err(|e| format!("序列化单次扫描计划失败: {}", e))?;
+    write_bytes_atomic(&get_one_shot_path(), content.as_bytes())?;
+    arm_one_shot(&app, at);
+    Ok(())
+}
+
+// 取消尚未执行的单次扫描；返回是否真的取消了计划
+#[tauri::command]
+pub fn cancel_one_shot(state: tauri::State<'_, AppState>) -> bool {
+    let task = state.one_shot.lock().unwrap_or_else(|e| e.into_inner()).take();
+    let had_plan = task.is_some() || get_one_shot_path().exists();
+    if let Some(task) = task {
+        task.abort();
+    }
+    clear_one_shot_file();
+    had_plan
+}
+
+// ========== 托盘角标 ==========
+
+// 与 tauri.conf.json 中 trayIcon.id 一致
+const TRAY_ID: &str = "main";
+const TRAY_BADGE_COLOR: [u8; 4] = [0xE5, 0x39, 0x35, 0xFF];
+
+// 直接读文件计数，不经过 get_zombie_alerts：后者可能清理孤儿提醒并加锁，
+// 而这里会在提醒文件的写入过程中被调用
+fn count_unread_alerts() -> usize {
+    let path = get_zombie_alerts_path();
+    if !data_file_exists(&path) {
+        return 0;
+    }
+    read_data_file(&path)
+        .ok()
+        .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+        .and_then(|data| {
+            data["alerts"]
+                .as_array()
+                .map(|alerts| alerts.iter().filter(|a| !a["notified"].as_bool().unwrap_or(false)).count())
+        })
+        .unwrap_or(0)
+}
+
+// 在图标右上角画一个红点，用于不支持角标文字的平台
+fn icon_with_badge_dot(icon: &tauri::image::Image<'_>) -> tauri::image::Image<'static> {
+    let (width, height) = (icon.width(), icon.height());
+    let mut rgba = icon.rgba().to_vec();
+    let radius = width.min(height) as f64 * 0.22;
+    let (cx, cy) = (width as f64 - radius - 1.0, radius + 1.0);
+    for y in 0..height {
+        for x in 0..width {
+            let (dx, dy) = (x as f64 + 0.5 - cx, y as f64 + 0.5 - cy);
+            if dx * dx + dy * dy <= radius * radius {
+                let i = ((y * width + x) * 4) as usize;
+                rgba[i..i + 4].copy_from_slice(&TRAY_BADGE_COLOR);
+            }
+        }
+    }
+    tauri::image::Image::new_owned(rgba, width, height)
+}
+
+// 托盘上显示未读提醒数，归零时清除。macOS 在图标旁显示数字，其他平台换成带红点的图标。
+// 提醒文件每次写入后都会调用这里，也可以由前端手动刷新；返回当前未读数
+#[tauri::command]
+pub fn refresh_tray_badge(app: tauri::AppHandle) -> Result<usize, String> {
+    let unread = count_unread_alerts();
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return Ok(unread);
+    };
+
+    let tooltip = if unread > 0 {
+        tr_args("tray.unread", &[("count", unread.to_string())])
+    } else {
+        String::from("🪦 Code Corpses")
+    };
+    tray.set_tooltip(Some(tooltip))
+        .map_err(|e| format!("设置托盘提示失败: {}", e))?;
+
+    if cfg!(target_os = "macos") {
+        tray.set_title(if unread > 0 { Some(unread.to_string()) } else { None })
+            .map_err(|e| format!("设置托盘角标失败: {}", e))?;
+    } else if let Some(icon) = app.default_window_icon() {
+        let icon = if unread > 0 {
+            icon_with_badge_dot(icon)
+        } else {
+            icon.clone().to_owned()
+        };
+        tray.set_icon(Some(icon))
+            .map_err(|e| format!("设置托盘图标失败: {}", e))?;
+    }
+    Ok(unread)
+}
+
+// ========== 托盘菜单 ==========
+
+const TRAY_RECENT_CORPSES_LIMIT: usize = 5;
+const TRAY_CORPSE_ID_PREFIX: &str = "corpse:";
+
+// 托盘菜单项：id 为 scan_now / open_dashboard / pause_scheduler / recent_corpses / separator / quit，
+// 按列表顺序显示，enabled = false 的不显示
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct TrayItem {
+    pub id: String,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_tray_menu_items() -> Vec<TrayItem> {
+    ["scan_now", "open_dashboard", "recent_corpses", "pause_scheduler", "separator", "quit"]
+        .iter()
+        .map(|id| TrayItem {
+            id: id.to_string(),
+            enabled: true,
+        })
+        .collect()
+}
+
+fn show_main_window(app: &tauri::AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+}
+
+fn build_tray_menu(app: &tauri::AppHandle, items: &[TrayItem]) -> tauri::Result<tauri::menu::Menu<tauri::Wry>> {
+    use tauri::menu::{Menu, MenuItem, PredefinedMenuItem, Submenu};
+
+    let menu = Menu::new(app)?;
+    let mut has_quit = false;
+    for item in items.iter().filter(|i| i.enabled) {
+        match item.id.as_str() {
+            "scan_now" | "open_dashboard" => {
+                let label = tr(&format!("tray.{}", item.id));
+                menu.append(&MenuItem::with_id(app, &item.id, label, true, None::<&str>)?)?;
+            }
+            "pause_scheduler" => {
+                let paused = app.state::<AppState>().scheduler_paused.load(std::sync::atomic::Ordering::Relaxed);
+                let label = tr(if paused { "tray.resume_scheduler" } else { "tray.pause_scheduler" });
+                menu.append(&MenuItem::with_id(app, &item.id, label, true, None::<&str>)?)?;
+            }
+            "recent_corpses" => {
+                let submenu = Submenu::with_id(app, &item.id, tr("tray.recent_corpses"), true)?;
+                let mut recent = load_tombstones_or_log();
+                recent.sort_by(|a, b| parse_timestamp(&b.died_at).cmp(&parse_timestamp(&a.died_at)));
+                if recent.is_empty() {
+                    submenu.append(&MenuItem::new(app, tr("tray.no_corpses"), false, None::<&str>)?)?;
+                }
+                for t in recent.iter().take(TRAY_RECENT_CORPSES_LIMIT) {
+                    let id = format!("{}{}", TRAY_CORPSE_ID_PREFIX, t.id);
+                    submenu.append(&MenuItem::with_id(app, id, format!("🪦 {}", t.name), true, None::<&str>)?)?;
+                }
+                menu.append(&submenu)?;
+            }
+            "separator" => menu.append(&PredefinedMenuItem::separator(app)?)?,
+            "quit" => {
+                has_quit = true;
+                menu.append(&MenuItem::with_id(app, "quit", tr("tray.quit"), true, None::<&str>)?)?;
+            }
+            other => eprintln!("⚠️ 忽略未知的托盘菜单项: {}", other),
+        }
+    }
+    // 配置里删掉了退出项也保留一个，避免菜单栏应用没法退出
+    if !has_quit {
+        menu.append(&PredefinedMenuItem::separator(app)?)?;
+        menu.append(&MenuItem::with_id(app, "quit", tr("tray.quit"), true, None::<&str>)?)?;
+    }
+    Ok(menu)
+}
+
+// 按 tray_menu_items 重建托盘菜单；保存配置时如果菜单配置变了会自动调用
+#[tauri::command]
+pub fn refresh_tray_menu(app: tauri::AppHandle) -> Result<(), String> {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return Ok(());
+    };
+    let items = resolve_config().map(|c| c.tray_menu_items).unwrap_or_else(|_| default_tray_menu_items());
+    let menu = build_tray_menu(&app, &items).map_err(|e| format!("构建托盘菜单失败: {}", e))?;
+    tray.set_menu(Some(menu)).map_err(|e| format!("设置托盘菜单失败: {}", e))
+}
+
+fn handle_tray_menu_event(app: &tauri::AppHandle, id: &str) {
+    match id {
+        "scan_now" => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                let seen = unread_alert_ids();
+                match trigger_scan().await {
+                    Ok(_) => notify_new_alerts(&app, &seen),
+                    Err(e) => record_background_error(&app, "tray-scan", e),
+                }
+                if let Err(e) = refresh_tray_menu(app.clone()) {
+                    eprintln!("⚠️ 重建托盘菜单失败: {}", e);
+                }
+            });
+        }
+        "open_dashboard" => show_main_window(app),
+        "pause_scheduler" => {
+            let paused = &app.state::<AppState>().scheduler_paused;
+            let now_paused = !paused.load(std::sync::atomic::Ordering::Relaxed);
+            paused.store(now_paused, std::sync::atomic::Ordering::Relaxed);
+            println!("{}", if now_paused { "⏸️ 定时扫描已暂停" } else { "▶️ 定时扫描已恢复" });
+            if let Err(e) = refresh_tray_menu(app.clone()) {
+                eprintln!("⚠️ 重建托盘菜单失败: {}", e);
+            }
+        }
+        "quit" => app.exit(0),
+        other => {
+            if let Some(tombstone_id) = other.strip_prefix(TRAY_CORPSE_ID_PREFIX) {
+                show_main_window(app);
+                if let Err(e) = app.emit("open-tombstone", tombstone_id) {
+                    eprintln!("⚠️ 推送 open-tombstone 失败: {}", e);
+                }
+            }
+        }
+    }
+}
+
+// 同时运行两个实例会争抢数据文件。插件用系统级机制（而不是锁文件）判断实例，
+// 进程崩溃后不会留下陈旧的锁
+fn focus_primary_instance(app: &tauri::AppHandle, args: Vec<String>, cwd: String) {
+    println!("🪦 已有实例在运行，转交启动参数: {:?}", args);
+    show_main_window(app);
+    if let Err(e) = app.emit("second-instance-launched", serde_json::json!({ "args": args, "cwd": cwd })) {
+        eprintln!("⚠️ 推送 second-instance-launched 失败: {}", e);
+    }
+}
+
+// ========== 注册表监听 ==========
+
+const REGISTRY_WATCH_DEBOUNCE_MS: u64 = 500;
+// 本进程写注册表后这段时间内的文件事件视为自己触发的，不再推送
+const OWN_WRITE_GRACE_MS: u64 = 2000;
+
+static LAST_OWN_REGISTRY_WRITE: Mutex<Option<std::time::Instant>> = Mutex::new(None);
+
+fn mark_own_registry_write() {
+    *LAST_OWN_REGISTRY_WRITE.lock().unwrap_or_else(|e| e.into_inner()) = Some(std::time::Instant::now());
+}
+
+fn is_recent_own_registry_write() -> bool {
+    LAST_OWN_REGISTRY_WRITE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .is_some_and(|at| at.elapsed() < std::time::Duration::from_millis(OWN_WRITE_GRACE_MS))
+}
+
+fn is_registry_event(event: &notify::Event) -> bool {
+    let registry = get_tombstone_registry_path();
+    let gz = gz_path(&registry);
+    let names = [registry.file_name(), gz.file_name()];
+    event
+        .paths
+        .iter()
+        .any(|p| p.file_name().is_some() && names.contains(&p.file_name()))
+}
+
+// 外部工具直接改写注册表时推送 corpses-updated（带最新墓碑数）。
+// 监听 .cemetery 目录而不是文件本身，原子写入（rename）后也不会丢失监听。
+// watcher 存在 AppState 中，退出时释放，通道随之关闭，后台线程自然结束
+fn start_registry_watcher(app: &tauri::AppHandle) -> Result<(), String> {
+    use notify::Watcher;
+
+    let registry = get_tombstone_registry_path();
+    let dir = registry
+        .parent()
+        .map(Path::to_path_buf)
+        .ok_or_else(|| String::from("注册表路径没有父目录"))?;
+    fs::create_dir_all(&dir).map_err(|e| format!("创建目录失败: {}", e))?;
+
+    let (tx, rx) = std::sync::mpsc::channel::<notify::Result<notify::Event>>();
+    let mut watcher = notify::recommended_watcher(tx)
+        .map_err(|e| format!("创建文件监听失败: {}", e))?;
+    watcher
+        .watch(&dir, notify::RecursiveMode::NonRecursive)
+        .map_err(|e| format!("监听 {} 失败: {}", dir.display(), e))?;
+
+    let app_handle = app.clone();
+    std::thread::spawn(move || {
+        let debounce = std::time::Duration::from_millis(REGISTRY_WATCH_DEBOUNCE_MS);
+        while let Ok(event) = rx.recv() {
+            if !event.as_ref().is_ok_and(is_registry_event) {
+                continue;
+            }
+            // 连续写入只推送一次：等到 debounce 时间内没有新事件
+            loop {
+                match rx.recv_timeout(debounce) {
+                    Ok(_) => continue,
+                    Err(std::sync::mpsc::RecvTimeoutError::Timeout) => break,
+                    Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => return,
+                }
+            }
+            if is_recent_own_registry_write() {
+                continue;
+            }
+
+            let total = load_tombstones_or_log().len();
+            if let Err(e) = app_handle.emit("corpses-updated", serde_json::json!({ "total": total })) {
+                eprintln!("⚠️ 推送 corpses-updated 失败: {}", e);
+            }
+        }
+    });
+
+    let state = app.state::<AppState>();
+    *state.registry_watcher.lock().unwrap_or_else(|e| e.into_inner()) = Some(watcher);
+    Ok(())
+}
+
+fn stop_registry_watcher(app: &tauri::AppHandle) {
+    let state = app.state::<AppState>();
+    state.registry_watcher.lock().unwrap_or_else(|e| e.into_inner()).take();
+}
+
+// ========== 系统通知 ==========
+
+const DEFAULT_NOTIFICATION_COOLDOWN_SECS: u64 = 60;
+
+#[derive(Default)]
+pub struct NotificationState {
+    pub last_sent: Option<DateTime<Utc>>,
+    pub suppressed: usize,
+    pub flush_pending: bool,
+    // 勿扰模式截止时间，之前的通知全部压下，结束时合并成一条
+    pub muted_until: Option<DateTime<Utc>>,
+}
+
+fn schedule_notification_flush(app: &tauri::AppHandle, wait: Duration) {
+    let app = app.clone();
+    let wait = wait.to_std().unwrap_or_default();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(wait).await;
+        flush_suppressed_notifications(&app);
+    });
+}
+
+fn show_notification(app: &tauri::AppHandle, title: &str, body: &str) {
+    use tauri_plugin_notification::NotificationExt;
+    if let Err(e) = app.notification().builder().title(title).body(body).show() {
+        eprintln!("⚠️ 发送通知失败: {}", e);
+    }
+}
+
+// 现在发通知还要等多久：冷却和勿扰取较晚结束的那个；None 表示可以立即发送
+fn notification_delay(notifications: &NotificationState, cooldown_secs: u64, now: DateTime<Utc>) -> Option<Duration> {
+    let cooldown_end = notifications
+        .last_sent
+        .map(|last| last + Duration::seconds(cooldown_secs as i64));
+    cooldown_end
+        .max(notifications.muted_until)
+        .map(|release| release - now)
+        .filter(|remaining| *remaining > Duration::zero())
+}
+
+// 所有系统通知都经这里：距上一条不足 notification_cooldown_secs 或处于勿扰模式时先计数，
+// 冷却/勿扰结束时合并成一条汇总通知
+fn notify(app: &tauri::AppHandle, title: &str, body: &str) {
+    let cooldown_secs = resolve_config()
+        .map(|c| c.notification_cooldown_secs)
+        .unwrap_or(DEFAULT_NOTIFICATION_COOLDOWN_SECS);
+    let now = Utc::now();
+
+    let state = app.state::<AppState>();
+    let mut notifications = state.notifications.lock().unwrap_or_else(|e| e.into_inner());
+    let Some(remaining) = notification_delay(&notifications, cooldown_secs, now) else {
+        notifications.last_sent = Some(now);
+        drop(notifications);
+        show_notification(app, title, body);
+        return;
+    };
+
+    notifications.suppressed += 1;
+    if !notifications.flush_pending {
+        notifications.flush_pending = true;
+        schedule_notification_flush(app, remaining);
+    }
+}
+
+fn flush_suppressed_notifications(app: &tauri::AppHandle) {
+    let count = {
+        let state = app.state::<AppState>();
+        let mut notifications = state.notifications.lock().unwrap_or_else(|e| e.into_inner());
+
+        // 等待期间勿扰被延长了：推迟到新的截止时间
+        let now = Utc::now();
+        if let Some(until) = notifications.muted_until.filter(|until| *until > now) {
+            schedule_notification_flush(app, until - now);
+            return;
+        }
+
+        let count = notifications.suppressed;
+        notifications.suppressed = 0;
+        notifications.flush_pending = false;
+        if count > 0 {
+            notifications.last_sent = Some(Utc::now());
+        }
+        count
+    };
+
+    if count > 0 {
+        show_notification(
+            app,
+            &tr("notify.zombie_title"),
+            &tr_args("notify.coalesced", &[("count", count.to_string())]),
+        );
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct NotificationStatus {
+    pub muted: bool,
+    pub muted_until: Option<String>,
+    pub suppressed: usize,
+}
+
+fn notification_status_of(notifications: &NotificationState) -> NotificationStatus {
+    let muted_until = notifications.muted_until.filter(|until| *until > Utc::now());
+    NotificationStatus {
+        muted: muted_until.is_some(),
+        muted_until: muted_until.map(|until| until.to_rfc3339()),
+        suppressed: notifications.suppressed,
+    }
+}
+
+const MAX_SNOOZE_MINUTES: u64 = 7 * 24 * 60;
+
+// 勿扰截止时间；minutes = 0 表示解除
+fn snooze_deadline(minutes: u64, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    (minutes > 0).then(|| now + Duration::minutes(minutes.min(MAX_SNOOZE_MINUTES) as i64))
+}
+
+// 全局勿扰 minutes 分钟（最多一周）；minutes = 0 立即解除，并马上发出积压的汇总
+#[tauri::command]
+pub fn snooze_notifications(app: tauri::AppHandle, minutes: u64) -> NotificationStatus {
+    let state = app.state::<AppState>();
+    let mut notifications = state.notifications.lock().unwrap_or_else(|e| e.into_inner());
+
+    if minutes == 0 {
+        notifications.muted_until = None;
+        let pending = notifications.suppressed > 0;
+        drop(notifications);
+        if pending {
+            flush_suppressed_notifications(&app);
+        }
+        let notifications = state.notifications.lock().unwrap_or_else(|e| e.into_inner());
+        return notification_status_of(&notifications);
+    }
+
+    notifications.muted_until = snooze_deadline(minutes, Utc::now());
+    notification_status_of(&notifications)
+}
+
+#[tauri::command]
+pub fn notification_status(state: tauri::State<'_, AppState>) -> NotificationStatus {
+    let notifications = state.notifications.lock().unwrap_or_else(|e| e.into_inner());
+    notification_status_of(&notifications)
+}
+
+fn unread_alert_ids() -> HashSet<String> {
+    get_zombie_alerts()
+        .alerts
+        .into_iter()
+        .filter(|a| !a.notified)
+        .map(|a| a.id)
+        .collect()
+}
+
+fn notify_new_alerts(app: &tauri::AppHandle, seen: &HashSet<String>) {
+    for alert in get_zombie_alerts().alerts {
+        if alert.notified || seen.contains(&alert.id) {
+            continue;
+        }
+        notify(
+            app,
+            &tr("notify.zombie_title"),
+            &tr_args(
+                "notify.zombie_body",
+                &[("corpse", alert.corpse_path.clone()), ("zombie", alert.zombie_repo.clone())],
+            ),
+        );
+    }
+}
+
+// ========== 主入口 ==========
+
+#[cfg_attr(mobile, tauri::mobile_entry_point)]
+pub fn run() {
+    // 日志级别默认 info，可用 RUST_LOG 调整（例如 RUST_LOG=frontend=debug）
+    let _ = tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info")),
+        )
+        .try_init();
+
+    tauri::Builder::default()
+        // 必须最先注册：第二个实例启动时在这里把参数转交给已运行的实例，然后自己退出
+        .plugin(tauri_plugin_single_instance::init(focus_primary_instance))
+        .plugin(tauri_plugin_shell::init())
+        .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_notification::init())
+        .manage(AppState::default())
+        .on_menu_event(|app, event| handle_tray_menu_event(app, event.id().as_ref()))
+        .setup(|app| {
+            let _ = APP_HANDLE.set(app.handle().clone());
+            emit_stats_updated();
+            if let Err(e) = refresh_tray_badge(app.handle().clone()) {
+                eprintln!("⚠️ 更新托盘角标失败: {}", e);
+            }
+            if let Err(e) = refresh_tray_menu(app.handle().clone()) {
+                eprintln!("⚠️ 构建托盘菜单失败: {}", e);
+            }
+
+            if safe_mode_enabled() {
+                println!("🛟 安全模式：跳过后台任务，使用默认配置");
+                app.emit("safe-mode-active", ())?;
+                return Ok(());
+            }
+            spawn_scan_scheduler(app.handle().clone());
+            rearm_one_shot(app.handle());
+            if let Err(e) = start_registry_watcher(app.handle()) {
+                eprintln!("⚠️ 注册表监听未启动: {}", e);
+            }
+            Ok(())
+        })
+        .invoke_handler(tauri::generate_handler![
+            get_stats,
+            get_recent_corpses,
+            trigger_scan,
+            send_report,
+            load_config,
+            save_config,
+            update_github_token,
+            set_autostart,
+            log_message,
+            log_batch,
+            get_version,
+            get_zombie_alerts,
+            mark_alert_read,
+            clear_all_alerts,
+            compute_similarity,
+            archive_resurrected,
+            unarchive,
+            test_report_webhook,
+            delete_tombstones_where,
+            undo_last_change,
+            get_effective_config,
+            get_cause_impact,
+            get_alerts_for_repo,
+            get_last_background_error,
+            set_pinned,
+            generate_graveyard_card,
+            export_bundle,
+            import_bundle,
+            get_resurrection_by_language,
+            suggest_epitaph,
+            reload_epitaph_templates,
+            prune_orphan_alerts,
+            get_build_info,
+            export_to_file,
+            set_target_org,
+            get_graveyard_score,
+            search_assets,
+            get_alerts_by_type,
+            record_stats_snapshot,
+            get_stats_history,
+            is_safe_mode,
+            get_churn_rate,
+            set_scan_interval,
+            get_scan_interval_human,
+            get_death_heatmap,
+            normalize_tombstone,
+            get_deaths_by_weekday,
+            bulk_resurrect,
+            get_paths,
+            refresh_tombstone,
+            get_config_history,
+            estimate_scan,
+            export_alerts_report,
+            set_org_interval,
+            score_against_tombstone,
+            set_asset_exempt,
+            find_by_hash,
+            refresh_content_hashes,
+            trigger_scan_org,
+            export_feed,
+            find_duplicate_assets,
+            dedupe_assets,
+            import_csv,
+            get_worst_day,
+            set_display_timezone,
+            preview_report,
+            get_resurrection_flows,
+            snooze_notifications,
+            notification_status,
+            validate_dataset,
+            get_effort_estimate,
+            get_alive_dead_ratio,
+            schedule_one_shot_scan,
+            cancel_one_shot,
+            auto_tag,
+            get_death_timeline,
+            export_timeline_svg,
+            get_throttle_status,
+            find_undead_assets,
+            get_resurrection_candidates,
+            add_attachment,
+            remove_attachment,
+            get_avg_size_trend,
+            merge_asset_index,
+            generate_death_certificate,
+            diagnose_config,
+            get_longest_resurrections,
+            refresh_tray_badge,
+            get_tag_cooccurrence,
+            get_at_risk_repos,
+            get_resurrection_halflife,
+            export_html,
+            get_author_stats,
+            apply_retention,
+            fetch_org_members,
+            get_cached_members,
+            get_stats_for_tag,
+            refresh_tray_menu,
+            get_language_entropy,
+            export_state_snapshot,
+            import_state_snapshot,
+            get_quota_history,
+            evaluate_death_rules,
+            test_death_rules,
+            get_necromancy_risk,
+            create_tombstone,
+            delete_tombstone,
+            resurrect_tombstone,
+            detect_zombies,
+            get_corpses_page
+        ])
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                stop_registry_watcher(app);
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn approx(actual: f64, expected: f64) -> bool {
+        (actual - expected).abs() < 1e-9
+    }
+
+    // ========== 测试沙箱 ==========
+
+    // 读写磁盘的测试共用进程级的环境变量和应用目录，只能串行执行
+    static TEST_ENV: std::sync::Mutex<()> = std::sync::Mutex::new(());
+    static SANDBOX_SEQ: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+    struct Sandbox {
+        base: PathBuf,
+        _guard: std::sync::MutexGuard<'static, ()>,
+    }
+
+    impl Sandbox {
+        fn cemetery(&self) -> PathBuf {
+            self.base.join(".cemetery")
+        }
+    }
+
+    impl Drop for Sandbox {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.base);
+        }
+    }
+
+    fn test_root() -> PathBuf {
+        std::env::temp_dir().join(format!("code-corpses-test-{}", std::process::id()))
+    }
+
+    // 把配置、数据和墓地目录都指向临时目录；每次调用都从空目录开始
+    fn sandbox() -> Sandbox {
+        let guard = TEST_ENV.lock().unwrap_or_else(|e| e.into_inner());
+        let root = test_root();
+        static INIT: std::sync::Once = std::sync::Once::new();
+        INIT.call_once(|| {
+            std::env::set_var("CODE_CORPSES_CONFIG_DIR", root.join("config"));
+            std::env::set_var("CODE_CORPSES_DATA_DIR", root.join("data"));
+            for (_, var) in ENV_OVERRIDES {
+                std::env::remove_var(var);
+            }
+        });
+        // 应用目录一旦缓存就不会再变；不在临时目录下时绝不能清空
+        assert!(
+            app_config_dir().0.starts_with(&root) && app_data_dir().0.starts_with(&root),
+            "应用目录不在测试临时目录下"
+        );
+        for dir in [&app_config_dir().0, &app_data_dir().0] {
+            let _ = fs::remove_dir_all(dir);
+            fs::create_dir_all(dir).unwrap();
+        }
+
+        let seq = SANDBOX_SEQ.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        let base = root.join(format!("base-{}", seq));
+        let _ = fs::remove_dir_all(&base);
+        fs::create_dir_all(base.join(".cemetery")).unwrap();
+        std::env::set_var("CODE_CORPSES_BASE_DIR", &base);
+        Sandbox { base, _guard: guard }
+    }
+
+    fn tombstone(id: &str, repo: &str, path: &str) -> Tombstone {
+        Tombstone {
+            id: id.to_string(),
+            name: path.rsplit('/').next().unwrap_or(path).to_string(),
+            cause_of_death: "deleted".to_string(),
+            epitaph: String::new(),
+            tags: vec![],
+            original_path: path.to_string(),
+            language: None,
+            line_count: 10,
+            died_at: "2024-01-01T00:00:00+00:00".to_string(),
+            resurrected_at: None,
+            resurrected_to: None,
+            repo: Some(repo.to_string()),
+            pinned: false,
+            content_hash: None,
+            attachments: vec![],
+            author: None,
+        }
+    }
+
+    fn asset(id: &str, location: &str, alive: bool) -> Asset {
+        Asset {
+            id: id.to_string(),
+            name: location.rsplit('/').next().unwrap_or(location).to_string(),
+            r#type: "file".to_string(),
+            location: location.to_string(),
+            language: None,
+            tags: vec![],
+            alive,
+            line_count: 10,
+            exempt: false,
+            content_hash: None,
+            repo: Some("acme/api".to_string()),
+            author: None,
+            updated_at: None,
+            blob_sha: None,
+            tombstone_id: None,
+        }
+    }
+
+    // ========== 相似度算法 ==========
+
+    #[test]
+    fn jaccard_scores_shared_shingles() {
+        // 各有两个 3-token 滑窗，共享其中一个：1 / 3
+        let score = similarity_score(SimAlgo::Jaccard, "alpha beta gamma delta", "alpha beta gamma epsilon");
+        assert!(approx(score, 1.0 / 3.0), "{}", score);
+    }
+
+    #[test]
+    fn levenshtein_scores_edit_distance() {
+        // kitten → sitting 编辑距离 3，较长一方 7 个字符
+        let score = similarity_score(SimAlgo::Levenshtein, "kitten", "sitting");
+        assert!(approx(score, 4.0 / 7.0), "{}", score);
+    }
+
+    #[test]
+    fn token_cosine_scores_term_frequencies() {
+        // {a:2, b:1} · {a:1, b:2} = 4，两个向量的模都是 √5
+        let score = similarity_score(SimAlgo::TokenCosine, "a a b", "a b b");
+        assert!(approx(score, 0.8), "{}", score);
+    }
+
+    #[test]
+    fn only_token_cosine_ignores_reordering() {
+        let a = "let total = price * count";
+        let b = "count * price = total let";
+        assert!(approx(similarity_score(SimAlgo::TokenCosine, a, b), 1.0));
+        assert!(similarity_score(SimAlgo::Jaccard, a, b) < 1.0);
+        assert!(similarity_score(SimAlgo::Levenshtein, a, b) < 1.0);
+    }
+
+    #[test]
+    fn identical_sources_score_one_for_every_algorithm() {
+        let source = "fn main() { println!(\"hi\"); }";
+        for algo in [SimAlgo::Jaccard, SimAlgo::Levenshtein, SimAlgo::TokenCosine] {
+            assert!(approx(similarity_score(algo, source, source), 1.0), "{:?}", algo);
+        }
+    }
+
+    // ========== Webhook 签名 ==========
+
+    #[test]
+    fn webhook_signature_matches_rfc4231_vector() {
+        // RFC 4231 测试用例 2
+        assert_eq!(
+            sign_webhook_payload("Jefe", b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+
+    // ========== 状态快照 ==========
+
+    #[test]
+    fn state_snapshot_round_trips_without_secrets() {
+        let sb = sandbox();
+        write_tombstones(&[tombstone("t1", "acme/api", "src/old.rs")]).unwrap();
+        write_json_atomic(&get_asset_index_path(), &vec![asset("a1", "src/live.rs", true)]).unwrap();
+        fs::write(get_cemetery_ignore_path(), "target/\n").unwrap();
+        save_config(&Config {
+            github_token: Some("ghp_exported".to_string()),
+            webhook_secret: Some("hook-secret".to_string()),
+            target_org: "acme".to_string(),
+            ..Config::default()
+        })
+        .unwrap();
+
+        let json = serde_json::to_string(&build_state_snapshot(true).unwrap()).unwrap();
+        assert!(!json.contains("ghp_exported"));
+        assert!(!json.contains("hook-secret"));
+
+        // 换一台机器：数据清空，本地有自己的令牌
+        fs::remove_dir_all(sb.cemetery()).unwrap();
+        fs::create_dir_all(sb.cemetery()).unwrap();
+        save_config(&Config {
+            github_token: Some("ghp_local".to_string()),
+            ..Config::default()
+        })
+        .unwrap();
+
+        let snapshot: StateSnapshot = serde_json::from_str(&json).unwrap();
+        let (paused, one_shot_at) = restore_state_snapshot(snapshot).unwrap();
+        assert!(paused);
+        assert!(one_shot_at.is_none());
+
+        let tombstones = read_tombstones().unwrap();
+        assert_eq!(tombstones.len(), 1);
+        assert_eq!(tombstones[0].id, "t1");
+        let assets = read_assets().unwrap();
+        assert_eq!(assets.len(), 1);
+        assert_eq!(assets[0].location, "src/live.rs");
+        assert_eq!(fs::read_to_string(get_cemetery_ignore_path()).unwrap(), "target/\n");
+
+        let config = load_config().unwrap();
+        assert_eq!(config.target_org, "acme");
+        assert_eq!(config.github_token.as_deref(), Some("ghp_local"));
+        assert_eq!(config.webhook_secret, None);
+    }
+
+
+    // ========== 埋葬 ==========
+
+    // 相当于一次扫描的埋葬步骤
+    fn bury_from_index() -> Vec<Tombstone> {
+        bury_dead_assets(&read_assets().unwrap(), DEFAULT_RENAME_SIMILARITY_THRESHOLD, &[], false).unwrap()
+    }
+
+    #[test]
+    fn repeated_scans_bury_each_asset_once() {
+        let _sb = sandbox();
+        write_json_atomic(
+            &get_asset_index_path(),
+            &vec![asset("a1", "src/app.rs", true), asset("a2", "src/legacy_parser.rs", false)],
+        )
+        .unwrap();
+
+        let first = bury_from_index();
+        assert_eq!(first.len(), 1);
+        assert!(bury_from_index().is_empty());
+        assert_eq!(read_tombstones().unwrap().len(), 1);
+
+        let assets = read_assets().unwrap();
+        assert_eq!(assets[0].tombstone_id, None);
+        assert_eq!(assets[1].tombstone_id.as_deref(), Some(first[0].id.as_str()));
+    }
+
+    #[test]
+    fn deleted_tombstone_is_not_reburied() {
+        let _sb = sandbox();
+        write_json_atomic(&get_asset_index_path(), &vec![asset("a1", "src/legacy_parser.rs", false)]).unwrap();
+
+        let first = bury_from_index();
+        assert_eq!(first.len(), 1);
+        delete_tombstone(first[0].id.clone()).unwrap();
+
+        assert!(bury_from_index().is_empty());
+        assert!(read_tombstones().unwrap().is_empty());
+    }
+
+    #[test]
+    fn tombstones_from_before_the_marker_get_marked() {
+        let _sb = sandbox();
+        // 旧版本立的碑：资产上还没有 tombstoneId
+        let existing = tombstone_from_asset(&asset("a1", "src/old.rs", false), Some("acme/api"), "deleted");
+        write_tombstones(&[existing.clone()]).unwrap();
+        write_json_atomic(&get_asset_index_path(), &vec![asset("a1", "src/old.rs", false)]).unwrap();
+
+        assert!(bury_from_index().is_empty());
+        assert_eq!(read_assets().unwrap()[0].tombstone_id.as_deref(), Some(existing.id.as_str()));
+    }
+
+    #[test]
+    fn reappearing_asset_clears_its_marker() {
+        let mut existing = vec![serde_json::json!({
+            "id": "a1",
+            "location": "src/old.rs",
+            "alive": false,
+            "tombstoneId": "tomb-1",
+        })];
+        merge_asset_values(&mut existing, vec![asset("a1", "src/old.rs", true)], |_| true).unwrap();
+        assert_eq!(existing[0]["alive"], true);
+        assert!(existing[0].get("tombstoneId").is_none());
+    }
+
+
+    // ========== 保留策略 ==========
+
+    // 先扫描立碑，再把死亡时间改到很久以前，让它超出保留期
+    fn bury_expired(retention_action: RetentionAction) -> Tombstone {
+        save_config(&Config {
+            retention_days: Some(30),
+            retention_action,
+            ..Config::default()
+        })
+        .unwrap();
+        write_json_atomic(&get_asset_index_path(), &vec![asset("a1", "src/legacy_parser.rs", false)]).unwrap();
+        let mut buried = bury_from_index();
+        assert_eq!(buried.len(), 1);
+        buried[0].died_at = "2020-01-01T00:00:00+00:00".to_string();
+        write_tombstones(&buried).unwrap();
+        buried.remove(0)
+    }
+
+    #[test]
+    fn retention_archive_survives_rescan() {
+        let _sb = sandbox();
+        let expired = bury_expired(RetentionAction::Archive);
+
+        let summary = apply_retention().unwrap();
+        assert_eq!(summary.archived, 1);
+        assert!(bury_from_index().is_empty());
+
+        assert!(read_tombstones().unwrap().is_empty());
+        let archive = read_tombstones_from(&get_archive_path()).unwrap();
+        assert_eq!(archive.len(), 1);
+        assert_eq!(archive[0].id, expired.id);
+    }
+
+    #[test]
+    fn retention_delete_survives_rescan() {
+        let _sb = sandbox();
+        bury_expired(RetentionAction::Delete);
+
+        let summary = apply_retention().unwrap();
+        assert_eq!(summary.deleted, 1);
+        assert!(bury_from_index().is_empty());
+
+        assert!(read_tombstones().unwrap().is_empty());
+        assert!(read_tombstones_from(&get_archive_path()).unwrap().is_empty());
+    }
+
+
+    // ========== 归档 ==========
+
+    #[test]
+    fn archived_resurrection_survives_rescan() {
+        let _sb = sandbox();
+        write_json_atomic(&get_asset_index_path(), &vec![asset("a1", "src/legacy_parser.rs", false)]).unwrap();
+        let buried = bury_from_index();
+        assert_eq!(buried.len(), 1);
+
+        resurrect_tombstone(buried[0].id.clone(), "src/parser.rs".to_string()).unwrap();
+        let mut tombstones = read_tombstones().unwrap();
+        tombstones[0].resurrected_at = Some("2020-01-01T00:00:00+00:00".to_string());
+        write_tombstones(&tombstones).unwrap();
+
+        let summary = archive_resurrected(30).unwrap();
+        assert_eq!(summary.archived, 1);
+        assert_eq!(summary.remaining, 0);
+
+        // 资产仍是 alive = false，但已经立过碑，不会重新出现在注册表里
+        assert!(bury_from_index().is_empty());
+        assert!(read_tombstones().unwrap().is_empty());
+        assert_eq!(read_tombstones_from(&get_archive_path()).unwrap().len(), 1);
+    }
+
+
+    #[test]
+    fn delete_tombstone_can_be_undone_and_rejects_unknown_ids() {
+        let _sb = sandbox();
+        write_tombstones(&[tombstone("t1", "acme/api", "src/a.rs"), tombstone("t2", "acme/api", "src/b.rs")]).unwrap();
+
+        assert!(delete_tombstone("missing".to_string()).is_err());
+        delete_tombstone("t1".to_string()).unwrap();
+        let ids: Vec<String> = read_tombstones().unwrap().into_iter().map(|t| t.id).collect();
+        assert_eq!(ids, ["t2"]);
+
+        undo_last_change().unwrap();
+        assert_eq!(read_tombstones().unwrap().len(), 2);
+    }
+
+
+    // ========== 流式导出 ==========
+
+    #[test]
+    fn export_streams_tombstones_from_plain_and_gzip_registries() {
+        let sb = sandbox();
+        let tombstones: Vec<Tombstone> = (0..3)
+            .map(|i| tombstone(&format!("t{}", i), "acme/api", &format!("src/f{}.rs", i)))
+            .collect();
+        write_tombstones(&tombstones).unwrap();
+
+        let json_path = sb.base.join("tombstones.json");
+        let written = export_to_file("tombstones".into(), "json".into(), json_path.to_string_lossy().into()).unwrap();
+        assert_eq!(written, 3);
+        let exported: Vec<Tombstone> = serde_json::from_str(&fs::read_to_string(&json_path).unwrap()).unwrap();
+        let ids: Vec<&str> = exported.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(ids, ["t0", "t1", "t2"]);
+
+        save_config(&Config { compress_data: true, ..Config::default() }).unwrap();
+        assert!(gz_path(&get_tombstone_registry_path()).exists());
+        let csv_path = sb.base.join("tombstones.csv");
+        let written = export_to_file("tombstones".into(), "csv".into(), csv_path.to_string_lossy().into()).unwrap();
+        assert_eq!(written, 3);
+        assert_eq!(fs::read_to_string(&csv_path).unwrap().lines().count(), 4);
+
+        // 归档不存在时导出空表
+        let empty_path = sb.base.join("archive.json");
+        assert_eq!(export_to_file("archive".into(), "json".into(), empty_path.to_string_lossy().into()).unwrap(), 0);
+        assert!(serde_json::from_str::<Vec<Tombstone>>(&fs::read_to_string(&empty_path).unwrap()).unwrap().is_empty());
+    }
+
+
+    // ========== 并发写入 ==========
+
+    #[test]
+    fn concurrent_create_tombstone_keeps_every_write() {
+        let _sb = sandbox();
+        const WRITERS: usize = 16;
+        let handles: Vec<_> = (0..WRITERS)
+            .map(|i| {
+                std::thread::spawn(move || {
+                    create_tombstone(tombstone(&format!("t{}", i), "acme/api", &format!("src/f{}.rs", i)))
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap().unwrap();
+        }
+
+        let mut ids: Vec<String> = read_tombstones().unwrap().into_iter().map(|t| t.id).collect();
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), WRITERS);
+    }
+
+
+    // ========== 批量删除 ==========
+
+    // 三个墓碑：不同仓库、语言、标签和死亡日期
+    fn filter_fixture() -> Vec<Tombstone> {
+        let mut a = tombstone("a", "acme/api", "src/a.rs");
+        a.language = Some("Rust".to_string());
+        a.tags = vec!["legacy".to_string()];
+        a.died_at = "2023-01-01T00:00:00+00:00".to_string();
+        let mut b = tombstone("b", "acme/web", "src/b.ts");
+        b.language = Some("TypeScript".to_string());
+        b.died_at = "2024-01-01T00:00:00+00:00".to_string();
+        let mut c = tombstone("c", "acme/web", "src/c.ts");
+        c.language = Some("TypeScript".to_string());
+        c.tags = vec!["Legacy".to_string()];
+        c.died_at = "2025-01-01T00:00:00+00:00".to_string();
+        vec![a, b, c]
+    }
+
+    // 在干净的注册表上按 filter 批量删除，返回剩下的 id
+    fn delete_where(filter: TombstoneFilter) -> Vec<String> {
+        write_tombstones(&filter_fixture()).unwrap();
+        delete_tombstones_where(filter).unwrap();
+        read_tombstones().unwrap().into_iter().map(|t| t.id).collect()
+    }
+
+    #[test]
+    fn delete_tombstones_where_matches_each_condition() {
+        let _sb = sandbox();
+        let some = |v: &str| Some(v.to_string());
+
+        assert_eq!(delete_where(TombstoneFilter { repo: some("acme/web"), ..Default::default() }), ["a"]);
+        assert_eq!(delete_where(TombstoneFilter { language: some("rust"), ..Default::default() }), ["b", "c"]);
+        assert_eq!(delete_where(TombstoneFilter { tag: some("legacy"), ..Default::default() }), ["b"]);
+        assert_eq!(delete_where(TombstoneFilter { died_before: some("2024-01-01"), ..Default::default() }), ["b", "c"]);
+        assert_eq!(delete_where(TombstoneFilter { died_after: some("2024-01-01"), ..Default::default() }), ["a", "b"]);
+        // 条件同时成立才删除
+        assert_eq!(
+            delete_where(TombstoneFilter { repo: some("acme/web"), tag: some("legacy"), ..Default::default() }),
+            ["a", "b"]
+        );
+    }
+
+    #[test]
+    fn delete_tombstones_where_rejects_empty_and_invalid_filters() {
+        let _sb = sandbox();
+        write_tombstones(&filter_fixture()).unwrap();
+
+        assert!(delete_tombstones_where(TombstoneFilter::default()).is_err());
+        let invalid = TombstoneFilter { died_before: Some("not a date".to_string()), ..Default::default() };
+        assert!(delete_tombstones_where(invalid).is_err());
+        assert_eq!(read_tombstones().unwrap().len(), 3);
+    }
+
+
+    // ========== 仓库可见性 ==========
+
+    // GitHub /orgs/{org}/repos 的录制响应（截取部分字段）
+    const ORG_REPOS_FIXTURE: &str = include_str!("../tests/fixtures/github-org-repos.json");
+
+    fn visible_repo_names(include_private: bool) -> Vec<String> {
+        let repos: Vec<GhRepo> = serde_json::from_str(ORG_REPOS_FIXTURE).unwrap();
+        filter_repo_visibility(repos, include_private)
+            .into_iter()
+            .map(|r| r.full_name)
+            .collect()
+    }
+
+    #[test]
+    fn include_private_keeps_every_listed_repo() {
+        assert_eq!(repo_list_type(true), "all");
+        assert_eq!(
+            visible_repo_names(true),
+            ["acme/api", "acme/billing", "acme/handbook", "acme/legacy-site"]
+        );
+    }
+
+    #[test]
+    fn excluding_private_drops_private_and_internal_repos() {
+        assert_eq!(repo_list_type(false), "public");
+        assert_eq!(visible_repo_names(false), ["acme/api", "acme/legacy-site"]);
+    }
+
+
+    // ========== 数据压缩 ==========
+
+    #[test]
+    fn gzip_data_files_round_trip() {
+        let sb = sandbox();
+        let path = sb.cemetery().join("tombstone-registry.json");
+        let content = r#"[{"id":"t1","note":"墓志铭"}]"#;
+
+        write_data_bytes(&path, content.as_bytes(), true).unwrap();
+        assert!(!path.exists());
+        assert!(gz_path(&path).exists());
+        assert_ne!(fs::read(gz_path(&path)).unwrap(), content.as_bytes());
+        assert_eq!(read_data_file(&path).unwrap(), content);
+
+        // 切回明文时删除 .gz，磁盘上只留一份
+        write_data_bytes(&path, content.as_bytes(), false).unwrap();
+        assert!(path.exists());
+        assert!(!gz_path(&path).exists());
+        assert_eq!(read_data_file(&path).unwrap(), content);
+    }
+
+
+    // ========== 置顶 ==========
+
+    fn page_ids(offset: usize, limit: usize, pinned_first: Option<bool>) -> Vec<String> {
+        get_corpses_page(offset, limit, pinned_first)
+            .unwrap()
+            .items
+            .into_iter()
+            .map(|t| t.id)
+            .collect()
+    }
+
+    #[test]
+    fn pinned_tombstones_lead_only_when_requested() {
+        let _sb = sandbox();
+        let tombstones: Vec<Tombstone> = ["2023-01-01", "2024-01-01", "2025-01-01"]
+            .iter()
+            .enumerate()
+            .map(|(i, day)| {
+                let mut t = tombstone(&format!("t{}", i), "acme/api", &format!("src/f{}.rs", i));
+                t.died_at = day.to_string();
+                t
+            })
+            .collect();
+        write_tombstones(&tombstones).unwrap();
+        set_pinned("t0".to_string(), true).unwrap();
+        assert!(set_pinned("missing".to_string(), true).is_err());
+
+        assert_eq!(page_ids(0, 10, None), ["t2", "t1", "t0"]);
+        assert_eq!(page_ids(0, 10, Some(true)), ["t0", "t2", "t1"]);
+
+        let page = get_corpses_page(1, 1, Some(true)).unwrap();
+        assert_eq!(page.total, 3);
+        assert!(page.has_more);
+        assert_eq!(page.items[0].id, "t2");
+
+        set_pinned("t0".to_string(), false).unwrap();
+        assert_eq!(page_ids(0, 10, Some(true)), ["t2", "t1", "t0"]);
+    }
+
+
+    // ========== 孤儿提醒 ==========
+
+    fn alert(id: &str, corpse_repo: &str, corpse_path: &str) -> ZombieAlert {
+        ZombieAlert {
+            id: id.to_string(),
+            corpse_repo: corpse_repo.to_string(),
+            corpse_path: corpse_path.to_string(),
+            zombie_repo: "acme/web".to_string(),
+            zombie_path: "lib/copied.rs".to_string(),
+            similarity: 0.92,
+            resurrection_type: "copy".to_string(),
+            confidence: 0.9,
+            detected_at: "2024-06-01T00:00:00+00:00".to_string(),
+            notified: false,
+        }
+    }
+
+    fn write_alerts(alerts: Vec<ZombieAlert>) {
+        let unread_count = alerts.iter().filter(|a| !a.notified).count();
+        let data = ZombieAlerts {
+            total_alerts: alerts.len(),
+            unread_count,
+            alerts,
+            last_check: "2024-06-01T00:00:00+00:00".to_string(),
+        };
+        write_json_atomic(&get_zombie_alerts_path(), &data).unwrap();
+    }
+
+    #[test]
+    fn prune_orphan_alerts_drops_alerts_without_a_tombstone() {
+        let _sb = sandbox();
+        write_alerts(vec![alert("kept", "acme/api", "./src/old.rs"), alert("dangling", "acme/api", "src/gone.rs")]);
+        // 注册表不存在时不清理
+        assert_eq!(prune_orphan_alerts().unwrap(), 0);
+
+        write_tombstones(&[tombstone("t1", "acme/api", "src/old.rs")]).unwrap();
+        assert_eq!(prune_orphan_alerts().unwrap(), 1);
+        let remaining = get_zombie_alerts();
+        assert_eq!(remaining.alerts.len(), 1);
+        assert_eq!(remaining.alerts[0].id, "kept");
+        assert_eq!(remaining.total_alerts, 1);
+        assert_eq!(prune_orphan_alerts().unwrap(), 0);
+    }
+
+
+    // ========== 诈尸检测 ==========
+
+    #[test]
+    fn resurrection_type_follows_similarity_thresholds() {
+        assert_eq!(resurrection_type(1.0), "verbatim");
+        assert_eq!(resurrection_type(ZOMBIE_VERBATIM_SIMILARITY), "verbatim");
+        assert_eq!(resurrection_type(ZOMBIE_VERBATIM_SIMILARITY - 0.001), "refactored");
+        assert_eq!(resurrection_type(ZOMBIE_REFACTORED_SIMILARITY), "refactored");
+        assert_eq!(resurrection_type(ZOMBIE_REFACTORED_SIMILARITY - 0.001), "partial");
+        assert_eq!(resurrection_type(ZOMBIE_MIN_SIMILARITY), "partial");
+    }
+
+    #[test]
+    fn zombie_confidence_discounts_short_files() {
+        let words = |n: usize| (0..n).map(|i| format!("w{}", i)).collect::<Vec<_>>().join(" ");
+        let long = words(ZOMBIE_CONFIDENT_TOKENS * 2);
+        // 双方都够长：置信度等于相似度
+        assert!((zombie_confidence(0.8, &long, &words(ZOMBIE_CONFIDENT_TOKENS)) - 0.8).abs() < 1e-9);
+        // 按较短一方的 token 数打折：10 / 50
+        assert!((zombie_confidence(0.8, &long, &words(10)) - 0.16).abs() < 1e-9);
+        assert!((zombie_confidence(0.8, &words(10), &long) - 0.16).abs() < 1e-9);
+        assert_eq!(zombie_confidence(0.8, "", &long), 0.0);
+    }
+
+    #[test]
+    fn ingest_alerts_keeps_existing_alerts_and_their_read_state() {
+        let _sb = sandbox();
+        let mut read = alert("seen", "acme/api", "src/old.rs");
+        read.notified = true;
+        write_alerts(vec![read, alert("unseen", "acme/api", "src/other.rs")]);
+
+        // 重复的 id 即使带着未读状态也不覆盖已有提醒
+        let mut again = alert("seen", "acme/api", "src/old.rs");
+        again.confidence = 0.1;
+        let added = ingest_alerts(vec![again, alert("fresh", "acme/api", "src/new.rs")]).unwrap();
+        assert_eq!(added, 1);
+
+        let data = get_zombie_alerts();
+        let ids: Vec<&str> = data.alerts.iter().map(|a| a.id.as_str()).collect();
+        assert_eq!(ids, ["seen", "unseen", "fresh"]);
+        assert!(data.alerts[0].notified);
+        assert_eq!(data.alerts[0].confidence, 0.9);
+        assert_eq!((data.total_alerts, data.unread_count), (3, 2));
+    }
+
+
+    // ========== 统计历史 ==========
+
+    fn stats_with(total_tombstones: usize) -> Stats {
+        Stats {
+            total_assets: 0,
+            alive_assets: 0,
+            dead_assets: 0,
+            total_tombstones,
+            resurrected: 0,
+            last_scan: String::new(),
+        }
+    }
+
+    #[test]
+    fn stats_history_keeps_one_snapshot_per_day() {
+        let mut history = vec![];
+        let morning = Utc.with_ymd_and_hms(2024, 3, 1, 8, 0, 0).unwrap();
+        push_stats_snapshot(&mut history, stats_with(1), morning);
+        push_stats_snapshot(&mut history, stats_with(2), morning + Duration::hours(10));
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].stats.total_tombstones, 2);
+
+        push_stats_snapshot(&mut history, stats_with(3), morning + Duration::days(1));
+        let totals: Vec<usize> = history.iter().map(|s| s.stats.total_tombstones).collect();
+        assert_eq!(totals, [2, 3]);
+    }
+
+    #[test]
+    fn stats_history_is_capped() {
+        let mut history = vec![];
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        for day in 0..STATS_HISTORY_LIMIT + 5 {
+            push_stats_snapshot(&mut history, stats_with(day), start + Duration::days(day as i64));
+        }
+        assert_eq!(history.len(), STATS_HISTORY_LIMIT);
+        assert_eq!(history[0].stats.total_tombstones, 5);
+        assert_eq!(history.last().unwrap().stats.total_tombstones, STATS_HISTORY_LIMIT + 4);
+    }
+
+
+    // ========== 改名识别 ==========
+
+    fn sized(id: &str, location: &str, alive: bool, line_count: usize) -> Asset {
+        Asset { line_count, ..asset(id, location, alive) }
+    }
+
+    #[test]
+    fn detect_renames_respects_the_threshold() {
+        // 文件名编辑距离 1 / 10、行数相同：0.6 * 0.9 + 0.4 = 0.94
+        let assets = vec![sized("a", "src/helpers.rs", false, 100), sized("b", "src/helper.rs", true, 100)];
+        assert_eq!(
+            detect_renames(&assets, 0.9),
+            [("src/helpers.rs".to_string(), "src/helper.rs".to_string())]
+        );
+        assert!(detect_renames(&assets, 0.95).is_empty());
+
+        // 行数差太多时同名也不算移动：0.6 + 0.4 * 0.1 = 0.64
+        let shrunk = vec![sized("a", "src/parser.rs", false, 100), sized("b", "lib/parser.rs", true, 10)];
+        assert!(detect_renames(&shrunk, DEFAULT_RENAME_SIMILARITY_THRESHOLD).is_empty());
+    }
+
+    #[test]
+    fn detect_renames_claims_each_live_asset_once() {
+        let assets = vec![
+            sized("a", "old/parser.rs", false, 100),
+            sized("b", "legacy/parser.rs", false, 100),
+            sized("c", "src/parser.rs", true, 100),
+        ];
+        let moves = detect_renames(&assets, DEFAULT_RENAME_SIMILARITY_THRESHOLD);
+        assert_eq!(moves, [("old/parser.rs".to_string(), "src/parser.rs".to_string())]);
+
+        // 没被认领的那个照常立碑
+        let _sb = sandbox();
+        let buried = bury_dead_assets(&assets, DEFAULT_RENAME_SIMILARITY_THRESHOLD, &[], true).unwrap();
+        let paths: Vec<&str> = buried.iter().map(|t| t.original_path.as_str()).collect();
+        assert_eq!(paths, ["legacy/parser.rs"]);
+    }
+
+    #[test]
+    fn remote_assets_are_buried_under_their_repo_path() {
+        let _sb = sandbox();
+        let repo: GhRepo = serde_json::from_value(serde_json::json!({"name": "api", "full_name": "acme/api"})).unwrap();
+        let entry = GhTreeEntry {
+            path: "src/legacy.rs".to_string(),
+            kind: "blob".to_string(),
+            sha: "abc123".to_string(),
+            size: Some(400),
+        };
+        let mut dead = remote_asset(&repo, &entry);
+        dead.alive = false;
+        assert_eq!(dead.location, "https://github.com/acme/api/blob/HEAD/src/legacy.rs");
+
+        let buried = bury_dead_assets(&[dead.clone()], DEFAULT_RENAME_SIMILARITY_THRESHOLD, &[], true).unwrap();
+        assert_eq!(buried.len(), 1);
+        assert_eq!(buried[0].original_path, "src/legacy.rs");
+        assert_eq!(buried[0].repo.as_deref(), Some("acme/api"));
+        // 旧版本存下的 URL 形式的墓碑得到同一个 id，不会重复立碑
+        assert_eq!(buried[0].id, tombstone_id_for(Some("acme/api"), &dead.location));
+
+        // 以 / 开头的忽略规则按仓库根目录匹配远程文件
+        let config = Config {
+            ignore_globs: vec!["/generated/".to_string()],
+            ..Config::default()
+        };
+        let rules = load_ignore_rules(&config).unwrap();
+        assert!(is_ignored(&rules, "https://github.com/acme/api/blob/HEAD/generated/api.rs"));
+        assert!(!is_ignored(&rules, "https://github.com/acme/api/blob/HEAD/src/generated/api.rs"));
+    }
+
+    #[test]
+    fn scan_burial_buries_each_repo_as_it_is_merged() {
+        let _sb = sandbox();
+        let remote = |repo: &str, path: &str, sha: &str| Asset {
+            repo: Some(repo.to_string()),
+            blob_sha: Some(sha.to_string()),
+            ..asset(path, &format!("https://github.com/{}/blob/HEAD/{}", repo, path), true)
+        };
+        write_json_atomic(
+            &get_asset_index_path(),
+            &vec![
+                remote("acme/api", "src/gone.rs", "111"),
+                remote("acme/api", "src/moved.rs", "222"),
+                remote("acme/web", "src/old.rs", "333"),
+            ],
+        )
+        .unwrap();
+        let buried_paths = || {
+            let mut paths: Vec<String> = read_tombstones().unwrap().into_iter().map(|t| t.original_path).collect();
+            paths.sort();
+            paths
+        };
+
+        let mut burial = ScanBurial::new(&Config::default(), false).unwrap();
+        // web 先遍历完：只为 web 里消失的文件立碑，api 的文件还不受影响
+        let in_web = |location: &str| location.starts_with("https://github.com/acme/web/");
+        burial.merge(vec![remote("acme/web", "lib/moved.rs", "222")], in_web).unwrap();
+        burial.bury(in_web).unwrap();
+        assert_eq!(buried_paths(), ["src/old.rs"]);
+
+        // api 遍历完：moved.rs 的 sha 已在 web 中出现，视为移动而不是死亡
+        let in_api = |location: &str| location.starts_with("https://github.com/acme/api/");
+        burial.merge(vec![], in_api).unwrap();
+        burial.bury(in_api).unwrap();
+        assert_eq!(buried_paths(), ["src/gone.rs", "src/old.rs"]);
+        assert_eq!((burial.newly_dead, burial.buried.len()), (2, 2));
+
+        // 整体补扫不会重复计数
+        burial.bury(|_: &str| true).unwrap();
+        assert_eq!(burial.buried.len(), 2);
+        let locations: Vec<String> = read_assets().unwrap().into_iter().map(|a| a.location).collect();
+        assert!(!locations.iter().any(|l| l.ends_with("src/moved.rs")), "{:?}", locations);
+    }
+
+
+    // ========== 忽略规则 ==========
+
+    #[test]
+    fn cemeteryignore_extends_and_overrides_config_globs() {
+        let sb = sandbox();
+        fs::write(
+            get_cemetery_ignore_path(),
+            "# 快照和第三方代码\n*.snap\nvendor/\n!important.log\n",
+        )
+        .unwrap();
+        let config = Config {
+            ignore_globs: vec!["generated/".to_string(), "*.log".to_string()],
+            ..Config::default()
+        };
+        let rules = load_ignore_rules(&config).unwrap();
+
+        for ignored in ["generated/api.rs", "src/__snapshots__/view.snap", "vendor/lib/x.rs", "logs/debug.log"] {
+            assert!(is_ignored(&rules, ignored), "{}", ignored);
+        }
+        for kept in ["src/main.rs", "./src/vendor.rs", "logs/important.log"] {
+            assert!(!is_ignored(&rules, kept), "{}", kept);
+        }
+
+        // 绝对路径：项目内的按相对路径匹配，项目外的不受约束
+        assert!(is_ignored(&rules, &sb.base.join("generated/api.rs").to_string_lossy()));
+        assert!(!is_ignored(&rules, "/elsewhere/generated/api.rs"));
+    }
+
+
+    // ========== 批量复活 ==========
+
+    #[test]
+    fn bulk_resurrect_applies_what_it_can() {
+        let _sb = sandbox();
+        let mut already = tombstone("t2", "acme/api", "src/b.rs");
+        already.resurrected_at = Some("2024-01-01T00:00:00+00:00".to_string());
+        already.resurrected_to = Some("src/earlier.rs".to_string());
+        write_tombstones(&[tombstone("t1", "acme/api", "src/a.rs"), already]).unwrap();
+
+        let mapping = vec![
+            ("t1".to_string(), "src/new_a.rs".to_string()),
+            ("t2".to_string(), "src/new_b.rs".to_string()),
+            ("missing".to_string(), "src/new_c.rs".to_string()),
+        ];
+        assert_eq!(bulk_resurrect(mapping).unwrap(), 1);
+
+        let tombstones = read_tombstones().unwrap();
+        assert_eq!(tombstones[0].resurrected_to.as_deref(), Some("src/new_a.rs"));
+        assert!(tombstones[0].resurrected_at.is_some());
+        // 已复活的保持原样
+        assert_eq!(tombstones[1].resurrected_to.as_deref(), Some("src/earlier.rs"));
+
+        // 全部跳过时不写注册表也不压撤销栈
+        assert_eq!(bulk_resurrect(vec![("missing".to_string(), "x".to_string())]).unwrap(), 0);
+        undo_last_change().unwrap();
+        assert!(read_tombstones().unwrap()[0].resurrected_at.is_none());
+        assert!(undo_last_change().is_err());
+    }
+
+
+    // ========== 配置历史 ==========
+
+    #[test]
+    fn config_history_records_masked_token_changes() {
+        let _sb = sandbox();
+        let first = Config { github_token: Some("ghp_aaaaaaaaaaaa1111".to_string()), ..Config::default() };
+        save_config(&first).unwrap();
+        save_config(&Config { github_token: Some("ghp_bbbbbbbbbbbb2222".to_string()), ..first.clone() }).unwrap();
+        // 没有变化时不记录
+        save_config(&Config { github_token: Some("ghp_bbbbbbbbbbbb2222".to_string()), ..first }).unwrap();
+
+        let history = get_config_history(10).unwrap();
+        assert_eq!(history.len(), 2);
+        let latest = &history[0].changes;
+        assert_eq!(latest.len(), 1);
+        assert_eq!(latest[0].field, "github_token");
+        assert_eq!(latest[0].before, "ghp_****1111");
+        assert_eq!(latest[0].after, "ghp_****2222");
+
+        let raw = fs::read_to_string(get_config_history_path()).unwrap();
+        assert!(!raw.contains("aaaaaaaa") && !raw.contains("bbbbbbbb"));
+    }
+
+
+    // ========== SARIF 报告 ==========
+
+    #[test]
+    fn sarif_report_describes_each_alert() {
+        let _sb = sandbox();
+        let mut weak = alert("z2", "acme/api", "src/b.rs");
+        weak.confidence = 0.5;
+        weak.zombie_path = "./lib/other.rs".to_string();
+        write_alerts(vec![alert("z1", "acme/api", "src/old.rs"), weak]);
+
+        let report: serde_json::Value = serde_json::from_str(&export_alerts_report("SARIF".to_string()).unwrap()).unwrap();
+        assert_eq!(report["version"], "2.1.0");
+        assert_eq!(report["$schema"], SARIF_SCHEMA);
+        let run = &report["runs"][0];
+        assert_eq!(run["tool"]["driver"]["name"], "code-corpses");
+        assert_eq!(run["tool"]["driver"]["rules"][0]["id"], SARIF_RULE_ID);
+
+        let results = run["results"].as_array().unwrap();
+        assert_eq!(results.len(), 2);
+        let first = &results[0];
+        assert_eq!(first["ruleId"], SARIF_RULE_ID);
+        assert_eq!(first["level"], "warning");
+        assert_eq!(first["locations"][0]["physicalLocation"]["artifactLocation"]["uri"], "lib/copied.rs");
+        assert_eq!(first["locations"][0]["logicalLocations"][0]["name"], "acme/web");
+        assert_eq!(first["partialFingerprints"]["zombieAlertId"], "z1");
+        assert_eq!(first["properties"]["corpsePath"], "src/old.rs");
+        assert!(first["message"]["text"].as_str().unwrap().contains("92%"));
+        assert_eq!(results[1]["level"], "note");
+        assert_eq!(results[1]["locations"][0]["physicalLocation"]["artifactLocation"]["uri"], "lib/other.rs");
+
+        assert!(export_alerts_report("xml".to_string()).is_err());
+    }
+
+    #[test]
+    fn validate_sarif_requires_message_and_rule() {
+        let mut sarif = build_sarif(&[alert("z1", "acme/api", "src/old.rs")]);
+        assert!(validate_sarif(&sarif).is_ok());
+        sarif["runs"][0]["results"][0]["ruleId"] = serde_json::Value::Null;
+        assert!(validate_sarif(&sarif).is_err());
+    }
+
+
+    // ========== 多组织调度 ==========
+
+    #[test]
+    fn scheduled_orgs_dedupes_and_clamps_intervals() {
+        let config = Config {
+            target_org: "Acme".to_string(),
+            scan_interval: 7200,
+            orgs: vec![
+                OrgConfig { org: "acme".to_string(), interval_secs: Some(600) },
+                OrgConfig { org: "tiny".to_string(), interval_secs: Some(1) },
+                OrgConfig { org: "  ".to_string(), interval_secs: None },
+                OrgConfig { org: "globex".to_string(), interval_secs: None },
+                OrgConfig { org: "huge".to_string(), interval_secs: Some(u64::MAX) },
+            ],
+            ..Config::default()
+        };
+        assert_eq!(
+            scheduled_orgs(&config),
+            [
+                ("acme".to_string(), 600),
+                ("tiny".to_string(), MIN_SCAN_INTERVAL_SECS),
+                ("globex".to_string(), 7200),
+                ("huge".to_string(), MAX_SCAN_INTERVAL_SECS),
+            ]
+        );
+    }
+
+    #[test]
+    fn next_due_org_picks_the_earliest_then_by_name() {
+        let at = Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap();
+        let mut next_runs = HashMap::new();
+        assert_eq!(next_due_org(&next_runs), None);
+
+        next_runs.insert("globex".to_string(), at + Duration::minutes(5));
+        next_runs.insert("initech".to_string(), at);
+        next_runs.insert("acme".to_string(), at);
+        assert_eq!(next_due_org(&next_runs), Some(("acme".to_string(), at)));
+
+        next_runs.remove("acme");
+        assert_eq!(next_due_org(&next_runs), Some(("initech".to_string(), at)));
+    }
+
+
+    // ========== 扫描间隔 ==========
+
+    #[test]
+    fn humanize_interval_picks_the_largest_whole_unit() {
+        assert_eq!(humanize_interval(7200), (2, "hours".to_string()));
+        assert_eq!(humanize_interval(172800), (2, "days".to_string()));
+        assert_eq!(humanize_interval(300), (5, "minutes".to_string()));
+        // 除不尽任何单位时退回秒
+        assert_eq!(humanize_interval(90), (90, "seconds".to_string()));
+        assert_eq!(humanize_interval(0), (0, "seconds".to_string()));
+    }
+
+    #[test]
+    fn set_scan_interval_converts_units_and_clamps() {
+        let _sb = sandbox();
+        assert_eq!(interval_unit_secs("hours"), Ok(3600));
+        assert!(interval_unit_secs("fortnights").is_err());
+        assert!(interval_unit_secs("Hours").is_err());
+
+        assert_eq!(set_scan_interval(2, "hours".to_string()), Ok(7200));
+        assert_eq!(load_config().unwrap().scan_interval, 7200);
+        assert_eq!(set_scan_interval(0, "minutes".to_string()), Ok(MIN_SCAN_INTERVAL_SECS));
+        assert_eq!(set_scan_interval(u64::MAX, "days".to_string()), Ok(MAX_SCAN_INTERVAL_SECS));
+
+        // 单位不认识时报错，已保存的间隔不变
+        assert!(set_scan_interval(5, "weeks".to_string()).is_err());
+        assert_eq!(load_config().unwrap().scan_interval, MAX_SCAN_INTERVAL_SECS);
+    }
+
+
+    // ========== 豁免 ==========
+
+    #[test]
+    fn exempt_deleted_assets_are_not_buried() {
+        let _sb = sandbox();
+        write_json_atomic(
+            &get_asset_index_path(),
+            &vec![asset("fixture", "tests/fixtures/sample.rs", false), asset("real", "src/legacy_parser.rs", false)],
+        )
+        .unwrap();
+        set_asset_exempt("fixture".to_string(), true).unwrap();
+        assert!(set_asset_exempt("missing".to_string(), true).is_err());
+
+        let buried = bury_from_index();
+        let paths: Vec<&str> = buried.iter().map(|t| t.original_path.as_str()).collect();
+        assert_eq!(paths, ["src/legacy_parser.rs"]);
+
+        // 取消豁免后照常立碑
+        set_asset_exempt("fixture".to_string(), false).unwrap();
+        let buried = bury_from_index();
+        assert_eq!(buried.len(), 1);
+        assert_eq!(buried[0].original_path, "tests/fixtures/sample.rs");
+    }
+
+
+    // ========== 内容哈希 ==========
+
+    #[test]
+    fn content_hash_ignores_whitespace_only() {
+        let a = content_hash("fn main() {\n    run();\n}\n");
+        assert_eq!(a, content_hash("fn main() {\trun();   }"));
+        assert_eq!(a.len(), 64);
+        assert_ne!(a, content_hash("fn main() { stop(); }"));
+        // 空白只作分隔，不能把相邻的词粘在一起
+        assert_ne!(content_hash("a b"), content_hash("ab"));
+    }
+
+    #[test]
+    fn find_by_hash_matches_live_and_dead_assets() {
+        let sb = sandbox();
+        let live_file = sb.base.join("live.rs");
+        fs::write(&live_file, "fn parse() {}\n").unwrap();
+        let live = asset("live", &live_file.to_string_lossy(), true);
+        let dead = Asset { content_hash: Some(content_hash("fn  parse()  {}")), ..asset("dead", "src/old.rs", false) };
+        let other = Asset { content_hash: Some(content_hash("fn other() {}")), ..asset("other", "src/other.rs", true) };
+        write_json_atomic(&get_asset_index_path(), &vec![live, dead, other]).unwrap();
+        assert_eq!(refresh_asset_hashes().unwrap(), 1);
+
+        let hash = content_hash("fn parse() {}");
+        let mut ids: Vec<String> = find_by_hash(hash.to_uppercase()).into_iter().map(|a| a.id).collect();
+        ids.sort();
+        assert_eq!(ids, ["dead", "live"]);
+        assert!(find_by_hash("  ".to_string()).is_empty());
+    }
+
+
+    // ========== 墓地评分 ==========
+
+    #[test]
+    fn graveyard_score_breaks_down_into_components() {
+        let with = |id: &str, language: Option<&str>, line_count: usize| Tombstone {
+            language: language.map(String::from),
+            line_count,
+            ..tombstone(id, "acme/api", &format!("src/{}", id))
+        };
+        let revived = Tombstone {
+            resurrected_at: Some("2024-02-01T00:00:00+00:00".to_string()),
+            ..with("revived", Some("Rust"), 500)
+        };
+        let tombstones = [with("a", Some("Rust"), 100), with("b", Some("Go"), 44), with("c", None, 0), revived];
+
+        let score = compute_graveyard_score(&tombstones);
+        // 3 具尸体 × 10
+        assert_eq!((score.corpses, score.corpse_points), (3, 30));
+        // 复活的不计入死亡行数：√144 × 2
+        assert_eq!((score.dead_lines, score.line_points), (144, 24));
+        // 语言按全部墓碑算，没有语言的记为 Unknown：Rust / Go / Unknown
+        assert_eq!((score.languages, score.diversity_points), (3, 75));
+        // 复活率 1/4 × 200
+        assert_eq!(score.resurrection_rate, 0.25);
+        assert_eq!(score.resurrection_points, 50);
+        assert_eq!(score.total, 179);
+        assert_eq!(score.rank, "Gravekeeper");
+    }
+
+    #[test]
+    fn graveyard_rank_boundaries() {
+        assert_eq!(graveyard_rank(99), "Gravedigger");
+        assert_eq!(graveyard_rank(100), "Gravekeeper");
+        assert_eq!(graveyard_rank(499), "Gravekeeper");
+        assert_eq!(graveyard_rank(500), "Undertaker");
+        assert_eq!(graveyard_rank(4999), "Necromancer");
+        assert_eq!(graveyard_rank(5000), "Lich King");
+
+        // 单个墓碑：10 + 25 + round(√lines × 2)，1024 行正好 99 分，1057 行 100 分
+        let single = |line_count: usize| {
+            let score = compute_graveyard_score(&[Tombstone { line_count, ..tombstone("t", "acme/api", "src/a.rs") }]);
+            (score.total, score.rank)
+        };
+        assert_eq!(single(1024), (99, "Gravedigger".to_string()));
+        assert_eq!(single(1057), (100, "Gravekeeper".to_string()));
+    }
+
+    #[test]
+    fn graveyard_score_of_an_empty_registry_is_zero() {
+        let score = compute_graveyard_score(&[]);
+        assert_eq!(score.total, 0);
+        assert_eq!(score.rank, "Gravedigger");
+        assert_eq!((score.corpses, score.dead_lines, score.languages), (0, 0, 0));
+        assert_eq!(score.resurrection_rate, 0.0);
+    }
+
+
+    // ========== 分享卡片 ==========
+
+    #[test]
+    fn graveyard_card_is_a_stable_png_of_the_card_size() {
+        let _sb = sandbox();
+        write_tombstones(&[tombstone("t1", "acme/api", "src/old.rs"), tombstone("t2", "acme/web", "lib/gone.ts")])
+            .unwrap();
+        let png = generate_graveyard_card().unwrap();
+        assert_eq!(png, generate_graveyard_card().unwrap());
+
+        let pixmap = tiny_skia::Pixmap::decode_png(&png).unwrap();
+        assert_eq!((pixmap.width(), pixmap.height()), (CARD_WIDTH, CARD_HEIGHT));
+        // 标题确实画上了：标题区域里有不是背景色的像素
+        let background = tiny_skia::PremultipliedColorU8::from_rgba(30, 27, 46, 255).unwrap();
+        assert!((36..84).any(|y| (40..400).any(|x| pixmap.pixel(x, y) != Some(background))));
+    }
+
+
+    // ========== Atom 订阅 ==========
+
+    #[test]
+    fn export_feed_is_well_formed_atom() {
+        let _sb = sandbox();
+        let mut tricky = tombstone("t<1>", "acme/api", "src/a&b.rs");
+        tricky.name = "a&b <\"quoted\">".to_string();
+        tricky.epitaph = "R.I.P. \u{1} & farewell".to_string();
+        let mut older = tombstone("t2", "acme/api", "src/old.rs");
+        older.died_at = "2020-01-01T00:00:00+00:00".to_string();
+        let mut undated = tombstone("t3", "acme/api", "src/undated.rs");
+        undated.died_at = "sometime".to_string();
+        write_tombstones(&[older, tricky, undated]).unwrap();
+
+        let xml = export_feed(10).unwrap();
+        let doc = roxmltree::Document::parse(&xml).unwrap();
+        let feed = doc.root_element();
+        assert_eq!(feed.tag_name().name(), "feed");
+        assert_eq!(feed.tag_name().namespace(), Some("http://www.w3.org/2005/Atom"));
+
+        // 死亡时间无法解析的墓碑不进 feed；其余按死亡时间倒序
+        let entries: Vec<_> = feed.children().filter(|n| n.has_tag_name("entry")).collect();
+        assert_eq!(entries.len(), 2);
+        let text = |entry: &roxmltree::Node, tag: &str| {
+            entry.children().find(|n| n.has_tag_name(tag)).and_then(|n| n.text()).unwrap_or_default().to_string()
+        };
+        assert_eq!(text(&entries[0], "title"), "a&b <\"quoted\">");
+        assert_eq!(text(&entries[0], "id"), "urn:code-corpses:tombstone:t<1>");
+        assert_eq!(text(&entries[1], "id"), "urn:code-corpses:tombstone:t2");
+        let updated = feed.children().find(|n| n.has_tag_name("updated")).and_then(|n| n.text()).unwrap();
+        assert_eq!(updated, text(&entries[0], "published"));
+
+        let limited = export_feed(1).unwrap();
+        let doc = roxmltree::Document::parse(&limited).unwrap();
+        assert_eq!(doc.descendants().filter(|n| n.has_tag_name("entry")).count(), 1);
+    }
+
+
+    // ========== 资产去重 ==========
+
+    #[test]
+    fn dedupe_assets_keeps_the_newest_entry_per_location() {
+        let _sb = sandbox();
+        let index = serde_json::json!([
+            { "id": "old", "location": "src/a.rs", "updatedAt": "2024-01-01T00:00:00Z" },
+            { "id": "new", "location": "./src/a.rs", "updated_at": "2024-03-01T00:00:00Z" },
+            { "id": "mid", "location": "src\\a.rs", "indexedAt": "2024-02-01T00:00:00Z" },
+            { "id": "b1", "location": "src/b.rs" },
+            { "id": "b2", "location": "src/b.rs" },
+            { "id": "solo", "location": "src/c.rs" },
+            { "id": "nowhere" },
+        ]);
+        write_json_atomic(&get_asset_index_path(), &index).unwrap();
+
+        let groups = find_duplicate_assets().unwrap();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].location, "src/a.rs");
+        assert_eq!(groups[0].count, 3);
+        assert_eq!(groups[0].ids, ["old", "new", "mid"]);
+        assert_eq!(groups[1].ids, ["b1", "b2"]);
+
+        assert_eq!(dedupe_assets().unwrap(), 3);
+        let ids: Vec<String> = read_asset_values()
+            .unwrap()
+            .iter()
+            .map(|a| a["id"].as_str().unwrap().to_string())
+            .collect();
+        // 时间相同（都缺失）时保留靠后的；没有 location 的原样保留
+        assert_eq!(ids, ["new", "b2", "solo", "nowhere"]);
+        assert!(find_duplicate_assets().unwrap().is_empty());
+        assert_eq!(dedupe_assets().unwrap(), 0);
+    }
+
+
+    // ========== 时间显示 ==========
+
+    #[test]
+    fn format_ts_follows_the_display_timezone() {
+        let dt = Utc.with_ymd_and_hms(2024, 1, 1, 20, 30, 0).unwrap();
+        assert_eq!(format_ts_in(&DisplayTz::Utc, dt), "2024-01-01 20:30:00 +00:00");
+        assert_eq!(
+            format_ts_in(&DisplayTz::Named("Asia/Shanghai".to_string()), dt),
+            "2024-01-02 04:30:00 +08:00"
+        );
+        assert_eq!(
+            format_ts_in(&DisplayTz::Named("America/New_York".to_string()), dt),
+            "2024-01-01 15:30:00 -05:00"
+        );
+        assert_eq!(
+            format_ts_in(&DisplayTz::Local, dt),
+            dt.with_timezone(&chrono::Local).format(DISPLAY_TS_FORMAT).to_string()
+        );
+        // 无效时区退回 UTC
+        assert_eq!(format_ts_in(&DisplayTz::Named("Mars/Olympus".to_string()), dt), "2024-01-01 20:30:00 +00:00");
+
+        // 按天分桶同样换算到显示时区
+        let shanghai = DisplayTz::Named("Asia/Shanghai".to_string());
+        assert_eq!(date_in(&shanghai, dt), NaiveDate::from_ymd_opt(2024, 1, 2).unwrap());
+        assert_eq!(date_in(&DisplayTz::Utc, dt), NaiveDate::from_ymd_opt(2024, 1, 1).unwrap());
+    }
+
+    #[test]
+    fn set_display_timezone_changes_format_ts() {
+        let _sb = sandbox();
+        let dt = Utc.with_ymd_and_hms(2024, 7, 1, 12, 0, 0).unwrap();
+        assert_eq!(format_ts(dt), "2024-07-01 12:00:00 +00:00");
+
+        set_display_timezone(DisplayTz::Named("Europe/Berlin".to_string())).unwrap();
+        assert_eq!(format_ts(dt), "2024-07-01 14:00:00 +02:00");
+        assert!(set_display_timezone(DisplayTz::Named("Mars/Olympus".to_string())).is_err());
+        assert_eq!(format_ts(dt), "2024-07-01 14:00:00 +02:00");
+    }
+
+
+    // ========== 系统通知 ==========
+
+    #[test]
+    fn notifications_wait_for_cooldown_and_snooze() {
+        let now = Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap();
+        let mut state = NotificationState::default();
+        assert_eq!(notification_delay(&state, 60, now), None);
+
+        state.last_sent = Some(now - Duration::seconds(20));
+        assert_eq!(notification_delay(&state, 60, now), Some(Duration::seconds(40)));
+        assert_eq!(notification_delay(&state, 10, now), None);
+
+        // 勿扰比冷却结束得晚时以勿扰为准
+        state.muted_until = snooze_deadline(30, now);
+        assert_eq!(notification_delay(&state, 60, now), Some(Duration::minutes(30)));
+        assert_eq!(notification_delay(&state, 60, now + Duration::minutes(30)), None);
+    }
+
+    #[test]
+    fn snooze_deadline_is_capped_and_zero_unmutes() {
+        let now = Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap();
+        assert_eq!(snooze_deadline(0, now), None);
+        assert_eq!(snooze_deadline(90, now), Some(now + Duration::minutes(90)));
+        assert_eq!(snooze_deadline(u64::MAX, now), Some(now + Duration::days(7)));
+    }
+
+
+    // ========== 自动标签 ==========
+
+    #[test]
+    fn inferred_tags_from_path_patterns() {
+        let cases: &[(&str, Option<&str>, &[&str])] = &[
+            ("src/__tests__/Button.test.tsx", Some("TypeScript"), &["test", "typescript"]),
+            ("docs/setup.md", None, &["docs"]),
+            ("tests/fixtures/sample.json", None, &["test", "fixture"]),
+            ("pkg/parser_test.go", Some(" Go "), &["test", "go"]),
+            ("config/.eslintrc", None, &["config"]),
+            ("deploy/app.yaml", None, &["config"]),
+            ("apps/vue-admin/src/main.ts", None, &["vue"]),
+            ("vendor\\jquery\\jquery.min.js", None, &["vendor", "jquery"]),
+            ("node_modules/react/index.js", Some("JavaScript"), &["vendor", "react", "javascript"]),
+            // 只按整段目录名 / 整词匹配
+            ("lib/expression.js", None, &[]),
+            ("src/protest/run.rs", None, &[]),
+        ];
+        for (path, language, expected) in cases {
+            assert_eq!(inferred_tags(path, *language), *expected, "{}", path);
+        }
+    }
+
+
+    // ========== HTTP 客户端 ==========
+
+    #[test]
+    fn request_settings_fall_back_and_clamp() {
+        let config = Config { user_agent: Some("  ".to_string()), ..Config::default() };
+        assert_eq!(request_user_agent(&config), DEFAULT_USER_AGENT);
+        let config = Config { user_agent: Some(" corp-proxy-ok/1.0 ".to_string()), ..Config::default() };
+        assert_eq!(request_user_agent(&config), "corp-proxy-ok/1.0");
+
+        let timeout = |secs: u64| request_timeout(&Config { request_timeout_secs: secs, ..Config::default() }).as_secs();
+        assert_eq!(timeout(0), MIN_REQUEST_TIMEOUT_SECS);
+        assert_eq!(timeout(45), 45);
+        assert_eq!(timeout(u64::MAX), MAX_REQUEST_TIMEOUT_SECS);
+    }
+
+    // 本地假服务器：接受一个连接，读完请求头后交给 respond 决定是否应答，返回收到的请求头
+    fn one_shot_server(respond: bool) -> (String, std::thread::JoinHandle<String>) {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/orgs/acme", listener.local_addr().unwrap());
+        let handle = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut head = Vec::new();
+            let mut buf = [0u8; 1024];
+            while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+                let n = stream.read(&mut buf).unwrap();
+                if n == 0 {
+                    break;
+                }
+                head.extend_from_slice(&buf[..n]);
+            }
+            if respond {
+                stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\n{}").unwrap();
+            } else {
+                // 不应答，直到客户端超时断开
+                let _ = stream.read(&mut buf);
+            }
+            String::from_utf8_lossy(&head).to_lowercase()
+        });
+        (url, handle)
+    }
+
+    #[tokio::test]
+    async fn github_client_sends_the_configured_user_agent() {
+        let (url, server) = one_shot_server(true);
+        let config = Config { user_agent: Some("corp-proxy-ok/1.0".to_string()), ..Config::default() };
+        let response = github_get(&github_client(&config).unwrap(), Some("ghp_test"), &url).send().await.unwrap();
+        assert!(response.status().is_success());
+
+        let head = server.join().unwrap();
+        assert!(head.contains("user-agent: corp-proxy-ok/1.0\r\n"), "{}", head);
+        assert!(head.contains("authorization: bearer ghp_test\r\n"), "{}", head);
+        assert!(head.contains("x-github-api-version: 2022-11-28\r\n"), "{}", head);
+    }
+
+    #[tokio::test]
+    async fn github_client_times_out_after_the_clamped_timeout() {
+        let (url, server) = one_shot_server(false);
+        // 1 秒会被提升到 MIN_REQUEST_TIMEOUT_SECS
+        let config = Config { request_timeout_secs: 1, ..Config::default() };
+        let started = std::time::Instant::now();
+        let error = github_get(&github_client(&config).unwrap(), None, &url).send().await.unwrap_err();
+        assert!(error.is_timeout(), "{}", error);
+        assert!(started.elapsed() >= std::time::Duration::from_secs(MIN_REQUEST_TIMEOUT_SECS));
+        server.join().unwrap();
+    }
+
+
+    // ========== 资产索引合并 ==========
+
+    #[test]
+    fn merge_asset_values_keeps_user_fields() {
+        let mut existing = vec![
+            serde_json::json!({
+                "id": "a1",
+                "location": "src/gen.rs",
+                "alive": true,
+                "lineCount": 10,
+                "line_count": 10,
+                "exempt": true,
+                "tags": ["generated"],
+                "content_hash": "abc",
+            }),
+            serde_json::json!({ "id": "a2", "location": "src/gone.rs", "alive": true, "exempt": true }),
+            serde_json::json!({ "id": "a3", "location": "other/out_of_scope.rs", "alive": true }),
+        ];
+        let fresh = vec![
+            Asset { line_count: 42, language: Some("Rust".to_string()), ..asset("x", "./src/gen.rs", true) },
+            asset("new", "src/new.rs", true),
+        ];
+        let summary = merge_asset_values(&mut existing, fresh, |location| location.starts_with("src/")).unwrap();
+        assert_eq!((summary.added, summary.updated, summary.marked_dead), (1, 1, 1));
+
+        let merged = &existing[0];
+        assert_eq!(merged["id"], "a1");
+        assert_eq!(merged["exempt"], true);
+        assert_eq!(merged["tags"], serde_json::json!(["generated"]));
+        assert_eq!(merged["content_hash"], "abc");
+        assert_eq!(merged["line_count"], 42);
+        assert_eq!(merged["lineCount"], 42);
+        assert_eq!(merged["language"], "Rust");
+
+        // 没扫到的豁免资产照样标记死亡，豁免标记保留（埋葬时跳过）
+        assert_eq!(existing[1]["alive"], false);
+        assert_eq!(existing[1]["exempt"], true);
+        // 扫描范围外的不动
+        assert_eq!(existing[2]["alive"], true);
+        assert_eq!(existing[3]["location"], "src/new.rs");
+        assert_eq!(existing[3]["exempt"], false);
+    }
+
+    #[test]
+    fn merge_asset_values_drops_the_hash_of_a_changed_blob() {
+        let blob = |id: &str, sha: &str| serde_json::json!({
+            "id": id,
+            "location": format!("https://github.com/acme/api/blob/HEAD/src/{}.rs", id),
+            "alive": true,
+            "blob_sha": sha,
+            "content_hash": "old",
+        });
+        let mut existing = vec![blob("same", "aaa"), blob("changed", "bbb")];
+        let remote = |id: &str, sha: &str| Asset {
+            blob_sha: Some(sha.to_string()),
+            ..asset(id, &format!("https://github.com/acme/api/blob/HEAD/src/{}.rs", id), true)
+        };
+        merge_asset_values(&mut existing, vec![remote("same", "aaa"), remote("changed", "ccc")], |_| true).unwrap();
+
+        assert_eq!(existing[0]["content_hash"], "old");
+        assert_eq!(existing[1]["blob_sha"], "ccc");
+        assert!(existing[1].get("content_hash").is_none());
+    }
+
+
+    // ========== 配置诊断 ==========
+
+    fn issue_fields(config: &Config) -> Vec<(String, Severity)> {
+        diagnose_config_value(config)
+            .into_iter()
+            .map(|issue| (issue.field, issue.severity))
+            .collect()
+    }
+
+    #[test]
+    fn diagnose_config_reports_each_issue() {
+        let sb = sandbox();
+        assert!(issue_fields(&Config::default()).is_empty());
+
+        let rule = |name: &str, path_glob: Option<&str>| DeathRule {
+            name: name.to_string(),
+            min_age_days: None,
+            min_lines: None,
+            max_lines: None,
+            path_glob: path_glob.map(String::from),
+            language: None,
+        };
+        let cases: Vec<(Config, &str, Severity)> = vec![
+            (Config { github_token: Some(" ".to_string()), ..Config::default() }, "github_token", Severity::Warning),
+            (Config { target_org: " ".to_string(), ..Config::default() }, "target_org", Severity::Error),
+            (Config { scan_interval: 5, ..Config::default() }, "scan_interval", Severity::Warning),
+            (Config { report_webhook: Some("ftp://hooks.example".to_string()), ..Config::default() }, "report_webhook", Severity::Error),
+            (Config { report_webhook: Some("not a url".to_string()), ..Config::default() }, "report_webhook", Severity::Error),
+            (
+                Config { epitaph_templates_path: Some("/nonexistent/epitaphs.json".to_string()), ..Config::default() },
+                "epitaph_templates_path",
+                Severity::Warning,
+            ),
+            (Config { ignore_globs: vec!["src/[".to_string()], ..Config::default() }, "ignore_globs", Severity::Error),
+            (Config { death_rules: vec![rule("empty", None)], ..Config::default() }, "death_rules", Severity::Warning),
+            (Config { death_rules: vec![rule("broken", Some("src/["))], ..Config::default() }, "death_rules", Severity::Error),
+        ];
+        for (config, field, severity) in cases {
+            assert_eq!(issue_fields(&config), [(field.to_string(), severity)], "{}", field);
+        }
+
+        fs::remove_dir_all(sb.cemetery()).unwrap();
+        assert_eq!(issue_fields(&Config::default()), [("cemetery_path".to_string(), Severity::Warning)]);
+    }
+
+
+    // ========== 作者统计 ==========
+
+    #[test]
+    fn author_stats_aggregate_by_last_author() {
+        let _sb = sandbox();
+        let by = |id: &str, author: Option<&str>, line_count: usize| Tombstone {
+            author: author.map(String::from),
+            line_count,
+            ..tombstone(id, "acme/api", &format!("src/{}.rs", id))
+        };
+        write_tombstones(&[
+            by("a", Some("alice"), 10),
+            by("b", Some(" bob "), 300),
+            by("c", Some("alice"), 20),
+            by("d", None, 5),
+            by("e", Some(""), 7),
+            by("f", Some("carol"), 300),
+        ])
+        .unwrap();
+
+        let stats: Vec<(String, usize, usize)> = get_author_stats()
+            .into_iter()
+            .map(|s| (s.author, s.tombstones, s.dead_lines))
+            .collect();
+        // 先按墓碑数、再按行数降序，都相同时按名字
+        assert_eq!(
+            stats,
+            [
+                ("alice".to_string(), 2, 30),
+                ("unknown".to_string(), 2, 12),
+                ("bob".to_string(), 1, 300),
+                ("carol".to_string(), 1, 300),
+            ]
+        );
+    }
+
+
+    // ========== 按标签统计 ==========
+
+    #[test]
+    fn tag_stats_only_count_tagged_entries() {
+        let _sb = sandbox();
+        let tagged = |a: Asset| Asset { tags: vec!["Legacy".to_string()], ..a };
+        write_json_atomic(
+            &get_asset_index_path(),
+            &vec![
+                tagged(asset("a1", "src/a.rs", true)),
+                tagged(asset("a2", "src/b.rs", false)),
+                asset("a3", "src/c.rs", true),
+                asset("a4", "src/d.rs", false),
+            ],
+        )
+        .unwrap();
+        let mut resurrected = tombstone("t2", "acme/api", "src/y.rs");
+        resurrected.resurrected_at = Some("2024-02-01T00:00:00+00:00".to_string());
+        resurrected.tags = vec!["legacy".to_string()];
+        write_tombstones(&[tombstone("t1", "acme/api", "src/x.rs"), resurrected]).unwrap();
+
+        let all = get_stats();
+        assert_eq!(
+            (all.total_assets, all.alive_assets, all.dead_assets, all.total_tombstones, all.resurrected),
+            (4, 2, 2, 2, 1)
+        );
+
+        let legacy = get_stats_for_tag(" legacy ".to_string());
+        assert_eq!(
+            (legacy.total_assets, legacy.alive_assets, legacy.dead_assets, legacy.total_tombstones, legacy.resurrected),
+            (2, 1, 1, 1, 1)
+        );
+        assert_eq!(legacy.last_scan, all.last_scan);
+
+        let none = get_stats_for_tag("missing".to_string());
+        assert_eq!((none.total_assets, none.total_tombstones), (0, 0));
+    }
+
+
+    // ========== 标签共现 ==========
+
+    #[test]
+    fn tag_cooccurrence_counts_each_pair_once_per_tombstone() {
+        let _sb = sandbox();
+        let tagged = |id: &str, tags: &[&str]| Tombstone {
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            ..tombstone(id, "acme/api", &format!("src/{}.rs", id))
+        };
+        write_tombstones(&[
+            // 大小写不同、重复的标签只算一个
+            tagged("t1", &["Legacy", "parser", " legacy ", "PARSER"]),
+            tagged("t2", &["parser", "legacy", "api"]),
+            // 不足两个标签（去重、去空之后）的墓碑不计入
+            tagged("t3", &["api"]),
+            tagged("t4", &["Api", "api", "  "]),
+            tagged("t5", &[]),
+        ])
+        .unwrap();
+
+        let pairs: Vec<(String, String, usize)> = get_tag_cooccurrence().into_iter().map(|p| (p.a, p.b, p.count)).collect();
+        let pair = |a: &str, b: &str, count: usize| (a.to_string(), b.to_string(), count);
+        // 每对内 a < b；按次数降序，次数相同按 a、b 排序
+        assert_eq!(pairs, [pair("legacy", "parser", 2), pair("api", "legacy", 1), pair("api", "parser", 1)]);
+    }
+
+
+    // ========== 语言熵 ==========
+
+    #[test]
+    fn shannon_entropy_of_uniform_and_single_distributions() {
+        assert!((shannon_entropy(&[3, 3, 3, 3]) - 2.0).abs() < 1e-12);
+        assert_eq!(shannon_entropy(&[7]), 0.0);
+        // 计数为 0 的类别不影响结果
+        assert_eq!(shannon_entropy(&[7, 0]), 0.0);
+        assert_eq!(shannon_entropy(&[]), 0.0);
+    }
+
+    #[test]
+    fn language_entropy_counts_missing_languages_as_unknown() {
+        let _sb = sandbox();
+        let in_language = |id: &str, language: Option<&str>| Tombstone {
+            language: language.map(String::from),
+            ..tombstone(id, "acme/api", &format!("src/{}", id))
+        };
+        write_tombstones(&[in_language("a", Some("Rust")), in_language("b", Some("Go")), in_language("c", Some("TypeScript")), in_language("d", None)])
+            .unwrap();
+
+        let entropy = get_language_entropy().unwrap();
+        assert!((entropy.entropy - 2.0).abs() < 1e-12);
+        let languages: Vec<&str> = entropy.shares.iter().map(|s| s.language.as_str()).collect();
+        assert_eq!(languages, ["Go", "Rust", "TypeScript", "Unknown"]);
+        assert!(entropy.shares.iter().all(|s| s.count == 1 && s.probability == 0.25));
+
+        // 全是同一种语言（都没有语言信息）时熵为 0
+        write_tombstones(&[in_language("a", None), in_language("b", Some("  "))]).unwrap();
+        let entropy = get_language_entropy().unwrap();
+        assert_eq!(entropy.entropy, 0.0);
+        assert_eq!(entropy.shares.len(), 1);
+        assert_eq!((entropy.shares[0].language.as_str(), entropy.shares[0].count), ("Unknown", 2));
+    }
+
+
+    // ========== 复活间隔 ==========
+
+    fn revived(id: &str, died_at: &str, resurrected_at: &str) -> Tombstone {
+        Tombstone {
+            died_at: died_at.to_string(),
+            resurrected_at: Some(resurrected_at.to_string()),
+            ..tombstone(id, "acme/api", &format!("src/{}.rs", id))
+        }
+    }
+
+    #[test]
+    fn longest_resurrections_skip_bad_dates_and_order_ties_by_id() {
+        let _sb = sandbox();
+        write_tombstones(&[
+            revived("b-short", "2024-01-01T00:00:00+00:00", "2024-01-11T00:00:00+00:00"),
+            revived("z-long", "2024-01-01T00:00:00+00:00", "2024-04-10T00:00:00+00:00"),
+            // 与 z-long 同为 100 天，按 id 排在前面
+            revived("a-long", "2023-01-01T00:00:00+00:00", "2023-04-11T00:00:00+00:00"),
+            revived("garbled", "last tuesday", "2024-01-11T00:00:00+00:00"),
+            revived("backwards", "2024-03-01T00:00:00+00:00", "2024-02-01T00:00:00+00:00"),
+            tombstone("still-dead", "acme/api", "src/dead.rs"),
+        ])
+        .unwrap();
+
+        let all: Vec<(String, i64)> =
+            get_longest_resurrections(10).into_iter().map(|r| (r.tombstone_id, r.days_dead)).collect();
+        assert_eq!(
+            all,
+            [("a-long".to_string(), 100), ("z-long".to_string(), 100), ("b-short".to_string(), 10)]
+        );
+
+        let top: Vec<String> = get_longest_resurrections(1).into_iter().map(|r| r.tombstone_id).collect();
+        assert_eq!(top, ["a-long"]);
+        assert!(get_longest_resurrections(0).is_empty());
+        assert!(get_longest_resurrections(-3).is_empty());
+    }
+
+    #[test]
+    fn resurrection_halflife_takes_the_median_gap() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let after_hours = |id: &str, hours: i64| {
+            revived(id, &start.to_rfc3339(), &(start + Duration::hours(hours)).to_rfc3339())
+        };
+        let mut tombstones: Vec<Tombstone> =
+            [24, 48, 72, 96].iter().enumerate().map(|(i, &h)| after_hours(&format!("t{}", i), h)).collect();
+        // 无法解析、复活早于死亡、尚未复活的都不算样本
+        tombstones.push(revived("garbled", "soon", "2024-01-02T00:00:00+00:00"));
+        tombstones.push(after_hours("backwards", -24));
+        tombstones.push(tombstone("still-dead", "acme/api", "src/dead.rs"));
+        assert_eq!(resurrection_halflife(&tombstones), None);
+
+        // 5 个样本（奇数）：取正中间，1 2 3 4 10 → 3 天
+        tombstones.push(after_hours("t4", 240));
+        assert_eq!(resurrection_halflife(&tombstones), Some(3.0));
+
+        // 6 个样本（偶数）：取中间两个的平均，间隔按小时折算 → (3 + 3.5) / 2
+        tombstones.push(after_hours("t5", 84));
+        assert_eq!(resurrection_halflife(&tombstones), Some(3.25));
+    }
+
+
+    // ========== 配额历史 ==========
+
+    fn quota_point(at: DateTime<Utc>, remaining: u64, reset_at: &str) -> QuotaPoint {
+        QuotaPoint {
+            recorded_at: at.to_rfc3339(),
+            limit: 5000,
+            remaining,
+            reset_at: reset_at.to_string(),
+        }
+    }
+
+    #[test]
+    fn quota_points_merge_within_the_gap() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap();
+        let mut history = vec![];
+        push_quota_point(&mut history, quota_point(start, 4990, "13:00"), start);
+        // 同一窗口、间隔不足 QUOTA_POINT_MIN_GAP_SECS：覆盖上一个点
+        let soon = start + Duration::seconds(QUOTA_POINT_MIN_GAP_SECS - 1);
+        push_quota_point(&mut history, quota_point(soon, 4980, "13:00"), soon);
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].remaining, 4980);
+
+        // 间隔够长，或者配额窗口换了，都追加新点
+        let later = soon + Duration::seconds(QUOTA_POINT_MIN_GAP_SECS);
+        push_quota_point(&mut history, quota_point(later, 4970, "13:00"), later);
+        let reset = later + Duration::seconds(1);
+        push_quota_point(&mut history, quota_point(reset, 5000, "14:00"), reset);
+        let remaining: Vec<u64> = history.iter().map(|p| p.remaining).collect();
+        assert_eq!(remaining, [4980, 4970, 5000]);
+    }
+
+    #[test]
+    fn quota_history_is_capped() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let mut history = vec![];
+        for i in 0..QUOTA_HISTORY_LIMIT + 3 {
+            let at = start + Duration::minutes(i as i64 * 2);
+            push_quota_point(&mut history, quota_point(at, i as u64, "reset"), at);
+        }
+        assert_eq!(history.len(), QUOTA_HISTORY_LIMIT);
+        assert_eq!(history[0].remaining, 3);
+        assert_eq!(history.last().unwrap().remaining, (QUOTA_HISTORY_LIMIT + 2) as u64);
+    }
 }
@@ -48,6 +48,7 @@ export interface AssetMetadata {
   repo?: string
   alive: boolean            // true = alive, false = tombstoned
   tombstoneId?: string      // link to tombstone if dead
+  exempt?: boolean          // never tombstone when deleted (tests, fixtures, generated code)
   extra?: Record<string, any>
 }
 
@@ -393,6 +394,55 @@ async function indexGitHubRepo(repoUrl: string): Promise<AssetMetadata[]> {
   return assets
 }
 
+export interface MergeResult {
+  assets: AssetMetadata[]
+  added: number
+  updated: number
+  markedDead: number
+}
+
+/**
+ * Merge a fresh scan into the existing index, matched by location.
+ * Only scan-derived fields are refreshed; user-set fields (exempt, tags, tombstoneId, ...) survive.
+ * Existing assets inside the scanned scope that the scan no longer sees are marked dead, not dropped,
+ * so the menu bar app can tombstone them.
+ */
+function mergeIndex(
+  existing: AssetMetadata[],
+  fresh: AssetMetadata[],
+  inScope: (asset: AssetMetadata) => boolean,
+): MergeResult {
+  const freshByLocation = new Map(fresh.map(a => [a.location, a]))
+  let updated = 0
+  let markedDead = 0
+
+  const assets = existing.map(old => {
+    const found = freshByLocation.get(old.location)
+    if (found) {
+      freshByLocation.delete(old.location)
+      updated++
+      return {
+        ...old,
+        alive: true,
+        language: found.language,
+        lineCount: found.lineCount,
+        size: found.size,
+        hash: found.hash,
+        updatedAt: found.updatedAt,
+        indexedAt: found.indexedAt,
+      }
+    }
+    if (old.alive && inScope(old)) {
+      markedDead++
+      return { ...old, alive: false }
+    }
+    return old
+  })
+
+  const added = [...freshByLocation.values()]
+  return { assets: [...assets, ...added], added: added.length, updated, markedDead }
+}
+
 /**
  * Search assets
  */
@@ -504,6 +554,7 @@ export {
   indexFile,
   indexDirectory,
   indexGitHubRepo,
+  mergeIndex,
   searchAssets,
   getStats,
   listByType,
@@ -526,25 +577,27 @@ if (require.main === module) {
         }
 
         let assets: AssetMetadata[]
+        let inScope: (asset: AssetMetadata) => boolean
         if (target.includes('github.com') || target.includes('/')) {
           assets = await indexGitHubRepo(target)
+          const repos = new Set(assets.map(a => a.repo))
+          inScope = a => repos.has(a.repo)
         } else {
           const fullPath = path.resolve(target)
           console.log(`📂 索引 ${fullPath}...`)
           assets = indexDirectory(fullPath)
+          inScope = a => a.source === AssetSource.LOCAL && a.location.startsWith(fullPath + path.sep)
         }
 
-        // Merge with existing index
-        const existing = loadIndex()
-        const existingIds = new Set(existing.map(a => a.hash))
-        const newAssets = assets.filter(a => !existingIds.has(a.hash))
-        const merged = [...existing, ...newAssets]
-        saveIndex(merged)
+        // Merge with existing index (keeps exempt flags and other manual fields)
+        const result = mergeIndex(loadIndex(), assets, inScope)
+        saveIndex(result.assets)
 
         console.log(`\n📊 索引完成:`)
-        console.log(`   新增: ${newAssets.length}`)
-        console.log(`   跳过 (已存在): ${assets.length - newAssets.length}`)
-        console.log(`   总计: ${merged.length}`)
+        console.log(`   新增: ${result.added}`)
+        console.log(`   更新: ${result.updated}`)
+        console.log(`   标记死亡: ${result.markedDead}`)
+        console.log(`   总计: ${result.assets.length}`)
         break
       }
 